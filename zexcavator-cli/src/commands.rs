@@ -12,9 +12,14 @@
 
 mod export;
 mod parse;
+mod recover_password;
+mod repair_mnemonic;
+mod sync_args;
 
 use self::export::ExportCmd;
 use self::parse::ParseCmd;
+use self::recover_password::RecoverPasswordCmd;
+use self::repair_mnemonic::RepairMnemonicCmd;
 use crate::config::ZexCavatorCliConfig;
 use abscissa_core::{Command, Configurable, FrameworkError, Runnable, config::Override};
 use std::path::PathBuf;
@@ -31,6 +36,12 @@ pub enum ZexCavatorCliCmd {
 
     /// The `export` subcommand
     Export(ExportCmd),
+
+    /// The `recover-password` subcommand
+    RecoverPassword(RecoverPasswordCmd),
+
+    /// The `repair-mnemonic` subcommand
+    RepairMnemonic(RepairMnemonicCmd),
 }
 
 /// Entry point for the application. It needs to be a struct to allow using subcommands!
@@ -87,6 +98,8 @@ impl Configurable<ZexCavatorCliConfig> for EntryPoint {
         match &self.cmd {
             ZexCavatorCliCmd::Parse(cmd) => cmd.override_config(config),
             ZexCavatorCliCmd::Export(cmd) => cmd.override_config(config),
+            ZexCavatorCliCmd::RecoverPassword(cmd) => cmd.override_config(config),
+            ZexCavatorCliCmd::RepairMnemonic(cmd) => cmd.override_config(config),
         }
     }
 }
@@ -1,18 +1,25 @@
-//! `parse` subcommand - example of how to write a subcommand
+//! `export` subcommand - syncs a wallet file and exports it to ZeWIF
 
 use std::{path::PathBuf, str::FromStr};
 
-use crate::{config::ZexCavatorCliConfig, prelude::APP};
-use abscissa_core::{Application, Command, FrameworkError, Runnable, config};
-use bc_envelope::Envelope;
+use crate::prelude::*;
+
+use crate::config::ZexCavatorCliConfig;
+use abscissa_core::{Command, FrameworkError, Runnable, config};
+use zexcavator_lib::export::protect::Protection;
+use zexcavator_lib::export::{watch_only, write_zewif, zewif_envelope};
+use zexcavator_lib::parser::detect::WalletFormat;
+use zexcavator_lib::sync::{Syncer, new_log_buffer};
+
+use super::parse::{exit_code, read_wallet, wallet_secret};
+use super::sync_args::SyncArgs;
 
 /// `export` subcommand
 ///
-/// The `Parser` proc macro generates an option parser based on the struct
-/// definition, and is defined in the `clap` crate. See their documentation
-/// for a more comprehensive example:
-///
-/// <https://docs.rs/clap/>
+/// The wallet is synced first, so the export holds its transaction history. The
+/// org.zingolabs extension is attached to the ZeWIF envelope. The export can be
+/// encrypted with a password and split into SSKR shares, in that order. A watch-only
+/// export has its seed and spending keys elided.
 #[derive(clap::Parser, Command, Debug)]
 pub struct ExportCmd {
     /// A wallet file. Currently only ZecWallet and YWallet are supported.
@@ -22,34 +29,119 @@ pub struct ExportCmd {
     /// Where to save the ZeWIF file.
     #[arg(value_name = "OUTPUT_FILE")]
     output_file: Option<String>,
-}
 
-impl Runnable for ExportCmd {
-    /// Start the application.
-    fn run(&self) {
-        let config = APP.config();
+    /// Password of an encrypted wallet, or passphrase of an encrypted zcashd wallet. If
+    /// omitted, it is prompted for when needed.
+    #[arg(short('p'), long("password"))]
+    password: Option<String>,
+
+    /// Seed phrase of a wallet that doesn't store its seed, like a Zashi database. If
+    /// omitted, it is prompted for when needed.
+    #[arg(long("mnemonic"))]
+    mnemonic: Option<String>,
 
-        let _output = config.output_file.to_str().unwrap();
+    /// Wallet format, overriding the format detected from the file content: zwl,
+    /// zingolib, zcashd, zcashd-dump, ywallet, zashi or zewif
+    #[arg(long("format"))]
+    format: Option<WalletFormat>,
 
-        let erp_envelope =
-            Envelope::new("<seed>").add_assertion("generates", "<emergency_recovery_phrase>");
+    /// Read a damaged ZecWallet Lite file as far as possible, skipping the damaged
+    /// sections, e.g. to rescue its seed
+    #[arg(long("tolerant"))]
+    tolerant: bool,
 
-        let zc_object: Envelope = Envelope::new("wallet").add_assertion("hasSeed", erp_envelope);
+    /// Encrypt the export with this password
+    #[arg(long("export-password"))]
+    export_password: Option<String>,
 
-        // Extension version
-        let zc_extension =
-            zc_object.add_assertion(bc_envelope::known_values::VERSION_VALUE, "0.0.1");
+    /// Split the export into SSKR shares, e.g. `2-of-3` writes 3 files, any 2 of
+    /// which recover it
+    #[arg(long("shares"), value_parser = parse_shares)]
+    shares: Option<(usize, usize)>,
+
+    /// Elide the seed and spending keys, keeping viewing keys, addresses and history
+    #[arg(long("watch-only"))]
+    watch_only: bool,
+
+    #[command(flatten)]
+    sync: SyncArgs,
+}
+
+/// Parses a `<threshold>-of-<count>` share spec.
+fn parse_shares(spec: &str) -> Result<(usize, usize), String> {
+    let invalid = || format!("Invalid shares {}, expected e.g. 2-of-3", spec);
+    let (threshold, count) = spec.split_once("-of-").ok_or_else(invalid)?;
+    let threshold: usize = threshold.parse().map_err(|_| invalid())?;
+    let count: usize = count.parse().map_err(|_| invalid())?;
+    if threshold == 0 || threshold > count {
+        return Err(invalid());
+    }
+    Ok((threshold, count))
+}
+
+impl ExportCmd {
+    fn protection(&self) -> Vec<Protection> {
+        let mut protection = vec![];
+        if let Some(password) = &self.export_password {
+            protection.push(Protection::Password(password.clone()));
+        }
+        if let Some((threshold, count)) = self.shares {
+            protection.push(Protection::Shares { threshold, count });
+        }
+        protection
+    }
 
-        let sample_envelope = Envelope::new("Alice")
-            .add_assertion("Knows", "Bob")
-            .add_attachment(
-            zc_extension,
-            "org.zingolabs",
-            Some("https://github.com/zingolabs/zexcavator/blob/main/docs/zewif-extension-spec.md"),
+    fn export(&self, config: &ZexCavatorCliConfig) -> anyhow::Result<()> {
+        let input = config.input_file.to_str().unwrap();
+        let wallet_parser = read_wallet(input, self.format, self.tolerant)?;
+        for lost in wallet_parser.parser.get_lost_sections() {
+            status_warn!("Damaged wallet file, lost {}", lost);
+        }
+        let secret = wallet_secret(
+            &wallet_parser,
+            self.password.as_deref(),
+            self.mnemonic.as_deref(),
         );
 
-        println!("{:}", Envelope::format_flat(&sample_envelope));
-        todo!()
+        let log_buffer = new_log_buffer();
+        let syncer = Syncer::new_with_log(log_buffer.clone())
+            .with_settings(config.sync_settings()?)
+            .with_tolerant_read(self.tolerant);
+
+        let rt = tokio::runtime::Runtime::new()?;
+        let envelope = rt.block_on(async {
+            let lc = syncer
+                .start_wallet_sync_from_path(config.input_file.clone(), self.format, secret)
+                .await?;
+            let source = syncer.source_wallet.lock().unwrap().clone();
+            zewif_envelope(&lc, source.as_ref()).await
+        });
+
+        for line in log_buffer.lock().unwrap().iter() {
+            println!("{}", line);
+        }
+
+        let mut envelope = envelope?;
+        if self.watch_only {
+            envelope = watch_only::elide_secrets(&envelope);
+        }
+
+        for path in write_zewif(&envelope, &config.output_file, &self.protection())? {
+            println!("Exported to {}", path.display());
+        }
+        Ok(())
+    }
+}
+
+impl Runnable for ExportCmd {
+    /// Start the application.
+    fn run(&self) {
+        let config = APP.config();
+
+        if let Err(e) = self.export(&config) {
+            status_err!("Export failed: {}", e);
+            std::process::exit(exit_code(&e));
+        }
     }
 }
 
@@ -66,6 +158,7 @@ impl config::Override<ZexCavatorCliConfig> for ExportCmd {
         if let Some(output_file) = &self.output_file {
             config.output_file = PathBuf::from_str(output_file).unwrap();
         }
+        self.sync.override_config(&mut config)?;
 
         Ok(config)
     }
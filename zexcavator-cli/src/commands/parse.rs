@@ -6,14 +6,14 @@ use crate::prelude::*;
 
 use crate::config::ZexCavatorCliConfig;
 use abscissa_core::{Command, FrameworkError, Runnable, config};
-use http::Uri;
-use pepper_sync::sync::{SyncConfig, TransparentAddressDiscovery};
+use std::io::{self, BufRead, Write};
+use std::path::Path;
 use zexcavator_lib::parser::WalletParserFactory;
-use zingolib::{
-    config::{ChainType, load_clientconfig},
-    lightclient,
-    wallet::{LightWallet, WalletBase, WalletSettings},
-};
+use zexcavator_lib::parser::detect::{self, WalletFormat};
+use zexcavator_lib::parser::error::ParseError;
+use zexcavator_lib::sync::{Syncer, new_log_buffer};
+
+use super::sync_args::SyncArgs;
 
 /// `parse` subcommand
 ///
@@ -31,6 +31,90 @@ pub struct ParseCmd {
     /// Enable verbose mode. A flag `-v` or `--verbose` will enable verbose mode.
     #[arg(short('v'), long("verbose"))]
     verbose: bool,
+
+    /// Password of an encrypted wallet, or passphrase of an encrypted zcashd wallet. If
+    /// omitted, it is prompted for when needed.
+    #[arg(short('p'), long("password"))]
+    password: Option<String>,
+
+    /// Seed phrase of a wallet that doesn't store its seed, like a Zashi database. If
+    /// omitted, it is prompted for when needed.
+    #[arg(long("mnemonic"))]
+    mnemonic: Option<String>,
+
+    /// Wallet format, overriding the format detected from the file content: zwl,
+    /// zingolib, zcashd, zcashd-dump, ywallet, zashi or zewif
+    #[arg(long("format"))]
+    format: Option<WalletFormat>,
+
+    /// Read a damaged ZecWallet Lite file as far as possible, skipping the damaged
+    /// sections, e.g. to rescue its seed
+    #[arg(long("tolerant"))]
+    tolerant: bool,
+
+    #[command(flatten)]
+    sync: SyncArgs,
+}
+
+/// Reads a line from stdin after printing `message`.
+fn prompt(message: &str) -> String {
+    print!("{}", message);
+    io::stdout().flush().unwrap();
+
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line).unwrap();
+    line.trim_end_matches(['\r', '\n']).to_string()
+}
+
+/// Reads the wallet as `format`, or in the format detected from its content. With
+/// `tolerant`, damaged ZecWallet Lite files are read as far as possible.
+pub(super) fn read_wallet(
+    filename: &str,
+    format: Option<WalletFormat>,
+    tolerant: bool,
+) -> Result<WalletParserFactory, ParseError> {
+    let result = match format {
+        Some(format) if tolerant => WalletParserFactory::read_as_tolerant(filename, format),
+        Some(format) => WalletParserFactory::read_as(filename, format),
+        None => {
+            let candidates = detect::detect(Path::new(filename))?;
+            WalletParserFactory::read_detected(filename, &candidates, tolerant)
+        }
+    };
+    if !tolerant && matches!(result, Err(ParseError::Truncated { .. })) {
+        status_warn!("The wallet file is damaged, --tolerant reads what is left of it");
+    }
+    result
+}
+
+/// Process exit code of an error: wallet read errors have their own exit codes.
+pub(super) fn exit_code(e: &anyhow::Error) -> i32 {
+    e.downcast_ref::<ParseError>()
+        .map_or(1, ParseError::exit_code)
+}
+
+/// Secret opening the wallet: the seed phrase of a wallet that doesn't store it, or the
+/// password of an encrypted wallet. Prompted for when needed and not given.
+pub(super) fn wallet_secret(
+    wallet_parser: &WalletParserFactory,
+    password: Option<&str>,
+    mnemonic: Option<&str>,
+) -> Option<String> {
+    if wallet_parser.parser.requires_mnemonic() {
+        Some(
+            mnemonic
+                .map(str::to_string)
+                .unwrap_or_else(|| prompt("Wallet stores no seed. Seed phrase: ")),
+        )
+    } else if wallet_parser.parser.is_encrypted() {
+        Some(
+            password
+                .map(str::to_string)
+                .unwrap_or_else(|| prompt("Wallet is encrypted. Password: ")),
+        )
+    } else {
+        None
+    }
 }
 
 impl Runnable for ParseCmd {
@@ -38,70 +122,69 @@ impl Runnable for ParseCmd {
     fn run(&self) {
         let config = APP.config();
         println!("Config: {:#?}", config);
-        let wallet_parser = WalletParserFactory::read(config.input_file.to_str().unwrap()).unwrap();
+        let mut wallet_parser = match read_wallet(
+            config.input_file.to_str().unwrap(),
+            self.format,
+            self.tolerant,
+        ) {
+            Ok(wallet_parser) => wallet_parser,
+            Err(e) => {
+                status_err!("Could not read wallet: {}", e);
+                std::process::exit(e.exit_code());
+            }
+        };
+        println!("Wallet format: {}", wallet_parser.format);
+        for lost in wallet_parser.parser.get_lost_sections() {
+            status_warn!("Damaged wallet file, lost {}", lost);
+        }
+
+        let secret = wallet_secret(
+            &wallet_parser,
+            self.password.as_deref(),
+            self.mnemonic.as_deref(),
+        );
+
+        if let Err(e) = wallet_parser.unlock(secret.as_deref()) {
+            status_err!("Could not open wallet: {}", e);
+            std::process::exit(ParseError::from(e).exit_code());
+        }
 
         // println!("{:#?}", wallet_parser.parser.get_wallet_name());
         wallet_parser.parser.print_internal();
+        println!(
+            "Transactions in wallet file: {}",
+            wallet_parser.parser.get_transactions().len()
+        );
 
-        // LightClient initialization
+        // LightClient initialization and sync
 
-        let seed = wallet_parser.parser.get_wallet_seed();
-        let bd = wallet_parser.parser.get_birthday();
-
-        if let Err(e) = rustls::crypto::ring::default_provider().install_default() {
-            eprintln!("Error installing crypto provider: {:?}", e)
-        };
-
-        let rt = tokio::runtime::Runtime::new().unwrap();
-        let zc = match load_clientconfig(
-            Uri::from_static("https://na.zec.rocks:443"),
-            None,
-            ChainType::Mainnet,
-            WalletSettings {
-                sync_config: SyncConfig {
-                    transparent_address_discovery: TransparentAddressDiscovery::recovery(),
-                },
-            },
-        ) {
-            Ok(zc) => zc,
+        let log_buffer = new_log_buffer();
+        let settings = match config.sync_settings() {
+            Ok(settings) => settings,
             Err(e) => {
-                eprintln!("Error loading client config: {}", e);
-                return;
+                status_err!("Invalid sync settings: {}", e);
+                std::process::exit(exit_code(&e));
             }
         };
+        let syncer = Syncer::new_with_log(log_buffer.clone())
+            .with_settings(settings)
+            .with_tolerant_read(self.tolerant);
 
-        let initial_bh: u32 = bd.try_into().unwrap();
-        let lw = LightWallet::new(
-            ChainType::Mainnet,
-            WalletBase::SeedBytes(seed),
-            initial_bh.into(),
-            WalletSettings {
-                sync_config: SyncConfig {
-                    transparent_address_discovery: TransparentAddressDiscovery::recovery(),
-                },
-            },
-        )
-        .unwrap();
-
-        let mut lc = lightclient::LightClient::create_from_wallet(lw, zc, true).unwrap();
-        // let latest_block =
-        //     get_latest_block_height(config.lightwalletd_uri.read().unwrap().clone()).unwrap();
-
-        rt.block_on(async {
-            println!("Reading from birthday: {}", bd);
-            // println!("Upto block: {}", latest_block);
-            match lc.sync().await {
-                Ok(_) => {}
-                Err(e) => {
-                    println!("Error syncing: {}", e);
-                }
-            }
-
-            lc.await_sync().await.unwrap();
-            let balances = lc.do_balance().await;
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let result = rt.block_on(syncer.start_wallet_sync_from_path(
+            config.input_file.clone(),
+            self.format,
+            secret,
+        ));
+
+        for line in log_buffer.lock().unwrap().iter() {
+            println!("{}", line);
+        }
 
-            println!("Balances: {}", balances);
-        });
+        if let Err(e) = result {
+            status_err!("Sync failed: {}", e);
+            std::process::exit(exit_code(&e));
+        }
     }
 }
 
@@ -118,6 +201,7 @@ impl config::Override<ZexCavatorCliConfig> for ParseCmd {
         }
 
         config.verbose = self.verbose;
+        self.sync.override_config(&mut config)?;
 
         Ok(config)
     }
@@ -0,0 +1,142 @@
+//! `recover-password` subcommand - searches for the password of an encrypted
+//! ZecWallet Lite wallet
+
+use crate::prelude::*;
+
+use crate::config::ZexCavatorCliConfig;
+use abscissa_core::{Command, FrameworkError, Runnable, config};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use zexcavator_lib::recovery::password::{
+    Mask, MutationRule, PasswordRecovery, RecoveryResult, read_wordlist,
+};
+
+/// `recover-password` subcommand
+///
+/// Runs fully offline against the wallet file given on the command line. Ctrl-C stops
+/// the search after the current batch, which is saved to the checkpoint if one is
+/// given.
+#[derive(clap::Parser, Command, Debug)]
+pub struct RecoverPasswordCmd {
+    /// Encrypted ZecWallet Lite wallet file
+    #[arg(required = true)]
+    wallet_path: String,
+
+    /// Wordlist file, one candidate word per line
+    #[arg(short('w'), long("wordlist"))]
+    wordlist: Option<PathBuf>,
+
+    /// Comma separated mutation rules applied to every word:
+    /// none, lower, upper, capitalize, reverse, leet
+    #[arg(short('r'), long("rules"), value_delimiter(','))]
+    rules: Vec<String>,
+
+    /// Shape of the candidates, e.g. `?w?d?d`. Defaults to `?w`
+    #[arg(short('m'), long("mask"))]
+    mask: Option<String>,
+
+    /// File where progress is saved, and resumed from if it exists
+    #[arg(short('c'), long("checkpoint"))]
+    checkpoint: Option<PathBuf>,
+
+    /// Number of worker threads. Defaults to one per CPU core
+    #[arg(short('t'), long("threads"))]
+    threads: Option<usize>,
+}
+
+impl RecoverPasswordCmd {
+    fn recovery(&self, wallet_path: &Path) -> Result<PasswordRecovery, String> {
+        let rules = self
+            .rules
+            .iter()
+            .map(|r| r.parse::<MutationRule>())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mask = match &self.mask {
+            Some(mask) => mask.parse::<Mask>()?,
+            None => Mask::default(),
+        };
+
+        let wordlist = match &self.wordlist {
+            Some(path) => read_wordlist(path).map_err(|e| format!("Can't read wordlist: {}", e))?,
+            None => vec![],
+        };
+
+        let mut recovery = PasswordRecovery::new(wallet_path, &wordlist, &rules, &mask)
+            .map_err(|e| e.to_string())?;
+        if let Some(checkpoint) = &self.checkpoint {
+            recovery = recovery.with_checkpoint(checkpoint.clone());
+        }
+        if let Some(threads) = self.threads {
+            recovery = recovery.with_threads(threads);
+        }
+
+        Ok(recovery)
+    }
+}
+
+impl Runnable for RecoverPasswordCmd {
+    fn run(&self) {
+        let config = APP.config();
+
+        let recovery = match self.recovery(&config.input_file) {
+            Ok(recovery) => recovery,
+            Err(e) => {
+                status_err!("{}", e);
+                std::process::exit(1);
+            }
+        };
+
+        println!("Testing {} candidates", recovery.total());
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let rt = match tokio::runtime::Runtime::new() {
+            Ok(rt) => rt,
+            Err(e) => {
+                status_err!("{}", e);
+                std::process::exit(1);
+            }
+        };
+        let ctrl_c = Arc::clone(&stop);
+        rt.spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                eprintln!("Stopping after the current batch");
+                ctrl_c.store(true, Ordering::Relaxed);
+            }
+        });
+
+        let result = recovery.run(&stop, |next, total| {
+            eprintln!("Progress: {}/{}", next, total);
+        });
+
+        match result {
+            Ok(RecoveryResult::Found(password)) => println!("Password found: {}", password),
+            Ok(RecoveryResult::Exhausted) => println!("Password not found"),
+            Ok(RecoveryResult::Stopped) => match &self.checkpoint {
+                Some(checkpoint) => println!(
+                    "Search stopped, run again with --checkpoint {} to resume",
+                    checkpoint.display()
+                ),
+                None => println!("Search stopped"),
+            },
+            Err(e) => {
+                status_err!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+impl config::Override<ZexCavatorCliConfig> for RecoverPasswordCmd {
+    fn override_config(
+        &self,
+        mut config: ZexCavatorCliConfig,
+    ) -> Result<ZexCavatorCliConfig, FrameworkError> {
+        if !self.wallet_path.is_empty() {
+            config.input_file = self.wallet_path.to_string().into();
+        }
+
+        Ok(config)
+    }
+}
@@ -0,0 +1,108 @@
+//! `repair-mnemonic` subcommand - finds the phrases a damaged BIP-39 mnemonic
+//! could have been
+
+use crate::prelude::*;
+
+use crate::config::ZexCavatorCliConfig;
+use abscissa_core::{Command, FrameworkError, Runnable, config};
+use zexcavator_lib::recovery::mnemonic::{
+    RepairOptions, addresses_from_wallet, filter_by_addresses, repair,
+};
+use zexcavator_lib::settings::Network;
+
+/// `repair-mnemonic` subcommand
+///
+/// Unknown words are written as `?`. Runs fully offline.
+#[derive(clap::Parser, Command, Debug)]
+pub struct RepairMnemonicCmd {
+    /// Damaged phrase, quoted, e.g. "abandon ? abandon ..."
+    #[arg(required = true)]
+    phrase: String,
+
+    /// Wallet file whose addresses the repaired phrase must derive
+    #[arg(short('w'), long("wallet"))]
+    wallet: Option<String>,
+
+    /// Comma separated addresses the repaired phrase must derive
+    #[arg(short('a'), long("address"), value_delimiter(','))]
+    addresses: Vec<String>,
+
+    /// Number of addresses derived per candidate when checking addresses
+    #[arg(short('d'), long("depth"), default_value_t = 5)]
+    depth: u32,
+
+    /// Network the addresses belong to. Defaults to the network of the wallet file,
+    /// or mainnet
+    #[arg(short('n'), long("network"))]
+    network: Option<Network>,
+
+    /// Maximum edit distance of a misspelled word
+    #[arg(long("max-distance"), default_value_t = 2)]
+    max_distance: usize,
+
+    /// Don't try swapping adjacent words
+    #[arg(long("no-swaps"))]
+    no_swaps: bool,
+}
+
+impl RepairMnemonicCmd {
+    fn candidates(&self) -> Result<Vec<String>, String> {
+        let options = RepairOptions {
+            max_distance: self.max_distance,
+            try_swaps: !self.no_swaps,
+        };
+        let phrases = repair(&self.phrase, &options).map_err(|e| e.to_string())?;
+
+        let mut network = self.network.unwrap_or_default();
+        let mut addresses = self.addresses.clone();
+        if let Some(wallet) = &self.wallet {
+            let (wallet_network, wallet_addresses) = addresses_from_wallet(wallet)
+                .map_err(|e| format!("Can't read wallet addresses: {}", e))?;
+            network = self.network.unwrap_or(wallet_network);
+            addresses.extend(wallet_addresses);
+        }
+
+        if addresses.is_empty() {
+            Ok(phrases)
+        } else {
+            Ok(filter_by_addresses(
+                phrases, &addresses, self.depth, network,
+            ))
+        }
+    }
+}
+
+impl Runnable for RepairMnemonicCmd {
+    fn run(&self) {
+        let phrases = match self.candidates() {
+            Ok(phrases) => phrases,
+            Err(e) => {
+                status_err!("{}", e);
+                std::process::exit(1);
+            }
+        };
+
+        if phrases.is_empty() {
+            println!("No valid phrase found");
+            return;
+        }
+
+        println!("Found {} candidate phrases:", phrases.len());
+        for phrase in phrases {
+            println!("{}", phrase);
+        }
+    }
+}
+
+impl config::Override<ZexCavatorCliConfig> for RepairMnemonicCmd {
+    fn override_config(
+        &self,
+        mut config: ZexCavatorCliConfig,
+    ) -> Result<ZexCavatorCliConfig, FrameworkError> {
+        if let Some(wallet) = &self.wallet {
+            config.input_file = wallet.into();
+        }
+
+        Ok(config)
+    }
+}
@@ -0,0 +1,64 @@
+//! Command line flags selecting the network and lightwalletd servers used to sync
+
+use abscissa_core::{FrameworkError, FrameworkErrorKind};
+use zexcavator_lib::settings::{Network, SyncSettings};
+
+use crate::config::ZexCavatorCliConfig;
+
+/// Sync flags, overriding the `sync` section of the config file
+#[derive(clap::Args, Debug)]
+pub struct SyncArgs {
+    /// Network of the wallet: mainnet, testnet or regtest
+    #[arg(short('n'), long("network"))]
+    network: Option<Network>,
+
+    /// Lightwalletd server, e.g. `https://na.zec.rocks:443`
+    #[arg(short('s'), long("server"))]
+    server: Option<String>,
+
+    /// Comma separated servers tried in order when syncing keeps failing
+    #[arg(long("fallback-servers"), value_delimiter(','))]
+    fallback_servers: Vec<String>,
+
+    /// Consecutive sync failures before moving to the next server
+    #[arg(long("max-sync-retries"))]
+    max_sync_retries: Option<u32>,
+}
+
+impl SyncArgs {
+    /// Applies the flags on top of the sync settings of `config`.
+    pub fn override_config(&self, config: &mut ZexCavatorCliConfig) -> Result<(), FrameworkError> {
+        let config_error =
+            |e: String| -> FrameworkError { FrameworkErrorKind::ConfigError.context(e).into() };
+
+        let mut settings = config
+            .sync_settings()
+            .map_err(|e| config_error(e.to_string()))?;
+
+        if let Some(network) = self.network {
+            // Servers of another network can't be reused
+            if network != settings.network {
+                settings = SyncSettings {
+                    max_sync_retries: settings.max_sync_retries,
+                    ..SyncSettings::for_network(network)
+                };
+            }
+        }
+        if let Some(server) = &self.server {
+            settings.server = server.clone();
+        }
+        if !self.fallback_servers.is_empty() {
+            settings.fallback_servers = self.fallback_servers.clone();
+        }
+        if let Some(max_sync_retries) = self.max_sync_retries {
+            settings.max_sync_retries = max_sync_retries;
+        }
+
+        settings
+            .validate()
+            .map_err(|e| config_error(e.to_string()))?;
+        config.sync = Some(settings);
+
+        Ok(())
+    }
+}
@@ -7,6 +7,7 @@
 use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
+use zexcavator_lib::settings::SyncSettings;
 
 const DEFAULT_OUTPUT_FILE: &str = "export.zewif";
 
@@ -22,6 +23,10 @@ pub struct ZexCavatorCliConfig {
 
     /// verbose mode
     pub verbose: bool,
+
+    /// Network and lightwalletd servers. Defaults to the shared settings file
+    #[serde(default)]
+    pub sync: Option<SyncSettings>,
 }
 
 /// Default configuration settings.
@@ -31,6 +36,17 @@ impl Default for ZexCavatorCliConfig {
             input_file: String::from("").into(),
             output_file: String::from(DEFAULT_OUTPUT_FILE).into(),
             verbose: false,
+            sync: None,
+        }
+    }
+}
+
+impl ZexCavatorCliConfig {
+    /// Sync settings from this config, or from the settings file shared with the TUI.
+    pub fn sync_settings(&self) -> anyhow::Result<SyncSettings> {
+        match &self.sync {
+            Some(settings) => Ok(settings.clone()),
+            None => SyncSettings::load(),
         }
     }
 }
@@ -0,0 +1,197 @@
+//! # Export
+//!
+//! Writes a recovered wallet to disk, either as a zingolib wallet file or as a
+//! ZeWIF envelope. Exports are saved under `<config_dir>/zexcavator/exports`.
+//!
+//! The ZeWIF document is built by [`convert`] from the synced wallet and the wallet
+//! file the sync started from. The seed and the data ZeWIF has no field for are
+//! attached as the org.zingolabs [`extension`]. Exports can be password protected or
+//! split into SSKR shares, see [`protect`], and made [`watch_only`] by eliding their
+//! secrets.
+
+pub mod convert;
+pub mod extension;
+pub mod protect;
+pub mod watch_only;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use bc_envelope::Envelope;
+use bc_envelope::prelude::{CBOREncodable, CBORTaggedDecodable};
+use chrono::Utc;
+use zingolib::grpc_connector::get_latest_block;
+use zingolib::lightclient::LightClient;
+
+use crate::parser::Wallet;
+use extension::ZingolabsExtension;
+use protect::Protection;
+
+/// Returns the directory where exports are saved, creating it if needed.
+fn export_dir(subdir: Option<&str>) -> anyhow::Result<PathBuf> {
+    let mut export_dir = dirs::config_dir().context("could not locate config directory")?;
+    export_dir.push("zexcavator");
+    export_dir.push("exports");
+    if let Some(subdir) = subdir {
+        export_dir.push(subdir);
+    }
+
+    fs::create_dir_all(&export_dir)
+        .with_context(|| format!("failed to create directory {:?}", export_dir))?;
+
+    Ok(export_dir)
+}
+
+/// Serializes the LightWallet into a Zingolib-compatible file and saves it to disk
+pub async fn export_to_zingolib(lc: &LightClient) -> anyhow::Result<PathBuf> {
+    let mut buf = Vec::new();
+    {
+        let mut lw_guard = lc.wallet.lock().await;
+        let network = lw_guard.network;
+        lw_guard
+            .write(&mut buf, &network)
+            .context("failed to serialize LightWallet")?;
+    }
+
+    let filename = format!("export-{}.dat", Utc::now().timestamp());
+    let path = export_dir(Some("zingolib"))?.join(&filename);
+
+    fs::write(&path, &buf).context("failed to write export file")?;
+
+    Ok(path)
+}
+
+/// Builds the ZeWIF envelope of the LightWallet, and of the wallet file it was synced
+/// from if any, with the org.zingolabs extension attached
+pub async fn zewif_envelope(lc: &LightClient, source: Option<&Wallet>) -> anyhow::Result<Envelope> {
+    let export_height = get_latest_block(lc.get_server_uri())
+        .await
+        .map_err(|e| anyhow::anyhow!("failed to fetch latest block: {}", e))?
+        .height as u32;
+
+    let zewif = convert::build_zewif(lc, source, export_height).await?;
+
+    let extension = lc
+        .wallet
+        .lock()
+        .await
+        .mnemonic()
+        .map(|mnemonic| ZingolabsExtension {
+            seed: mnemonic.to_seed("").to_vec(),
+            recovery_phrase: mnemonic.phrase().to_string(),
+            metadata: source.map(|w| w.metadata.clone()).unwrap_or_default(),
+        });
+
+    let envelope = Envelope::from(zewif);
+    Ok(match extension {
+        Some(extension) => extension.attach(envelope),
+        None => envelope,
+    })
+}
+
+/// Converts the LightWallet, and the wallet file it was synced from if any, into a
+/// ZeWIF-compatible format and saves it to disk. A `watch_only` export has its seed
+/// material and spending keys elided. Returns the written files.
+pub async fn save_zewif(
+    lc: &LightClient,
+    source: Option<&Wallet>,
+    watch_only: bool,
+    protection: &[Protection],
+) -> anyhow::Result<Vec<PathBuf>> {
+    let mut envelope = zewif_envelope(lc, source).await?;
+    if watch_only {
+        envelope = watch_only::elide_secrets(&envelope);
+    }
+    export_to_zewif(&envelope, protection)
+}
+
+/// Saves a ZeWIF envelope to `zexcavator-<timestamp>.zewif` in the exports directory
+pub fn export_to_zewif(
+    envelope: &Envelope,
+    protection: &[Protection],
+) -> anyhow::Result<Vec<PathBuf>> {
+    let timestamp = Utc::now().format("%Y%m%d_%H%M%S").to_string();
+
+    let filename = format!("zexcavator-{}.zewif", timestamp);
+    let path = export_dir(None)?.join(filename);
+
+    write_zewif(envelope, &path, protection)
+}
+
+/// Saves a ZeWIF envelope to `path`, applying `protection` in order. Shares are
+/// written next to `path`, as `<name>.share-<n>-of-<count>.zewif`. Returns the
+/// written files.
+pub fn write_zewif(
+    envelope: &Envelope,
+    path: &Path,
+    protection: &[Protection],
+) -> anyhow::Result<Vec<PathBuf>> {
+    let mut envelopes = vec![(path.to_path_buf(), envelope.clone())];
+    for protection in protection {
+        envelopes = match protection {
+            Protection::Password(password) => envelopes
+                .into_iter()
+                .map(|(path, envelope)| Ok((path, protect::lock(&envelope, password)?)))
+                .collect::<anyhow::Result<_>>()?,
+            Protection::Shares { threshold, count } => {
+                let mut shares = vec![];
+                for (path, envelope) in envelopes {
+                    for (i, share) in protect::split(&envelope, *threshold, *count)?
+                        .into_iter()
+                        .enumerate()
+                    {
+                        shares.push((share_path(&path, i + 1, *count), share));
+                    }
+                }
+                shares
+            }
+        };
+    }
+
+    let mut paths = vec![];
+    for (path, envelope) in envelopes {
+        fs::write(&path, envelope.to_cbor_data())
+            .with_context(|| format!("failed to write export file {:?}", path))?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+fn share_path(path: &Path, n: usize, count: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{}.share-{}-of-{}.zewif", stem, n, count))
+}
+
+/// Reads a ZeWIF envelope saved by [`write_zewif`]. When `path` is a share, the
+/// other `.zewif` files of its directory are searched for the shares needed to
+/// recombine the export. A password protected envelope is returned still locked.
+pub fn read_zewif(path: &Path) -> anyhow::Result<Envelope> {
+    let envelope = read_envelope(path)?;
+    if !protect::is_share(&envelope) {
+        return Ok(envelope);
+    }
+
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let mut shares = vec![envelope];
+    for entry in fs::read_dir(dir).with_context(|| format!("failed to read {:?}", dir))? {
+        let other = entry?.path();
+        if other == path || other.extension().is_none_or(|ext| ext != "zewif") {
+            continue;
+        }
+        match read_envelope(&other) {
+            Ok(share) if protect::is_share(&share) => shares.push(share),
+            _ => (),
+        }
+    }
+
+    protect::join(&shares)
+}
+
+fn read_envelope(path: &Path) -> anyhow::Result<Envelope> {
+    let data = fs::read(path).with_context(|| format!("failed to read {:?}", path))?;
+    Envelope::try_from_cbor_data(data).with_context(|| format!("invalid ZeWIF file {:?}", path))
+}
@@ -0,0 +1,639 @@
+//! # ZeWIF conversion
+//!
+//! Builds a [`Zewif`] document out of a synced [`LightClient`], together with the
+//! wallet file the sync started from when there is one. The document holds:
+//!
+//! - **Seed material**: the mnemonic of the synced wallet.
+//! - **Accounts**: one per HD account. Addresses read from the wallet file carry
+//!   their spending or viewing keys and their derivation path. The unified and
+//!   transparent addresses discovered by the sync are added next to them.
+//! - **Imported keys**: keys not derived from the seed go into a separate account,
+//!   without a ZIP 32 account index.
+//! - **Transactions**: every transaction known to the synced wallet, with its raw
+//!   bytes and mined height. Received notes are stored as outputs of the
+//!   transaction and sent notes as sent outputs of the account, both with their
+//!   memos. Transactions of the wallet file the sync didn't find are added too,
+//!   without raw bytes, with the notes, payments and memos the wallet file stored.
+//!   Payments to transparent addresses have no ZeWIF sent output and are left out.
+//! - **Birthday**: the birthday height of the synced wallet, set on every account.
+//!
+//! Orchard spending keys read from a wallet file can't be represented by ZeWIF
+//! yet, only the addresses and derivation paths of orchard keys are exported.
+
+use std::collections::{BTreeMap, HashSet};
+
+use pepper_sync::wallet::{NoteInterface, OutputInterface, WalletTransaction};
+use zcash_keys::address::Address as KeysAddress;
+use zcash_primitives::consensus::NetworkConstants;
+use zcash_primitives::memo::MemoBytes;
+use zewif::orchard::{OrchardActionDescription, OrchardSentOutput};
+use zewif::sapling::{
+    SaplingAddress, SaplingExtendedSpendingKey, SaplingIncomingViewingKey,
+    SaplingOutputDescription, SaplingSentOutput,
+};
+use zewif::{
+    Account, Address, Amount, Bip39Mnemonic, BlockHeight, Data, DerivationInfo,
+    NonHardenedChildIndex, ProtocolAddress, SeedMaterial, Transaction, TransparentSpendAuthority,
+    TxId, UnifiedAddress, Zewif, ZewifWallet, transparent,
+};
+use zingolib::lightclient::LightClient;
+use zingolib::wallet::LightWallet;
+
+use crate::parser::{
+    Wallet, WalletAccount, WalletKeyType, WalletOKey, WalletPool, WalletTKey,
+    WalletTransaction as SourceTransaction, WalletZKey, zip32_path,
+};
+use crate::settings::Network;
+
+/// Name of the account holding the keys imported into the wallet file.
+const IMPORTED_ACCOUNT_NAME: &str = "Imported keys";
+
+/// ZeWIF network of a wallet network.
+fn zewif_network(network: Network) -> zewif::Network {
+    match network {
+        Network::Mainnet => zewif::Network::Main,
+        Network::Testnet => zewif::Network::Test,
+        Network::Regtest => zewif::Network::Regtest,
+    }
+}
+
+fn child_index(index: u32) -> NonHardenedChildIndex {
+    NonHardenedChildIndex::from(index)
+}
+
+fn amount(zatoshis: u64) -> anyhow::Result<Amount> {
+    Amount::try_from(zatoshis).map_err(|e| anyhow::anyhow!("invalid amount {}: {:?}", zatoshis, e))
+}
+
+fn memo_data(memo: &MemoBytes) -> Data {
+    Data::from_slice(memo.as_slice())
+}
+
+/// Builds the ZeWIF document of the wallet synced by `lc`. `source` is the wallet
+/// file the sync started from, if any.
+pub async fn build_zewif(
+    lc: &LightClient,
+    source: Option<&Wallet>,
+    export_height: u32,
+) -> anyhow::Result<Zewif> {
+    let lw = lc.wallet.lock().await;
+    zewif_of_wallet(&lw, source, export_height)
+}
+
+/// Builds the ZeWIF document of a synced wallet. See [`build_zewif`].
+fn zewif_of_wallet(
+    lw: &LightWallet,
+    source: Option<&Wallet>,
+    export_height: u32,
+) -> anyhow::Result<Zewif> {
+    let network = Network::from_chain_type(&lw.network);
+
+    let mut wallet = ZewifWallet::new(zewif_network(network));
+    match lw.mnemonic() {
+        Some(mnemonic) => {
+            wallet.set_seed_material(SeedMaterial::Bip39Mnemonic(Bip39Mnemonic::new(
+                mnemonic.clone().into_phrase(),
+                Some(zewif::MnemonicLanguage::English),
+            )))
+        }
+        None => anyhow::bail!("no seed material"),
+    }
+
+    let birthday = BlockHeight::from_u32(u32::from(lw.birthday));
+
+    // HD accounts, indexed by their ZIP 32 account index
+    let mut accounts: BTreeMap<u32, Account> = BTreeMap::new();
+    let source_accounts = source.map(|w| w.accounts.as_slice()).unwrap_or_default();
+    for (index, source_account) in source_accounts.iter().enumerate() {
+        let account = accounts
+            .entry(index as u32)
+            .or_insert_with(|| new_account(&source_account.name, Some(index as u32), birthday));
+        add_source_keys(account, source_account, network);
+    }
+    add_synced_addresses(lw, &mut accounts, birthday, network);
+
+    let mut zewif = Zewif::new(BlockHeight::from_u32(export_height));
+    for transaction in lw.wallet_transactions.values() {
+        let (tx, accounts_involved) = convert_transaction(transaction, &mut accounts)?;
+        for account in accounts_involved {
+            if let Some(account) = accounts.get_mut(&account) {
+                account.add_relevant_transaction(tx.txid());
+            }
+        }
+        zewif.add_transaction(tx.txid(), tx);
+    }
+
+    let synced: HashSet<[u8; 32]> = lw
+        .wallet_transactions
+        .values()
+        .map(|transaction| *transaction.txid().as_ref())
+        .collect();
+    let source_transactions = source
+        .map(|w| w.transactions.as_slice())
+        .unwrap_or_default();
+    // Account whose notes each transaction of the wallet file spent
+    let senders: BTreeMap<[u8; 32], u32> = source_transactions
+        .iter()
+        .flat_map(|transaction| transaction.received.iter())
+        .filter_map(|note| Some((*note.spent_in?.as_ref(), note.account? as u32)))
+        .collect();
+    for transaction in source_transactions {
+        if synced.contains(transaction.txid.as_ref()) {
+            continue;
+        }
+        let sender = senders.get(transaction.txid.as_ref()).copied();
+        let (tx, accounts_involved) =
+            convert_source_transaction(transaction, sender, &mut accounts, network)?;
+        for account in accounts_involved {
+            if let Some(account) = accounts.get_mut(&account) {
+                account.add_relevant_transaction(tx.txid());
+            }
+        }
+        zewif.add_transaction(tx.txid(), tx);
+    }
+
+    for account in accounts.into_values() {
+        wallet.add_account(account);
+    }
+
+    match source {
+        Some(source) if !source.imported_keys.is_empty() => {
+            let mut imported = new_account(IMPORTED_ACCOUNT_NAME, None, birthday);
+            for tkey in &source.imported_keys.tkeys {
+                imported.add_address(transparent_address(tkey));
+            }
+            for zkey in &source.imported_keys.zkeys {
+                for address in sapling_addresses(zkey, network) {
+                    imported.add_address(address);
+                }
+            }
+            for okey in &source.imported_keys.okeys {
+                for address in orchard_addresses(okey, network) {
+                    imported.add_address(address);
+                }
+            }
+            wallet.add_account(imported);
+        }
+        _ => (),
+    }
+
+    zewif.add_wallet(wallet);
+    Ok(zewif)
+}
+
+fn new_account(name: &str, zip32_account_id: Option<u32>, birthday: BlockHeight) -> Account {
+    let mut account = Account::new();
+    account.set_name(name);
+    if let Some(id) = zip32_account_id {
+        account.set_zip32_account_id(id);
+    }
+    account.set_birthday_height(birthday);
+    account
+}
+
+/// Adds the addresses read from the wallet file, with their keys.
+fn add_source_keys(account: &mut Account, source: &WalletAccount, network: Network) {
+    for tkey in &source.keys.tkeys {
+        account.add_address(transparent_address(tkey));
+    }
+    for zkey in &source.keys.zkeys {
+        for address in sapling_addresses(zkey, network) {
+            account.add_address(address);
+        }
+    }
+    for okey in &source.keys.okeys {
+        for address in orchard_addresses(okey, network) {
+            account.add_address(address);
+        }
+    }
+}
+
+fn transparent_address(tkey: &WalletTKey) -> Address {
+    let mut address = transparent::Address::new(tkey.address.clone());
+    address.set_spend_authority(TransparentSpendAuthority::SpendingKey(
+        tkey.pk.secret_bytes().into(),
+    ));
+    if let WalletKeyType::HdDerived = tkey.key_type {
+        address.set_derivation_info(DerivationInfo::new(
+            child_index(tkey.scope as u32),
+            child_index(tkey.index),
+        ));
+    }
+
+    Address::new(ProtocolAddress::Transparent(address))
+}
+
+/// ZIP 32 path of an HD shielded key, as read from the wallet or derived from its index.
+fn hd_derivation_path(
+    key_type: &WalletKeyType,
+    derivation_path: Option<&str>,
+    index: u32,
+    network: Network,
+) -> Option<String> {
+    match key_type {
+        WalletKeyType::HdDerived => Some(
+            derivation_path
+                .map(str::to_string)
+                .unwrap_or_else(|| zip32_path(network.network_type().coin_type(), index)),
+        ),
+        WalletKeyType::Imported => None,
+    }
+}
+
+/// The default and diversified addresses of a sapling key.
+fn sapling_addresses(zkey: &WalletZKey, network: Network) -> Vec<Address> {
+    let path = hd_derivation_path(
+        &zkey.key_type,
+        zkey.derivation_path.as_deref(),
+        zkey.index,
+        network,
+    );
+    std::iter::once(&zkey.address)
+        .chain(zkey.diversified_addresses.iter())
+        .map(|encoded| {
+            let mut address = SaplingAddress::new(encoded.clone());
+            address.set_incoming_viewing_key(SaplingIncomingViewingKey::from(
+                zkey.fvk.fvk.vk.ivk().to_repr(),
+            ));
+            if let Some(extsk) = &zkey.extsk {
+                address.set_spending_key(SaplingExtendedSpendingKey::from(extsk.to_bytes()));
+            }
+            if let Some(path) = &path {
+                address.set_hd_derivation_path(path.clone());
+            }
+            Address::new(ProtocolAddress::Sapling(Box::new(address)))
+        })
+        .collect()
+}
+
+/// The default and diversified addresses of an orchard key.
+fn orchard_addresses(okey: &WalletOKey, network: Network) -> Vec<Address> {
+    let path = hd_derivation_path(
+        &okey.key_type,
+        okey.derivation_path.as_deref(),
+        okey.index,
+        network,
+    );
+    std::iter::once(&okey.address)
+        .chain(okey.diversified_addresses.iter())
+        .map(|encoded| {
+            let mut address = UnifiedAddress::new(encoded.clone());
+            if let Some(path) = &path {
+                address.set_hd_derivation_path(path.clone());
+            }
+            Address::new(ProtocolAddress::Unified(Box::new(address)))
+        })
+        .collect()
+}
+
+/// Adds the unified and transparent addresses discovered by the sync, skipping
+/// addresses already read from the wallet file.
+fn add_synced_addresses(
+    lw: &LightWallet,
+    accounts: &mut BTreeMap<u32, Account>,
+    birthday: BlockHeight,
+    network: Network,
+) {
+    let network_type = network.network_type();
+
+    for (id, ua) in lw.unified_addresses.iter() {
+        let encoded = ua.to_address(network_type).to_string();
+        let account = account_entry(accounts, id.account_id, birthday);
+        if account.addresses().contains_key(&encoded) {
+            continue;
+        }
+        let mut address = UnifiedAddress::new(encoded);
+        address.set_hd_derivation_path(zip32_path(
+            network_type.coin_type(),
+            u32::from(id.account_id),
+        ));
+        account.add_address(Address::new(ProtocolAddress::Unified(Box::new(address))));
+    }
+
+    for (id, encoded) in lw.transparent_addresses.iter() {
+        let account = account_entry(accounts, id.account_id(), birthday);
+        if account.addresses().contains_key(encoded) {
+            continue;
+        }
+        let mut address = transparent::Address::new(encoded.clone());
+        address.set_derivation_info(DerivationInfo::new(
+            child_index(id.scope() as u32),
+            child_index(id.address_index().index()),
+        ));
+        account.add_address(Address::new(ProtocolAddress::Transparent(address)));
+    }
+}
+
+/// Account of a synced address, created if the wallet file didn't have it.
+fn account_entry(
+    accounts: &mut BTreeMap<u32, Account>,
+    id: zip32::AccountId,
+    birthday: BlockHeight,
+) -> &mut Account {
+    let index = u32::from(id);
+    accounts
+        .entry(index)
+        .or_insert_with(|| new_account(&format!("Account {}", index + 1), Some(index), birthday))
+}
+
+/// Converts a synced transaction, adding its sent notes to their account. Returns the
+/// transaction and the accounts it involves.
+fn convert_transaction(
+    transaction: &WalletTransaction,
+    accounts: &mut BTreeMap<u32, Account>,
+) -> anyhow::Result<(Transaction, Vec<u32>)> {
+    let txid = TxId::from_bytes(*transaction.txid().as_ref());
+    let mut tx = Transaction::new(txid);
+    let mut involved = vec![];
+
+    let mut raw = vec![];
+    transaction
+        .transaction()
+        .write(&mut raw)
+        .map_err(|e| anyhow::anyhow!("failed to serialize transaction {}: {}", txid, e))?;
+    tx.set_raw(Data::from_vec(raw));
+    if let Some(height) = transaction.status().get_confirmed_height() {
+        tx.set_mined_height(BlockHeight::from_u32(u32::from(height)));
+    }
+
+    for note in transaction.sapling_notes() {
+        let mut output = SaplingOutputDescription::new();
+        output.set_output_index(note.output_id().output_index() as u32);
+        output.set_value(amount(note.value())?);
+        output.set_memo(Some(memo_data(&MemoBytes::from(note.memo()))));
+        tx.add_sapling_output(output);
+        involved.push(u32::from(note.key_id().account_id));
+    }
+
+    for note in transaction.orchard_notes() {
+        let mut action = OrchardActionDescription::new();
+        action.set_action_index(note.output_id().output_index() as u32);
+        action.set_value(amount(note.value())?);
+        action.set_memo(Some(memo_data(&MemoBytes::from(note.memo()))));
+        tx.add_orchard_action(action);
+        involved.push(u32::from(note.key_id().account_id));
+    }
+
+    for coin in transaction.transparent_coins() {
+        involved.push(u32::from(coin.key_id().account_id()));
+    }
+
+    for note in transaction.outgoing_sapling_notes() {
+        let account_id = u32::from(note.key_id().account_id);
+        let mut sent = SaplingSentOutput::new();
+        sent.set_recipient_address(note.encoded_recipient_full_unified_address_or_address());
+        sent.set_value(amount(note.value())?);
+        sent.set_memo(Some(memo_data(&MemoBytes::from(note.memo()))));
+        if let Some(account) = accounts.get_mut(&account_id) {
+            account.add_sapling_sent_output(sent);
+        }
+        involved.push(account_id);
+    }
+
+    for note in transaction.outgoing_orchard_notes() {
+        let account_id = u32::from(note.key_id().account_id);
+        let mut sent = OrchardSentOutput::new();
+        sent.set_recipient_address(note.encoded_recipient_full_unified_address_or_address());
+        sent.set_value(amount(note.value())?);
+        sent.set_memo(Some(memo_data(&MemoBytes::from(note.memo()))));
+        if let Some(account) = accounts.get_mut(&account_id) {
+            account.add_orchard_sent_output(sent);
+        }
+        involved.push(account_id);
+    }
+
+    involved.sort_unstable();
+    involved.dedup();
+    Ok((tx, involved))
+}
+
+/// Shielded pool a payment to `address` went to. Payments to a unified address are
+/// taken to go to its orchard receiver, as the wallets sending them prefer orchard.
+fn payment_pool(address: &str, network: Network) -> Option<WalletPool> {
+    match KeysAddress::decode(&network.params(), address)? {
+        KeysAddress::Sapling(_) => Some(WalletPool::Sapling),
+        KeysAddress::Unified(ua) if ua.orchard().is_some() => Some(WalletPool::Orchard),
+        KeysAddress::Unified(ua) if ua.sapling().is_some() => Some(WalletPool::Sapling),
+        _ => Some(WalletPool::Transparent),
+    }
+}
+
+/// Converts a transaction read from the wallet file, whose raw bytes are unknown,
+/// adding its shielded payments to their account as sent outputs. `sender` is the
+/// account whose notes the transaction spent, for payments stored without their
+/// account. Returns the transaction and the accounts it involves.
+fn convert_source_transaction(
+    transaction: &SourceTransaction,
+    sender: Option<u32>,
+    accounts: &mut BTreeMap<u32, Account>,
+    network: Network,
+) -> anyhow::Result<(Transaction, Vec<u32>)> {
+    let mut tx = Transaction::new(TxId::from_bytes(*transaction.txid.as_ref()));
+    if let Some(height) = transaction.height {
+        tx.set_mined_height(BlockHeight::from_u32(u32::from(height)));
+    }
+
+    for note in &transaction.received {
+        let memo = note.memo.as_ref().map(|m| memo_data(&MemoBytes::from(m)));
+        match note.pool {
+            WalletPool::Sapling => {
+                let mut output = SaplingOutputDescription::new();
+                if let Some(index) = note.output_index {
+                    output.set_output_index(index);
+                }
+                output.set_value(amount(note.value)?);
+                output.set_memo(memo);
+                tx.add_sapling_output(output);
+            }
+            WalletPool::Orchard => {
+                let mut action = OrchardActionDescription::new();
+                if let Some(index) = note.output_index {
+                    action.set_action_index(index);
+                }
+                action.set_value(amount(note.value)?);
+                action.set_memo(memo);
+                tx.add_orchard_action(action);
+            }
+            WalletPool::Transparent => (),
+        }
+    }
+
+    let mut involved: Vec<u32> = transaction
+        .received
+        .iter()
+        .filter_map(|n| n.account)
+        .map(|a| a as u32)
+        .collect();
+
+    for payment in &transaction.sent {
+        let Some(account_id) = payment.account.map(|a| a as u32).or(sender) else {
+            continue;
+        };
+        involved.push(account_id);
+        let Some(account) = accounts.get_mut(&account_id) else {
+            continue;
+        };
+        let memo = payment
+            .memo
+            .as_ref()
+            .map(|m| memo_data(&MemoBytes::from(m)));
+        match payment_pool(&payment.address, network) {
+            Some(WalletPool::Sapling) => {
+                let mut sent = SaplingSentOutput::new();
+                sent.set_recipient_address(payment.address.clone());
+                sent.set_value(amount(payment.value)?);
+                sent.set_memo(memo);
+                account.add_sapling_sent_output(sent);
+            }
+            Some(WalletPool::Orchard) => {
+                let mut sent = OrchardSentOutput::new();
+                sent.set_recipient_address(payment.address.clone());
+                sent.set_value(amount(payment.value)?);
+                sent.set_memo(memo);
+                account.add_orchard_sent_output(sent);
+            }
+            _ => (),
+        }
+    }
+
+    involved.sort_unstable();
+    involved.dedup();
+    Ok((tx, involved))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::num::NonZero;
+    use std::str::FromStr;
+
+    use bip0039::{English, Mnemonic};
+    use zcash_primitives::consensus::BlockHeight as ChainHeight;
+    use zcash_primitives::memo::Memo;
+    use zcash_primitives::transaction::TxId as ChainTxId;
+    use zingolib::wallet::WalletBase;
+
+    use super::*;
+    use crate::parser::zwl::ZwlWallet;
+    use crate::parser::{WalletNote, WalletPayment};
+    use crate::sync::Syncer;
+
+    const PHRASE: &str = "clerk family rack dragon cannon wait vendor penalty absent country better coast expand true middle stable assist clerk tent phone toilet knee female kitchen";
+
+    fn test_wallet(phrase: &str, birthday: u32) -> LightWallet {
+        LightWallet::new(
+            Network::Mainnet.chain_type(),
+            WalletBase::Mnemonic {
+                mnemonic: Mnemonic::<English>::from_str(phrase).unwrap(),
+                no_of_accounts: NonZero::<u32>::MIN,
+            },
+            birthday.into(),
+            Syncer::wallet_settings(1),
+        )
+        .unwrap()
+    }
+
+    /// The ZecWallet Lite wallet restored from `phrase`.
+    fn test_source(phrase: &str) -> Wallet {
+        ZwlWallet::from_seed_phrase(phrase, 1, Network::Mainnet).unwrap()
+    }
+
+    /// ZeWIF document of a wallet restored from `phrase` at `birthday`, with the keys
+    /// of the ZecWallet Lite wallet restored from the same phrase as its wallet file.
+    pub(crate) fn test_zewif(phrase: &str, birthday: u32) -> Zewif {
+        let lw = test_wallet(phrase, birthday);
+        zewif_of_wallet(&lw, Some(&test_source(phrase)), birthday + 1000).unwrap()
+    }
+
+    fn source_transaction(txid: u8, height: u32) -> SourceTransaction {
+        SourceTransaction {
+            txid: ChainTxId::from_bytes([txid; 32]),
+            height: Some(ChainHeight::from_u32(height)),
+            timestamp: 0,
+            received: vec![],
+            sent: vec![],
+            net_value: 0,
+            zec_price: None,
+        }
+    }
+
+    #[test]
+    fn test_source_payments() {
+        let lw = test_wallet(PHRASE, 2_000_000);
+        let mut source = test_source(PHRASE);
+        let keys = &source.accounts[0].keys;
+        let zaddress = keys.zkeys[0].address.clone();
+        let taddress = keys.tkeys[0].address.clone();
+        let uaddress = lw
+            .unified_addresses
+            .values()
+            .next()
+            .unwrap()
+            .to_address(Network::Mainnet.network_type())
+            .to_string();
+
+        // A note received by the first account, spent by a transaction whose
+        // payments were stored without their account
+        let mut receive = source_transaction(1, 2_000_100);
+        receive.received.push(WalletNote {
+            pool: WalletPool::Sapling,
+            account: Some(0),
+            output_index: Some(0),
+            address: Some(zaddress.clone()),
+            value: 100_000,
+            memo: None,
+            is_change: false,
+            spent: true,
+            spent_in: Some(ChainTxId::from_bytes([2; 32])),
+        });
+        let mut send = source_transaction(2, 2_000_200);
+        for (address, value) in [
+            (&zaddress, 10_000),
+            (&uaddress, 20_000),
+            (&taddress, 30_000),
+        ] {
+            send.sent.push(WalletPayment {
+                account: None,
+                address: address.clone(),
+                value,
+                memo: Some(Memo::from_str("invoice 42").unwrap()),
+            });
+        }
+        source.transactions = vec![receive, send];
+
+        let zewif = zewif_of_wallet(&lw, Some(&source), 2_001_000).unwrap();
+        let account = &zewif.wallets().first().unwrap().accounts()[0];
+        assert_eq!(account.relevant_transactions().len(), 2);
+
+        let sapling = account.sapling_sent_outputs();
+        assert_eq!(sapling.len(), 1);
+        assert_eq!(sapling[0].recipient_address().to_string(), zaddress);
+        assert_eq!(sapling[0].value(), amount(10_000).unwrap());
+        assert!(sapling[0].memo().is_some());
+
+        let orchard = account.orchard_sent_outputs();
+        assert_eq!(orchard.len(), 1);
+        assert_eq!(orchard[0].recipient_address().to_string(), uaddress);
+        assert_eq!(orchard[0].value(), amount(20_000).unwrap());
+        assert!(orchard[0].memo().is_some());
+    }
+
+    #[test]
+    fn test_payment_pool() {
+        let lw = test_wallet(PHRASE, 2_000_000);
+        let source = test_source(PHRASE);
+        let keys = &source.accounts[0].keys;
+        let uaddress = lw
+            .unified_addresses
+            .values()
+            .next()
+            .unwrap()
+            .to_address(Network::Mainnet.network_type())
+            .to_string();
+
+        let pool = |address: &str| payment_pool(address, Network::Mainnet);
+        assert_eq!(pool(&keys.zkeys[0].address), Some(WalletPool::Sapling));
+        assert_eq!(pool(&uaddress), Some(WalletPool::Orchard));
+        assert_eq!(pool(&keys.tkeys[0].address), Some(WalletPool::Transparent));
+        assert_eq!(pool("not an address"), None);
+    }
+}
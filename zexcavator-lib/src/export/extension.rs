@@ -0,0 +1,258 @@
+//! # org.zingolabs ZeWIF extension
+//!
+//! Vendor attachment carrying the data ZeWIF has no field for, as described in
+//! `docs/zewif-extension-spec.md`:
+//!
+//! ```text
+//! {
+//!     "wallet" [
+//!         "hasSeed": <seed> [
+//!             "generates": "<emergency_recovery_phrase>"
+//!         ]
+//!         "walletOptions": "options" [
+//!             "downloadMemos": "WalletMemos"
+//!             "spamThreshold": -1
+//!         ]
+//!         "priceInfo": "price" [
+//!             "currency": "USD"
+//!             "lastHistoricalPricesFetchedAt": 1700000000
+//!             "historicalPricesRetryCount": 0
+//!         ]
+//!         'version': "0.0.2"
+//!     ]
+//! } [
+//!     'conformsTo': "https://github.com/zingolabs/zexcavator/blob/main/docs/zewif-extension-spec.md"
+//!     'vendor': "org.zingolabs"
+//! ]
+//! ```
+//!
+//! `walletOptions` and `priceInfo` are only present for ZecWallet Lite wallets.
+
+use bc_envelope::prelude::*;
+
+/// Vendor of the attachment.
+pub const VENDOR: &str = "org.zingolabs";
+/// Specification the attachment conforms to.
+pub const CONFORMS_TO: &str =
+    "https://github.com/zingolabs/zexcavator/blob/main/docs/zewif-extension-spec.md";
+/// Version of the specification written by this exporter.
+pub const EXTENSION_VERSION: &str = "0.0.2";
+
+const WALLET: &str = "wallet";
+const HAS_SEED: &str = "hasSeed";
+const GENERATES: &str = "generates";
+const WALLET_OPTIONS: &str = "walletOptions";
+const DOWNLOAD_MEMOS: &str = "downloadMemos";
+const SPAM_THRESHOLD: &str = "spamThreshold";
+const PRICE_INFO: &str = "priceInfo";
+const CURRENCY: &str = "currency";
+const LAST_HISTORICAL_PRICES_FETCHED_AT: &str = "lastHistoricalPricesFetchedAt";
+const HISTORICAL_PRICES_RETRY_COUNT: &str = "historicalPricesRetryCount";
+
+/// ZecWallet Lite `WalletOptions`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalletOptionsInfo {
+    /// Memo download policy: `NoMemos`, `WalletMemos` or `AllMemos`.
+    pub download_memos: String,
+    pub spam_threshold: i64,
+}
+
+/// ZecWallet Lite `WalletZecPriceInfo`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PriceInfo {
+    pub currency: String,
+    pub last_historical_prices_fetched_at: Option<u64>,
+    pub historical_prices_retry_count: u64,
+}
+
+/// Format specific data of a wallet file, with no ZeWIF equivalent.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceMetadata {
+    pub wallet_options: Option<WalletOptionsInfo>,
+    pub price_info: Option<PriceInfo>,
+}
+
+/// Content of the org.zingolabs attachment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZingolabsExtension {
+    /// Binary seed of the wallet, the BIP-39 seed of the recovery phrase.
+    pub seed: Vec<u8>,
+    /// Emergency recovery phrase generated from the seed.
+    pub recovery_phrase: String,
+    pub metadata: SourceMetadata,
+}
+
+/// Parses a `major.minor.patch` version, so versions can be compared.
+fn parse_version(version: &str) -> anyhow::Result<(u64, u64, u64)> {
+    let parts = version
+        .split('.')
+        .map(|p| p.parse::<u64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| anyhow::anyhow!("invalid extension version {}", version))?;
+    match parts.as_slice() {
+        [major, minor, patch] => Ok((*major, *minor, *patch)),
+        _ => anyhow::bail!("invalid extension version {}", version),
+    }
+}
+
+impl ZingolabsExtension {
+    /// Payload of the attachment.
+    pub fn to_envelope(&self) -> Envelope {
+        let seed = Envelope::new(ByteString::from(self.seed.clone()))
+            .add_assertion(GENERATES, self.recovery_phrase.clone());
+
+        let mut wallet = Envelope::new(WALLET).add_assertion(HAS_SEED, seed);
+
+        if let Some(options) = &self.metadata.wallet_options {
+            wallet = wallet.add_assertion(
+                WALLET_OPTIONS,
+                Envelope::new("options")
+                    .add_assertion(DOWNLOAD_MEMOS, options.download_memos.clone())
+                    .add_assertion(SPAM_THRESHOLD, options.spam_threshold),
+            );
+        }
+
+        if let Some(price) = &self.metadata.price_info {
+            let mut price_envelope = Envelope::new("price")
+                .add_assertion(CURRENCY, price.currency.clone())
+                .add_assertion(
+                    HISTORICAL_PRICES_RETRY_COUNT,
+                    price.historical_prices_retry_count,
+                );
+            if let Some(fetched_at) = price.last_historical_prices_fetched_at {
+                price_envelope =
+                    price_envelope.add_assertion(LAST_HISTORICAL_PRICES_FETCHED_AT, fetched_at);
+            }
+            wallet = wallet.add_assertion(PRICE_INFO, price_envelope);
+        }
+
+        wallet.add_assertion(known_values::VERSION_VALUE, EXTENSION_VERSION)
+    }
+
+    /// Reads the attachment payload written by [`Self::to_envelope`]. Payloads of a
+    /// newer version of the specification are rejected, unknown fields are ignored.
+    pub fn from_payload(payload: &Envelope) -> anyhow::Result<Self> {
+        let subject: String = payload.extract_subject()?;
+        if subject != WALLET {
+            anyhow::bail!("unexpected extension subject {}", subject);
+        }
+
+        let version: String = payload.extract_object_for_predicate(known_values::VERSION_VALUE)?;
+        if parse_version(&version)? > parse_version(EXTENSION_VERSION)? {
+            anyhow::bail!(
+                "extension version {} is newer than the supported version {}",
+                version,
+                EXTENSION_VERSION
+            );
+        }
+
+        let seed_envelope = payload.object_for_predicate(HAS_SEED)?;
+        let seed: ByteString = seed_envelope.extract_subject()?;
+        let recovery_phrase: String = seed_envelope.extract_object_for_predicate(GENERATES)?;
+
+        let wallet_options = match payload.optional_object_for_predicate(WALLET_OPTIONS)? {
+            Some(options) => Some(WalletOptionsInfo {
+                download_memos: options.extract_object_for_predicate(DOWNLOAD_MEMOS)?,
+                spam_threshold: options.extract_object_for_predicate(SPAM_THRESHOLD)?,
+            }),
+            None => None,
+        };
+
+        let price_info = match payload.optional_object_for_predicate(PRICE_INFO)? {
+            Some(price) => Some(PriceInfo {
+                currency: price.extract_object_for_predicate(CURRENCY)?,
+                last_historical_prices_fetched_at: price
+                    .extract_optional_object_for_predicate(LAST_HISTORICAL_PRICES_FETCHED_AT)?,
+                historical_prices_retry_count: price
+                    .extract_object_for_predicate(HISTORICAL_PRICES_RETRY_COUNT)?,
+            }),
+            None => None,
+        };
+
+        Ok(Self {
+            seed: seed.into(),
+            recovery_phrase,
+            metadata: SourceMetadata {
+                wallet_options,
+                price_info,
+            },
+        })
+    }
+
+    /// Attaches the extension to `envelope`.
+    pub fn attach(&self, envelope: Envelope) -> Envelope {
+        envelope.add_attachment(self.to_envelope(), VENDOR, Some(CONFORMS_TO))
+    }
+
+    /// Reads the extension attached to `envelope`, if any.
+    ///
+    /// Fails if an org.zingolabs attachment doesn't conform to this specification.
+    pub fn from_attachments(envelope: &Envelope) -> anyhow::Result<Option<Self>> {
+        for attachment in envelope.attachments()? {
+            if attachment.attachment_vendor()? != VENDOR {
+                continue;
+            }
+
+            let conforms_to = attachment.attachment_conforms_to()?;
+            if conforms_to.as_deref() != Some(CONFORMS_TO) {
+                anyhow::bail!(
+                    "{} attachment conforms to {}, expected {}",
+                    VENDOR,
+                    conforms_to.unwrap_or_else(|| "nothing".to_string()),
+                    CONFORMS_TO
+                );
+            }
+
+            return Self::from_payload(&attachment.attachment_payload()?).map(Some);
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extension_roundtrip() {
+        let extension = ZingolabsExtension {
+            seed: vec![7; 32],
+            recovery_phrase: "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about".to_string(),
+            metadata: SourceMetadata {
+                wallet_options: Some(WalletOptionsInfo {
+                    download_memos: "WalletMemos".to_string(),
+                    spam_threshold: -1,
+                }),
+                price_info: Some(PriceInfo {
+                    currency: "USD".to_string(),
+                    last_historical_prices_fetched_at: None,
+                    historical_prices_retry_count: 2,
+                }),
+            },
+        };
+
+        let envelope = extension.attach(Envelope::new("export"));
+        let parsed = ZingolabsExtension::from_attachments(&envelope)
+            .unwrap()
+            .unwrap();
+        assert_eq!(parsed, extension);
+
+        // Attachments of other vendors are ignored
+        let envelope =
+            Envelope::new("export").add_attachment(extension.to_envelope(), "com.example", None);
+        assert!(
+            ZingolabsExtension::from_attachments(&envelope)
+                .unwrap()
+                .is_none()
+        );
+
+        // Our attachment must conform to our specification
+        let envelope = Envelope::new("export").add_attachment(
+            extension.to_envelope(),
+            VENDOR,
+            Some("https://example.com/spec"),
+        );
+        assert!(ZingolabsExtension::from_attachments(&envelope).is_err());
+    }
+}
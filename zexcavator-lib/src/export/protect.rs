@@ -0,0 +1,118 @@
+//! # ZeWIF protection
+//!
+//! ZeWIF exports hold the wallet seed, so they can be protected before being written:
+//!
+//! - **Password**: the envelope is wrapped and encrypted with a key derived from the
+//!   password with Argon2id, so its attachments are encrypted too.
+//! - **SSKR shares**: the envelope is wrapped, encrypted with a random key, and the
+//!   key is split into `count` shares, any `threshold` of which recover it. Each
+//!   share is written to its own file.
+//!
+//! Both can be combined: a password protected envelope can be split into shares.
+
+use bc_components::{KeyDerivationMethod, SSKRGroupSpec, SSKRSpec, SymmetricKey};
+use bc_envelope::prelude::*;
+
+/// How a ZeWIF export is protected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Protection {
+    /// Encrypted with a password derived key.
+    Password(String),
+    /// Split into `count` SSKR shares, `threshold` of which are needed to recombine it.
+    Shares { threshold: usize, count: usize },
+}
+
+/// Encrypts `envelope` with a key derived from `password`.
+pub fn lock(envelope: &Envelope, password: &str) -> anyhow::Result<Envelope> {
+    envelope
+        .wrap_envelope()
+        .lock_subject(KeyDerivationMethod::Argon2id, password)
+        .map_err(|e| anyhow::anyhow!("failed to encrypt export: {}", e))
+}
+
+/// Whether `envelope` was encrypted by [`lock`].
+pub fn is_locked(envelope: &Envelope) -> bool {
+    envelope.is_locked_with_password()
+}
+
+/// Decrypts an envelope encrypted by [`lock`].
+pub fn unlock(envelope: &Envelope, password: &str) -> anyhow::Result<Envelope> {
+    envelope
+        .unlock_subject(password)
+        .map_err(|_| anyhow::anyhow!("wrong password"))?
+        .unwrap_envelope()
+        .map_err(|e| anyhow::anyhow!("invalid encrypted export: {}", e))
+}
+
+/// Splits `envelope` into `count` shares, `threshold` of which recombine it.
+pub fn split(envelope: &Envelope, threshold: usize, count: usize) -> anyhow::Result<Vec<Envelope>> {
+    if threshold == 0 || threshold > count {
+        anyhow::bail!(
+            "invalid share threshold {} of {}, it must be between 1 and the share count",
+            threshold,
+            count
+        );
+    }
+
+    let group = SSKRGroupSpec::new(threshold, count)
+        .map_err(|e| anyhow::anyhow!("invalid share count: {}", e))?;
+    let spec =
+        SSKRSpec::new(1, vec![group]).map_err(|e| anyhow::anyhow!("invalid share spec: {}", e))?;
+
+    let groups = envelope
+        .wrap_envelope()
+        .sskr_split(&spec, &SymmetricKey::new())
+        .map_err(|e| anyhow::anyhow!("failed to split export: {}", e))?;
+
+    Ok(groups.into_iter().flatten().collect())
+}
+
+/// Whether `envelope` is a share written by [`split`].
+pub fn is_share(envelope: &Envelope) -> bool {
+    !envelope
+        .assertions_with_predicate(known_values::SSKR_SHARE)
+        .is_empty()
+}
+
+/// Recombines the envelope split by [`split`]. Shares of other exports are ignored.
+pub fn join(shares: &[Envelope]) -> anyhow::Result<Envelope> {
+    let shares: Vec<&Envelope> = shares.iter().collect();
+    Envelope::sskr_join(&shares)
+        .map_err(|e| anyhow::anyhow!("failed to recombine shares, not enough shares? {}", e))?
+        .unwrap_envelope()
+        .map_err(|e| anyhow::anyhow!("invalid recombined export: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn export() -> Envelope {
+        Envelope::new("wallet").add_assertion("hasSeed", "seed")
+    }
+
+    #[test]
+    fn test_lock_unlock() {
+        let locked = lock(&export(), "hunter2").unwrap();
+        assert!(is_locked(&locked));
+        assert!(unlock(&locked, "wrong").is_err());
+        assert!(
+            unlock(&locked, "hunter2")
+                .unwrap()
+                .is_identical_to(&export())
+        );
+    }
+
+    #[test]
+    fn test_split_join() {
+        let shares = split(&export(), 2, 3).unwrap();
+        assert_eq!(shares.len(), 3);
+        assert!(shares.iter().all(is_share));
+
+        assert!(join(&shares[..1]).is_err());
+        let joined = join(&[shares[0].clone(), shares[2].clone()]).unwrap();
+        assert!(joined.is_identical_to(&export()));
+
+        assert!(split(&export(), 4, 3).is_err());
+    }
+}
@@ -0,0 +1,163 @@
+//! # Watch-only ZeWIF exports
+//!
+//! Removes spend authority from a ZeWIF envelope, so the wallet structure can be
+//! handed to an accountant or auditor. The assertions holding secrets are elided:
+//!
+//! - **Seed material**: the wallet mnemonic, and the seed and recovery phrase of the
+//!   org.zingolabs extension.
+//! - **Spending keys**: transparent secret keys, sapling extended spending keys and
+//!   orchard spending keys.
+//!
+//! Viewing keys, addresses, derivation paths and the transaction history are kept.
+//! Elision keeps the digest of every removed assertion, so the watch-only export has
+//! the same digest as the full export it was made from.
+//!
+//! A watch-only export can't be synced, as it holds no seed.
+
+use std::collections::HashSet;
+
+use bc_envelope::prelude::*;
+
+/// Predicates of the assertions holding seed material or spending keys.
+const SECRET_PREDICATES: &[&str] = &[
+    // ZeWIF
+    "seedMaterial",
+    "spendAuthority",
+    "spendingKey",
+    // org.zingolabs extension
+    "hasSeed",
+];
+
+/// Collects the digests of the assertions of `envelope` holding secrets, descending
+/// into wrapped subjects and assertion objects.
+fn collect_secrets(envelope: &Envelope, secrets: &mut HashSet<Digest>) {
+    if let Ok(inner) = envelope.subject().unwrap_envelope() {
+        collect_secrets(&inner, secrets);
+    }
+
+    for assertion in envelope.assertions() {
+        let predicate: Option<String> = assertion
+            .as_predicate()
+            .and_then(|predicate| predicate.extract_subject().ok());
+        match predicate {
+            Some(predicate) if SECRET_PREDICATES.contains(&predicate.as_str()) => {
+                secrets.insert(assertion.digest().into_owned());
+            }
+            _ => {
+                if let Some(object) = assertion.as_object() {
+                    collect_secrets(&object, secrets);
+                }
+            }
+        }
+    }
+}
+
+/// Elides the seed material and spending keys of a ZeWIF envelope.
+pub fn elide_secrets(envelope: &Envelope) -> Envelope {
+    let mut secrets = HashSet::new();
+    collect_secrets(envelope, &mut secrets);
+    envelope.elide_removing_set(&secrets)
+}
+
+/// Whether secrets were elided from `envelope` by [`elide_secrets`].
+pub fn is_watch_only(envelope: &Envelope) -> bool {
+    fn has_elided(envelope: &Envelope) -> bool {
+        if envelope.is_elided() {
+            return true;
+        }
+        if let Ok(inner) = envelope.subject().unwrap_envelope() {
+            if has_elided(&inner) {
+                return true;
+            }
+        }
+        envelope.assertions().iter().any(|assertion| {
+            assertion.is_elided() || assertion.as_object().is_some_and(|o| has_elided(&o))
+        })
+    }
+    has_elided(envelope)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::convert::tests::test_zewif;
+    use crate::parser::zwl::ZwlWallet;
+    use crate::settings::Network;
+
+    const PHRASE: &str = "clerk family rack dragon cannon wait vendor penalty absent country better coast expand true middle stable assist clerk tent phone toilet knee female kitchen";
+
+    fn contains(data: &[u8], secret: &[u8]) -> bool {
+        data.windows(secret.len()).any(|window| window == secret)
+    }
+
+    fn export() -> Envelope {
+        let address = Envelope::new("t1address")
+            .add_assertion("spendAuthority", "secret key")
+            .add_assertion("derivationInfo", "m/44'/133'/0'/0/0");
+        let account = Envelope::new("account")
+            .add_assertion("address", address)
+            .add_assertion("incomingViewingKey", "ivk");
+        let seed = Envelope::new("seed").add_assertion("generates", "phrase");
+        let extension = Envelope::new("wallet").add_assertion("hasSeed", seed);
+
+        Envelope::new("wallet")
+            .add_assertion("seedMaterial", "mnemonic")
+            .add_assertion("account", account)
+            .add_attachment(extension, "org.zingolabs", None)
+    }
+
+    #[test]
+    fn test_elide_secrets() {
+        let full = export();
+        let watch_only = elide_secrets(&full);
+
+        // The digest tree still verifies against the full export
+        assert!(watch_only.is_equivalent_to(&full));
+        assert!(!watch_only.is_identical_to(&full));
+        assert!(is_watch_only(&watch_only));
+        assert!(!is_watch_only(&full));
+
+        let format = watch_only.format();
+        for secret in ["secret key", "mnemonic", "phrase"] {
+            assert!(!format.contains(secret), "{} was not elided", secret);
+        }
+        for public in ["t1address", "m/44'/133'/0'/0/0", "ivk"] {
+            assert!(format.contains(public), "{} was elided", public);
+        }
+    }
+
+    #[test]
+    fn test_elide_zewif_secrets() {
+        let full = Envelope::from(test_zewif(PHRASE, 2_000_000));
+        let watch_only = elide_secrets(&full);
+        assert!(watch_only.is_equivalent_to(&full));
+        assert!(is_watch_only(&watch_only));
+
+        let source = ZwlWallet::from_seed_phrase(PHRASE, 1, Network::Mainnet).unwrap();
+        let keys = &source.accounts[0].keys;
+        let mut secrets = vec![PHRASE.as_bytes().to_vec()];
+        secrets.extend(keys.tkeys.iter().map(|k| k.pk.secret_bytes().to_vec()));
+        secrets.extend(
+            keys.zkeys
+                .iter()
+                .filter_map(|k| k.extsk.as_ref())
+                .map(|k| k.to_bytes().to_vec()),
+        );
+        assert!(!keys.tkeys.is_empty() && !keys.zkeys.is_empty());
+
+        let full_data = full.to_cbor_data();
+        let watch_only_data = watch_only.to_cbor_data();
+        for secret in &secrets {
+            assert!(
+                contains(&full_data, secret),
+                "{} not exported",
+                hex::encode(secret)
+            );
+            assert!(
+                !contains(&watch_only_data, secret),
+                "{} was not elided",
+                hex::encode(secret)
+            );
+        }
+    }
+}
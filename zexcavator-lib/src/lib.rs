@@ -0,0 +1,19 @@
+//! # ZExCavator
+//!
+//! Recovery engine behind the ZExCavator TUI and CLI. It parses wallet files into a
+//! wallet-neutral in-memory representation, syncs the recovered keys using
+//! `zingolib`/`pepper-sync` and exports the result.
+//!
+//! - [`parser`]: wallet file parsers and the common wallet model.
+//! - [`settings`]: network and lightwalletd servers used to sync.
+//! - [`sync`]: fund recovery through a `zingolib` [`LightClient`](zingolib::lightclient::LightClient).
+//! - [`export`]: ZeWIF and zingolib exporters.
+//! - [`recovery`]: offline password and mnemonic recovery tools.
+//! - [`sweep`]: sends every recovered fund to a single address.
+
+pub mod export;
+pub mod parser;
+pub mod recovery;
+pub mod settings;
+pub mod sweep;
+pub mod sync;
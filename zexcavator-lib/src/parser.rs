@@ -0,0 +1,515 @@
+//! # Wallet Parsers
+//!
+//! Common, wallet-neutral representation of a wallet, plus the traits every
+//! wallet parser implements. Each supported wallet format lives in its own
+//! submodule.
+
+pub mod detect;
+pub mod error;
+pub mod ywallet;
+pub mod zashi;
+pub mod zcashd;
+pub mod zewif;
+pub mod zingolib;
+pub mod zwl;
+
+use std::io;
+use std::path::Path;
+
+use orchard_old::keys::{FullViewingKey, SpendingKey};
+use sapling::zip32::{ExtendedFullViewingKey, ExtendedSpendingKey};
+use secp256k1::{PublicKey, Secp256k1};
+use zcash_keys::keys::UnifiedFullViewingKey;
+use zcash_primitives::consensus::{BlockHeight, NetworkConstants};
+use zcash_primitives::legacy::keys::{AccountPrivKey, NonHardenedChildIndex};
+use zcash_primitives::memo::Memo;
+use zcash_primitives::transaction::TxId;
+use zcash_primitives::zip32::{AccountId, ChildIndex};
+
+use self::zewif::ZewifFile;
+use detect::{FormatCandidate, WalletFormat};
+use error::{LostSection, ParseError};
+use ywallet::YWallet;
+use zashi::ZashiWallet;
+use zcashd::ZcashdWallet;
+use zcashd::dump::ZcashdDump;
+use zingolib::Zingolib;
+use zwl::ZwlWallet;
+
+use crate::export::extension::SourceMetadata;
+use crate::settings::Network;
+
+#[derive(Debug, Clone)]
+pub enum WalletKeyType {
+    // HdKey = 0, // For HD drevied keys
+    // ImportedExtsk = 1, // For imported sapling extended spending key
+    // ImportedSpendingKey = 2, // For imported orchard spending key
+    // ImportedViewKey = 3, // For imported sapling viewing key
+    // ImportedFvk = 4, // For imported orchard full viewing key
+    // ImportedUfvk = 5, // For imported unified full viewing key
+    // ImportedPrivateKey = 6, // For imported transparent private key
+    HdDerived = 0,
+    Imported = 1,
+}
+
+/// Whether an address is given out to receive funds or used for change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum KeyScope {
+    #[default]
+    External,
+    Internal,
+}
+
+impl std::fmt::Display for KeyScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::External => write!(f, "external"),
+            Self::Internal => write!(f, "internal"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WalletTKey {
+    pub pk: secp256k1::SecretKey,
+    pub key_type: WalletKeyType,
+    pub index: u32,
+    pub address: String,
+    pub scope: KeyScope,
+    /// BIP 32 path of the key, e.g. `m/44'/133'/0'/0/1`, if it is known.
+    pub derivation_path: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct WalletZKey {
+    pub extsk: Option<ExtendedSpendingKey>,
+    pub fvk: ExtendedFullViewingKey,
+    pub key_type: WalletKeyType,
+    pub index: u32,
+    /// Default address of the key.
+    pub address: String,
+    /// Other addresses of the key the wallet gave out, at other diversifier indices.
+    pub diversified_addresses: Vec<String>,
+    pub scope: KeyScope,
+    /// ZIP 32 path of the key, e.g. `m/32'/133'/0'`, if it is known.
+    pub derivation_path: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct WalletOKey {
+    pub sk: Option<SpendingKey>,
+    pub fvk: Option<FullViewingKey>,
+    pub key_type: WalletKeyType,
+    pub index: u32,
+    /// Default address of the key.
+    pub address: String,
+    /// Other addresses of the key the wallet gave out, at other diversifier indices.
+    pub diversified_addresses: Vec<String>,
+    pub scope: KeyScope,
+    /// ZIP 32 path of the key, e.g. `m/32'/133'/0'`, if it is known.
+    pub derivation_path: Option<String>,
+}
+
+impl WalletZKey {
+    /// Sapling-only UFVK for this key, used to scan keys that are not derived from the seed.
+    pub fn to_ufvk(&self) -> Option<UnifiedFullViewingKey> {
+        UnifiedFullViewingKey::from_sapling_extended_full_viewing_key(self.fvk.clone()).ok()
+    }
+}
+
+impl WalletOKey {
+    /// Orchard-only UFVK for this key, used to scan keys that are not derived from the seed.
+    pub fn to_ufvk(&self) -> Option<UnifiedFullViewingKey> {
+        let fvk = match (&self.fvk, &self.sk) {
+            (Some(fvk), _) => fvk.clone(),
+            (None, Some(sk)) => FullViewingKey::from(sk),
+            (None, None) => return None,
+        };
+        // The parsers use orchard 0.3, `zcash_keys` uses a newer release. The FVK
+        // encoding did not change between them.
+        let fvk = orchard_new::keys::FullViewingKey::from_bytes(&fvk.to_bytes())?;
+        UnifiedFullViewingKey::from_orchard_fvk(fvk).ok()
+    }
+}
+
+/// Keys of an account, in the order the wallet derived them.
+#[derive(Debug, Clone, Default)]
+pub struct WalletKeys {
+    pub tkeys: Vec<WalletTKey>,
+    pub zkeys: Vec<WalletZKey>,
+    pub okeys: Vec<WalletOKey>,
+}
+
+/// Keys imported into a wallet, which cannot be recovered from its seed.
+#[derive(Debug, Clone, Default)]
+pub struct WalletImportedKeys {
+    pub tkeys: Vec<WalletTKey>,
+    pub zkeys: Vec<WalletZKey>,
+    pub okeys: Vec<WalletOKey>,
+}
+
+impl WalletKeys {
+    /// Sapling key and first external transparent key of the ZIP 32 account `account`
+    /// of `seed`.
+    pub fn from_seed(network: Network, seed: &[u8], account: u32) -> Self {
+        let coin_type = network.network_type().coin_type();
+        let extsk = ExtendedSpendingKey::master(seed)
+            .derive_child(ChildIndex::hardened(32))
+            .derive_child(ChildIndex::hardened(coin_type))
+            .derive_child(ChildIndex::hardened(account));
+        let fvk = extsk.to_extended_full_viewing_key();
+        let zkeys = WalletZKey {
+            address: zcashd::sapling_address(network, &fvk),
+            extsk: Some(extsk),
+            fvk,
+            key_type: WalletKeyType::HdDerived,
+            index: account,
+            diversified_addresses: vec![],
+            scope: KeyScope::External,
+            derivation_path: Some(zip32_path(coin_type, account)),
+        };
+
+        let tkeys = AccountId::try_from(account)
+            .ok()
+            .and_then(|account| AccountPrivKey::from_seed(&network.params(), seed, account).ok())
+            .and_then(|key| {
+                key.derive_external_secret_key(NonHardenedChildIndex::from_index(0)?)
+                    .ok()
+            })
+            .map(|pk| {
+                let pubkey = PublicKey::from_secret_key(&Secp256k1::new(), &pk);
+                WalletTKey {
+                    pk,
+                    key_type: WalletKeyType::HdDerived,
+                    index: 0,
+                    address: zcashd::transparent_address(network, &pubkey.serialize()),
+                    scope: KeyScope::External,
+                    derivation_path: Some(transparent_path(
+                        coin_type,
+                        account,
+                        KeyScope::External,
+                        0,
+                    )),
+                }
+            });
+
+        Self {
+            tkeys: tkeys.into_iter().collect(),
+            zkeys: vec![zkeys],
+            okeys: vec![],
+        }
+    }
+
+    /// Every address of the account, transparent addresses first.
+    pub fn addresses(&self) -> Vec<&str> {
+        let tkeys = self.tkeys.iter().map(|k| k.address.as_str());
+        let zkeys = self.zkeys.iter().flat_map(|k| {
+            std::iter::once(k.address.as_str())
+                .chain(k.diversified_addresses.iter().map(String::as_str))
+        });
+        let okeys = self.okeys.iter().flat_map(|k| {
+            std::iter::once(k.address.as_str())
+                .chain(k.diversified_addresses.iter().map(String::as_str))
+        });
+        tkeys.chain(zkeys).chain(okeys).collect()
+    }
+}
+
+/// BIP 44 path of a transparent key.
+pub(crate) fn transparent_path(
+    coin_type: u32,
+    account: u32,
+    scope: KeyScope,
+    index: u32,
+) -> String {
+    let change = match scope {
+        KeyScope::External => 0,
+        KeyScope::Internal => 1,
+    };
+    format!("m/44'/{}'/{}'/{}/{}", coin_type, account, change, index)
+}
+
+/// ZIP 32 path of a sapling or orchard account key.
+pub(crate) fn zip32_path(coin_type: u32, account: u32) -> String {
+    format!("m/32'/{}'/{}'", coin_type, account)
+}
+
+/// Empty directory for the files of one test.
+#[cfg(test)]
+pub(crate) fn test_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("zexcavator-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+impl WalletImportedKeys {
+    pub fn is_empty(&self) -> bool {
+        self.tkeys.is_empty() && self.zkeys.is_empty() && self.okeys.is_empty()
+    }
+}
+
+/// Pool a note was received in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalletPool {
+    Transparent,
+    Sapling,
+    Orchard,
+}
+
+/// Note, or transparent output, received by the wallet.
+#[derive(Debug, Clone)]
+pub struct WalletNote {
+    pub pool: WalletPool,
+    /// Index in [`Wallet::accounts`] of the receiving account, `None` for imported keys.
+    pub account: Option<usize>,
+    /// Index of the output or action in its transaction, if the wallet stored it.
+    pub output_index: Option<u32>,
+    /// Receiving address, if the wallet stored it.
+    pub address: Option<String>,
+    pub value: u64,
+    pub memo: Option<Memo>,
+    pub is_change: bool,
+    pub spent: bool,
+    /// Transaction spending the note, if the wallet stored it.
+    pub spent_in: Option<TxId>,
+}
+
+/// Payment sent by the wallet.
+#[derive(Debug, Clone)]
+pub struct WalletPayment {
+    /// Index in [`Wallet::accounts`] of the sending account, if the wallet stored it.
+    pub account: Option<usize>,
+    pub address: String,
+    pub value: u64,
+    pub memo: Option<Memo>,
+}
+
+/// Transaction of the wallet history.
+#[derive(Debug, Clone)]
+pub struct WalletTransaction {
+    pub txid: TxId,
+    /// Mined height, `None` while the transaction is unconfirmed.
+    pub height: Option<BlockHeight>,
+    /// Block time, in seconds since the unix epoch.
+    pub timestamp: u64,
+    pub received: Vec<WalletNote>,
+    pub sent: Vec<WalletPayment>,
+    /// Change of the wallet balance, fee included.
+    pub net_value: i64,
+    /// ZEC price in the wallet currency when the transaction was mined.
+    pub zec_price: Option<f64>,
+}
+
+impl WalletTransaction {
+    /// Memos of the received notes and sent payments.
+    pub fn memos(&self) -> impl Iterator<Item = &Memo> {
+        self.received
+            .iter()
+            .filter_map(|n| n.memo.as_ref())
+            .chain(self.sent.iter().filter_map(|p| p.memo.as_ref()))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WalletAccount {
+    pub name: String,
+    pub seed: Option<Vec<u8>>,
+    // pub ufvk: Option<UnifiedFullViewingKey>,
+    pub birthday: BlockHeight,
+    pub keys: WalletKeys,
+}
+
+#[derive(Debug, Clone)]
+pub struct Wallet {
+    pub wallet_name: String,
+    pub version: u64,
+    pub network: Network,
+    pub accounts: Vec<WalletAccount>,
+    pub imported_keys: WalletImportedKeys,
+    /// Transaction history, oldest first.
+    pub transactions: Vec<WalletTransaction>,
+    pub metadata: SourceMetadata,
+}
+
+pub trait WalletParser: Send {
+    /// Read the wallet contents from disk
+    fn read(filename: &str) -> io::Result<Self>
+    where
+        Self: Sized;
+    fn get_wallet_name(&self) -> String;
+    fn get_wallet_version(&self) -> u64;
+    /// Network the wallet belongs to, which selects the address and key encodings.
+    fn get_network(&self) -> Network;
+    /// Entropy of the BIP-39 seed phrase of the wallet, 16 to 32 bytes long. `None`
+    /// when the wallet stores no BIP-39 seed, or while it is still encrypted.
+    fn get_wallet_seed(&self) -> Option<Vec<u8>>;
+    fn get_wallet_accounts(&self) -> io::Result<Vec<WalletAccount>>;
+    fn get_birthday(&self) -> u64;
+    /// Number of ZIP 32 accounts derived from the wallet seed.
+    fn get_hd_account_count(&self) -> u32;
+    /// Number of transparent addresses derived from the wallet seed.
+    fn get_hd_transparent_address_count(&self) -> u32;
+    /// Keys imported into the wallet, which are not derived from the seed.
+    fn get_imported_keys(&self) -> WalletImportedKeys;
+    /// Transaction history stored in the wallet, oldest first.
+    fn get_transactions(&self) -> Vec<WalletTransaction> {
+        vec![]
+    }
+    /// Sections of a damaged wallet file that were skipped while reading it.
+    fn get_lost_sections(&self) -> Vec<LostSection> {
+        vec![]
+    }
+    /// Format specific data exported in the org.zingolabs ZeWIF extension.
+    fn get_metadata(&self) -> SourceMetadata {
+        SourceMetadata::default()
+    }
+    /// Whether the wallet secrets are encrypted and must be decrypted before use.
+    fn is_encrypted(&self) -> bool {
+        false
+    }
+    /// Decrypts the wallet secrets in place.
+    fn decrypt(&mut self, _password: &str) -> io::Result<()> {
+        Ok(())
+    }
+    /// Whether the wallet stores no seed, which must be given with [`Self::set_mnemonic`].
+    fn requires_mnemonic(&self) -> bool {
+        false
+    }
+    /// Sets the seed phrase of a wallet that doesn't store it.
+    fn set_mnemonic(&mut self, _phrase: &str) -> io::Result<()> {
+        Ok(())
+    }
+    fn print_internal(&self);
+}
+
+pub trait WalletWriter {
+    /// Writes `wallet` to a new file. Returns warnings about the keys the format
+    /// couldn't hold.
+    fn write(wallet: &Wallet, filename: &str) -> std::io::Result<Vec<String>>;
+}
+
+impl Wallet {
+    pub fn parse<P>(filename: &str) -> Result<Self, ParseError>
+    where
+        P: WalletParser,
+    {
+        let wallet = P::read(filename)?;
+
+        Ok(Self::from_parser(&wallet)?)
+    }
+
+    /// Snapshot of a wallet already read, and decrypted if needed, by a parser.
+    pub fn from_parser(wallet: &dyn WalletParser) -> io::Result<Self> {
+        Ok(Self {
+            wallet_name: wallet.get_wallet_name(),
+            version: wallet.get_wallet_version(),
+            network: wallet.get_network(),
+            accounts: wallet.get_wallet_accounts()?,
+            imported_keys: wallet.get_imported_keys(),
+            transactions: wallet.get_transactions(),
+            metadata: wallet.get_metadata(),
+        })
+    }
+
+    pub fn write<W>(&self, filename: &str) -> io::Result<Vec<String>>
+    where
+        W: WalletWriter,
+    {
+        W::write(self, filename)
+    }
+}
+
+pub struct WalletParserFactory {
+    pub parser: Box<dyn WalletParser>,
+    pub filename: String,
+    pub format: WalletFormat,
+}
+
+impl WalletParserFactory {
+    /// Reads `filename` in the format detected from its content. When the most likely
+    /// format fails to parse, the other candidates are tried in order.
+    pub fn read(filename: &str) -> Result<Self, ParseError> {
+        let candidates = detect::detect(Path::new(filename))?;
+        Self::read_detected(filename, &candidates, false)
+    }
+
+    /// Reads `filename` with the first of `candidates` that parses it, or returns the
+    /// error of the first candidate. With `tolerant`, damaged ZecWallet Lite files are
+    /// read as far as possible, see [`Self::read_as_tolerant`].
+    pub fn read_detected(
+        filename: &str,
+        candidates: &[FormatCandidate],
+        tolerant: bool,
+    ) -> Result<Self, ParseError> {
+        let mut first_error = None;
+        for candidate in candidates {
+            match Self::read_with(filename, candidate.format, tolerant) {
+                Ok(factory) => return Ok(factory),
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        Err(first_error.unwrap_or_else(|| ParseError::UnknownFormat(filename.to_string())))
+    }
+
+    /// Reads `filename` as `format`, bypassing detection. Damaged files fail, e.g. with
+    /// [`ParseError::Truncated`].
+    pub fn read_as(filename: &str, format: WalletFormat) -> Result<Self, ParseError> {
+        Self::read_with(filename, format, false)
+    }
+
+    /// Reads `filename` as `format` like [`Self::read_as`], except that damaged
+    /// ZecWallet Lite files are read as far as possible, to rescue the seed. The
+    /// sections skipped are reported by [`WalletParser::get_lost_sections`].
+    pub fn read_as_tolerant(filename: &str, format: WalletFormat) -> Result<Self, ParseError> {
+        Self::read_with(filename, format, true)
+    }
+
+    fn read_with(filename: &str, format: WalletFormat, tolerant: bool) -> Result<Self, ParseError> {
+        let parser: Box<dyn WalletParser> = match format {
+            WalletFormat::ZecWalletLite if tolerant => {
+                Box::new(ZwlWallet::read_tolerant(filename)?)
+            }
+            WalletFormat::ZecWalletLite => Box::new(ZwlWallet::read(filename)?),
+            WalletFormat::Zingolib => Box::new(Zingolib::read(filename)?),
+            WalletFormat::Zcashd => Box::new(ZcashdWallet::read(filename)?),
+            WalletFormat::ZcashdDump => Box::new(ZcashdDump::read(filename)?),
+            WalletFormat::YWallet => Box::new(YWallet::read(filename)?),
+            WalletFormat::Zashi => Box::new(ZashiWallet::read(filename)?),
+            WalletFormat::Zewif => Box::new(ZewifFile::read(filename)?),
+        };
+
+        Ok(WalletParserFactory {
+            parser,
+            filename: filename.to_string(),
+            format,
+        })
+    }
+
+    /// Decrypts the wallet if it is encrypted, or sets the seed phrase of a wallet that
+    /// doesn't store it. Fails if either is needed and `secret` is not given.
+    pub fn unlock(&mut self, secret: Option<&str>) -> io::Result<()> {
+        if self.parser.requires_mnemonic() {
+            return match secret {
+                Some(phrase) => self.parser.set_mnemonic(phrase),
+                None => Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    "Wallet stores no seed, its seed phrase is required",
+                )),
+            };
+        }
+        if !self.parser.is_encrypted() {
+            return Ok(());
+        }
+
+        match secret {
+            Some(password) => self.parser.decrypt(password),
+            None => Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "Wallet is encrypted, a password is required",
+            )),
+        }
+    }
+}
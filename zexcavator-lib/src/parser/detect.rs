@@ -0,0 +1,303 @@
+//! # Wallet format detection
+//!
+//! Identifies the format of a wallet file from its content, so renamed files and
+//! formats sharing an extension are read by the right parser:
+//!
+//! - **zcashd dump**: the `# Wallet dump created by Zcash` header line.
+//! - **zcashd**: the Berkeley DB btree magic.
+//! - **SQLite**: the `SQLite format 3` magic, then the schema tells Zashi
+//!   (`zcash_client_sqlite` tables) from YWallet (`accounts.id_account`, `taddrs`).
+//! - **ZeWIF**: a CBOR Gordian Envelope.
+//! - **ZecWallet Lite** and **zingolib**: the leading `u64` wallet version, checked
+//!   against the version of the record that follows it, the `Keys` of ZecWallet Lite
+//!   or the `WalletCapability` of zingolib.
+//!
+//! The file extension is only used when the content matches no format.
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::str::FromStr;
+
+use bc_envelope::prelude::*;
+use byteorder::{ByteOrder, LittleEndian};
+use rusqlite::{Connection, OpenFlags};
+
+use super::zashi::{self, has_column, has_table};
+use super::zcashd;
+use super::zingolib::{FIRST_ZINGOLIB_VERSION, LAST_LEGACY_VERSION};
+use super::zwl::keys::Keys;
+
+const SQLITE_MAGIC: &[u8] = b"SQLite format 3\0";
+/// CBOR tag 200 of a Gordian Envelope.
+const ENVELOPE_TAG: &[u8] = &[0xd8, 0xc8];
+/// Bytes read to sniff the format.
+const HEAD_SIZE: u64 = 64;
+
+/// Wallet file formats ZExCavator reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WalletFormat {
+    ZecWalletLite,
+    Zingolib,
+    Zcashd,
+    ZcashdDump,
+    YWallet,
+    Zashi,
+    Zewif,
+}
+
+impl WalletFormat {
+    pub fn all() -> [Self; 7] {
+        [
+            Self::ZecWalletLite,
+            Self::Zingolib,
+            Self::Zcashd,
+            Self::ZcashdDump,
+            Self::YWallet,
+            Self::Zashi,
+            Self::Zewif,
+        ]
+    }
+}
+
+impl fmt::Display for WalletFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ZecWalletLite => write!(f, "zwl"),
+            Self::Zingolib => write!(f, "zingolib"),
+            Self::Zcashd => write!(f, "zcashd"),
+            Self::ZcashdDump => write!(f, "zcashd-dump"),
+            Self::YWallet => write!(f, "ywallet"),
+            Self::Zashi => write!(f, "zashi"),
+            Self::Zewif => write!(f, "zewif"),
+        }
+    }
+}
+
+impl FromStr for WalletFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::all()
+            .into_iter()
+            .find(|format| format.to_string() == s.to_lowercase())
+            .ok_or_else(|| {
+                let names: Vec<String> = Self::all().iter().map(|f| f.to_string()).collect();
+                format!(
+                    "Unknown wallet format {}, expected one of {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// A format the file may be in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatCandidate {
+    pub format: WalletFormat,
+    /// Confidence in the format, from 0 to 100.
+    pub confidence: u8,
+    /// What the confidence is based on.
+    pub reason: String,
+}
+
+impl FormatCandidate {
+    fn new(format: WalletFormat, confidence: u8, reason: &str) -> Self {
+        Self {
+            format,
+            confidence,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for FormatCandidate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({}%, {})", self.format, self.confidence, self.reason)
+    }
+}
+
+fn sqlite_candidates(path: &Path) -> Vec<FormatCandidate> {
+    let Ok(conn) = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY) else {
+        return vec![];
+    };
+
+    let mut candidates = vec![];
+    if zashi::is_client_sqlite(path) {
+        candidates.push(FormatCandidate::new(
+            WalletFormat::Zashi,
+            95,
+            "zcash_client_sqlite schema",
+        ));
+    }
+    if has_column(&conn, "accounts", "id_account") && has_table(&conn, "taddrs") {
+        candidates.push(FormatCandidate::new(
+            WalletFormat::YWallet,
+            90,
+            "YWallet schema",
+        ));
+    }
+    if candidates.is_empty() {
+        candidates = vec![
+            FormatCandidate::new(WalletFormat::YWallet, 20, "SQLite database"),
+            FormatCandidate::new(WalletFormat::Zashi, 20, "SQLite database"),
+        ];
+    }
+    candidates
+}
+
+/// Candidates of files starting with a `u64` wallet version.
+fn versioned_candidates(head: &[u8]) -> Vec<FormatCandidate> {
+    if head.len() < 17 {
+        return vec![];
+    }
+    let version = LittleEndian::read_u64(&head[..8]);
+
+    match version {
+        1..FIRST_ZINGOLIB_VERSION => {
+            // `Keys` version, then the encrypted flag. Up to version 14 the keys had no
+            // version and the flag comes first, from version 4 on.
+            let keys_match = if version <= 14 {
+                version >= 4 && head[8] <= 1
+            } else {
+                let keys_version = LittleEndian::read_u64(&head[8..16]);
+                (1..=Keys::serialized_version()).contains(&keys_version) && head[16] <= 1
+            };
+            if keys_match {
+                vec![FormatCandidate::new(
+                    WalletFormat::ZecWalletLite,
+                    90,
+                    "wallet and keys versions",
+                )]
+            } else {
+                vec![FormatCandidate::new(
+                    WalletFormat::ZecWalletLite,
+                    40,
+                    "wallet version",
+                )]
+            }
+        }
+        FIRST_ZINGOLIB_VERSION..=LAST_LEGACY_VERSION => {
+            if (1..=4).contains(&head[8]) {
+                vec![FormatCandidate::new(
+                    WalletFormat::Zingolib,
+                    90,
+                    "wallet and capability versions",
+                )]
+            } else {
+                vec![FormatCandidate::new(
+                    WalletFormat::Zingolib,
+                    40,
+                    "wallet version",
+                )]
+            }
+        }
+        _ => vec![],
+    }
+}
+
+fn extension_candidates(path: &Path) -> Vec<FormatCandidate> {
+    let format = match path.extension().and_then(|e| e.to_str()) {
+        Some("dat") => WalletFormat::ZecWalletLite,
+        Some("db") => WalletFormat::YWallet,
+        Some("sqlite") => WalletFormat::Zashi,
+        Some("zewif") => WalletFormat::Zewif,
+        Some("txt") => WalletFormat::ZcashdDump,
+        _ => return vec![],
+    };
+    vec![FormatCandidate::new(format, 10, "file extension")]
+}
+
+/// Candidate formats of the wallet file at `path`, most likely first.
+pub fn detect(path: &Path) -> io::Result<Vec<FormatCandidate>> {
+    let mut head = vec![];
+    File::open(path)?.take(HEAD_SIZE).read_to_end(&mut head)?;
+
+    let mut candidates = if head.starts_with(zcashd::dump::DUMP_HEADER.as_bytes()) {
+        vec![FormatCandidate::new(
+            WalletFormat::ZcashdDump,
+            100,
+            "zcashd dump header",
+        )]
+    } else if zcashd::bdb::is_berkeley_db(path) {
+        vec![FormatCandidate::new(
+            WalletFormat::Zcashd,
+            95,
+            "Berkeley DB btree",
+        )]
+    } else if head.starts_with(SQLITE_MAGIC) {
+        sqlite_candidates(path)
+    } else if head.starts_with(ENVELOPE_TAG) {
+        let is_envelope = std::fs::read(path)
+            .ok()
+            .and_then(|data| Envelope::try_from_cbor_data(data).ok())
+            .is_some();
+        match is_envelope {
+            true => vec![FormatCandidate::new(
+                WalletFormat::Zewif,
+                95,
+                "Gordian Envelope",
+            )],
+            false => vec![FormatCandidate::new(
+                WalletFormat::Zewif,
+                30,
+                "CBOR envelope tag",
+            )],
+        }
+    } else {
+        versioned_candidates(&head)
+    };
+
+    if candidates.is_empty() {
+        candidates = extension_candidates(path);
+    }
+    candidates.sort_by(|a, b| b.confidence.cmp(&a.confidence));
+    Ok(candidates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use byteorder::WriteBytesExt;
+
+    #[test]
+    fn test_versioned_candidates() {
+        let mut zwl = vec![];
+        zwl.write_u64::<LittleEndian>(25).unwrap();
+        zwl.write_u64::<LittleEndian>(21).unwrap();
+        zwl.push(0);
+        let candidates = versioned_candidates(&zwl);
+        assert_eq!(candidates[0].format, WalletFormat::ZecWalletLite);
+        assert_eq!(candidates[0].confidence, 90);
+
+        let mut legacy_zwl = vec![];
+        legacy_zwl.write_u64::<LittleEndian>(14).unwrap();
+        legacy_zwl.push(0);
+        legacy_zwl.extend_from_slice(&[0xAB; 48]);
+        let candidates = versioned_candidates(&legacy_zwl);
+        assert_eq!(candidates[0].format, WalletFormat::ZecWalletLite);
+        assert_eq!(candidates[0].confidence, 90);
+
+        let mut zingolib = vec![];
+        zingolib.write_u64::<LittleEndian>(30).unwrap();
+        zingolib.extend_from_slice(&[4; 9]);
+        let candidates = versioned_candidates(&zingolib);
+        assert_eq!(candidates[0].format, WalletFormat::Zingolib);
+        assert_eq!(candidates[0].confidence, 90);
+
+        let mut other = vec![];
+        other.write_u64::<LittleEndian>(1000).unwrap();
+        other.extend_from_slice(&[0; 9]);
+        assert!(versioned_candidates(&other).is_empty());
+    }
+
+    #[test]
+    fn test_format_names() {
+        for format in WalletFormat::all() {
+            assert_eq!(format.to_string().parse::<WalletFormat>(), Ok(format));
+        }
+        assert!("wallet".parse::<WalletFormat>().is_err());
+    }
+}
@@ -0,0 +1,141 @@
+//! # Parser Errors
+//!
+//! Structured error returned when a wallet file can't be read. Parsers keep returning
+//! `io::Result`, wrapping a [`ParseError`] in the `io::Error` where the failure is
+//! more specific than an I/O error; [`ParseError::from`] recovers it. Sections a
+//! tolerant read had to skip are reported as [`LostSection`]s.
+
+use std::{fmt, io};
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ParseError {
+    /// The file format could not be detected.
+    #[error("Unknown wallet format for file: {0}")]
+    UnknownFormat(String),
+
+    /// A record has a version this parser doesn't know how to read.
+    #[error("Unsupported {record} version {version}")]
+    UnsupportedVersion { record: &'static str, version: u64 },
+
+    /// The data ended before a record was complete.
+    #[error("Wallet data is truncated at byte {offset}")]
+    Truncated { offset: u64 },
+
+    /// A wallet database doesn't have the expected tables, columns or rows.
+    #[error("Unexpected wallet database schema: {0}")]
+    Schema(String),
+
+    /// A key or address stored in the wallet is invalid.
+    #[error("Invalid key: {0}")]
+    InvalidKey(String),
+
+    /// Any other I/O or decoding error.
+    #[error(transparent)]
+    Io(io::Error),
+}
+
+impl ParseError {
+    /// Process exit code reported by the CLI for this error.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ParseError::Io(_) => 2,
+            ParseError::UnknownFormat(_) => 3,
+            ParseError::UnsupportedVersion { .. } => 4,
+            ParseError::Truncated { .. } => 5,
+            ParseError::Schema(_) => 6,
+            ParseError::InvalidKey(_) => 7,
+        }
+    }
+
+    fn kind(&self) -> io::ErrorKind {
+        match self {
+            ParseError::UnknownFormat(_) => io::ErrorKind::Unsupported,
+            ParseError::Truncated { .. } => io::ErrorKind::UnexpectedEof,
+            ParseError::Io(e) => e.kind(),
+            _ => io::ErrorKind::InvalidData,
+        }
+    }
+
+    /// Turns an unexpected end of data into [`ParseError::Truncated`] at `offset`.
+    /// Other errors are returned unchanged.
+    pub fn at_offset(e: io::Error, offset: u64) -> io::Error {
+        if e.kind() == io::ErrorKind::UnexpectedEof && e.get_ref().is_none_or(|e| !e.is::<Self>()) {
+            ParseError::Truncated { offset }.into()
+        } else {
+            e
+        }
+    }
+}
+
+impl From<io::Error> for ParseError {
+    fn from(e: io::Error) -> Self {
+        if e.get_ref().is_some_and(|inner| inner.is::<Self>()) {
+            return *e.into_inner().unwrap().downcast::<Self>().unwrap();
+        }
+        ParseError::Io(e)
+    }
+}
+
+impl From<ParseError> for io::Error {
+    fn from(e: ParseError) -> Self {
+        match e {
+            ParseError::Io(e) => e,
+            e => io::Error::new(e.kind(), e),
+        }
+    }
+}
+
+/// Section of a damaged wallet file that couldn't be read, skipped by a tolerant read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LostSection {
+    /// Name of the section, e.g. `transactions`.
+    pub section: &'static str,
+    /// Byte offset where reading the section started.
+    pub offset: u64,
+    /// Why the section couldn't be read.
+    pub reason: String,
+}
+
+impl fmt::Display for LostSection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at byte {}: {}",
+            self.section, self.offset, self.reason
+        )
+    }
+}
+
+impl From<rusqlite::Error> for ParseError {
+    fn from(e: rusqlite::Error) -> Self {
+        ParseError::Schema(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_through_io_error() {
+        let e: io::Error = ParseError::UnsupportedVersion {
+            record: "wallet",
+            version: 99,
+        }
+        .into();
+        assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+
+        let e = ParseError::from(e);
+        assert!(matches!(
+            e,
+            ParseError::UnsupportedVersion { version: 99, .. }
+        ));
+        assert_eq!(e.exit_code(), 4);
+
+        let eof = io::Error::from(io::ErrorKind::UnexpectedEof);
+        let e = ParseError::from(ParseError::at_offset(eof, 42));
+        assert!(matches!(e, ParseError::Truncated { offset: 42 }));
+    }
+}
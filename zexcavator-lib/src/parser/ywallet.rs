@@ -0,0 +1,613 @@
+//! # YWallet Parser
+//!
+//! This module provides functionality to parse data from YWallet database files.
+//! YWallet stores wallet information in a simple SQLite3 database format,
+//! making it accessible via the `rusqlite` library.
+//!
+//! ## Overview
+//! The parser extracts the following information from the YWallet database:
+//! - **Account Names**: The names of the accounts (if available).
+//! - **Seeds**: The entropy used to derive account keys (if available).
+//! - **Keys**: The keys associated with each account.
+//! - **Transactions**: The transaction history, with received notes, UTXOs and memos.
+//!
+//! ## Caveats
+//! - **Wallet Birthday**: YWallet doesn't store a birthday. It is estimated from the oldest note received
+//!   by each account, and is never earlier than the Sapling activation height.
+//! - **Incomplete Parsing**: The parser focuses on the core components (accounts, seeds, keys, history) and does not
+//!   extract additional metadata or attributes that may be present in the database.
+//!
+//! ## Implementation Details
+//! - **Database Access**: This module uses `rusqlite` to query the SQLite database.
+//! - **Expected Format**: The parser assumes the database schema adheres to the standard YWallet format.
+//!   Parsing may fail if the database is corrupted or uses a different schema version.
+//!
+//```
+
+mod db;
+
+use std::collections::HashMap;
+use std::{io, path::Path};
+
+use bip0039::{English, Mnemonic};
+// use orchard::keys::FullViewingKey;
+use rusqlite::Connection;
+use zcash_primitives::consensus::{BlockHeight, NetworkConstants, NetworkType};
+use zcash_primitives::memo::Memo;
+use zcash_primitives::transaction::TxId;
+
+use super::error::ParseError;
+use super::zcashd::estimate_birthday;
+use super::{
+    KeyScope, Wallet, WalletAccount, WalletImportedKeys, WalletKeyType, WalletKeys, WalletNote,
+    WalletOKey, WalletParser, WalletPayment, WalletPool, WalletTKey, WalletTransaction,
+    WalletWriter, WalletZKey, transparent_path, zip32_path,
+};
+use crate::settings::Network;
+
+#[derive(Debug, Clone)]
+pub struct YWallet {
+    pub version: u32,
+    pub network: Network,
+    pub accounts: Vec<WalletAccount>,
+    pub transactions: Vec<WalletTransaction>,
+}
+
+impl YWallet {
+    fn get_account_seed(conn: &Connection, account: u32) -> Result<Option<Vec<u8>>, ParseError> {
+        let seed = conn
+            .query_row(
+                "SELECT seed FROM accounts WHERE id_account = ?1",
+                [account],
+                |row| {
+                    let seed: Option<String> = row.get(0)?;
+                    Ok(seed)
+                },
+            )?
+            .unwrap_or("".to_string());
+
+        if seed.is_empty() {
+            return Ok(None);
+        }
+
+        let mnemonic = <Mnemonic<English>>::from_phrase(&seed).map_err(|e| {
+            ParseError::InvalidKey(format!("Invalid seed phrase of account {}: {}", account, e))
+        })?;
+        let entropy = mnemonic.entropy();
+
+        Ok(Some(entropy.to_vec()))
+    }
+
+    fn get_account_tkeys(conn: &Connection, id: u32) -> io::Result<Vec<WalletTKey>> {
+        let address =
+            db::get_account_taddress(conn, id).map_err(|e| ParseError::Schema(e.to_string()))?;
+
+        match db::get_account_t_keys(conn, id) {
+            // A transparent address without its secret key can't be recovered
+            Ok(None) => Ok(vec![]),
+            Ok(Some(pk)) => {
+                Ok(vec![WalletTKey {
+                    pk,
+                    // key_type: crate::WalletKeyType::HdKey,
+                    key_type: WalletKeyType::HdDerived,
+                    index: 0u32,
+                    address,
+                    scope: KeyScope::External,
+                    derivation_path: None,
+                }])
+            }
+            Err(_) => Ok(vec![]),
+        }
+    }
+
+    fn get_account_zkeys(
+        conn: &Connection,
+        id: u32,
+        has_seed: bool,
+        network: NetworkType,
+    ) -> io::Result<Vec<WalletZKey>> {
+        let address =
+            db::get_account_zaddress(conn, id).map_err(|e| ParseError::Schema(e.to_string()))?;
+
+        match db::get_account_z_keys(conn, id, network) {
+            Ok((extsk, fvk, index)) => {
+                let key_type = if has_seed {
+                    // crate::WalletKeyType::HdKey
+                    WalletKeyType::HdDerived
+                }
+                // else if extsk.is_some() {
+                //     crate::WalletKeyType::ImportedExtsk
+                // }
+                else {
+                    // crate::WalletKeyType::ImportedViewKey
+                    WalletKeyType::Imported
+                };
+
+                let index = index.unwrap_or(0);
+                let derivation_path = has_seed.then(|| zip32_path(network.coin_type(), index));
+                Ok(vec![WalletZKey {
+                    extsk,
+                    fvk,
+                    key_type,
+                    index,
+                    address,
+                    diversified_addresses: vec![],
+                    scope: KeyScope::External,
+                    derivation_path,
+                }])
+            }
+            Err(_) => Ok(vec![]),
+        }
+    }
+
+    fn get_account_okeys(
+        conn: &Connection,
+        id: u32,
+        has_seed: bool,
+        network: NetworkType,
+    ) -> io::Result<Vec<WalletOKey>> {
+        match db::get_account_o_keys(conn, id, network) {
+            Ok((sk, fvk, index, address)) => {
+                let key_type = if has_seed {
+                    // crate::WalletKeyType::HdKey
+                    WalletKeyType::HdDerived
+                }
+                // else if sk.is_some() {
+                //     WalletKeyType::ImportedExtsk
+                // }
+                else {
+                    // WalletKeyType::ImportedViewKey
+                    WalletKeyType::Imported
+                };
+
+                // FIXME
+                // Ok(Some(WalletOKey {
+                //     sk,
+                //     fvk,
+                //     key_type,
+                //     index,
+                //     address,
+                // }))
+                Ok(vec![])
+            }
+            Err(_) => Ok(vec![]),
+        }
+    }
+}
+
+/// Index of the transaction `txid` in `transactions`, added if missing.
+fn transaction_index(
+    transactions: &mut Vec<WalletTransaction>,
+    txid: [u8; 32],
+    height: u32,
+    timestamp: i64,
+) -> usize {
+    let txid = TxId::from_bytes(txid);
+    match transactions.iter().position(|tx| tx.txid == txid) {
+        Some(index) => index,
+        None => {
+            transactions.push(WalletTransaction {
+                txid,
+                height: Some(BlockHeight::from_u32(height)),
+                timestamp: timestamp as u64,
+                received: vec![],
+                sent: vec![],
+                net_value: 0,
+                zec_price: None,
+            });
+            transactions.len() - 1
+        }
+    }
+}
+
+/// Text memo, `None` when empty.
+fn text_memo(text: Option<String>) -> Option<Memo> {
+    text.filter(|t| !t.is_empty()).and_then(|t| t.parse().ok())
+}
+
+impl YWallet {
+    /// Transaction history of every account. YWallet stores a row per account and
+    /// transaction, with the change of the account balance and, for outgoing
+    /// transactions, the recipient and memo. The value of a payment is the balance
+    /// change, fee included.
+    fn read_transactions(
+        conn: &Connection,
+        account_ids: &[u32],
+    ) -> Result<Vec<WalletTransaction>, Box<dyn std::error::Error>> {
+        let account_index = |id: u32| account_ids.iter().position(|&a| a == id);
+
+        let mut transactions: Vec<WalletTransaction> = vec![];
+        // Transaction of each YWallet `id_tx`, with the account balance change
+        let mut rows: HashMap<u32, (usize, i64)> = HashMap::new();
+        for row in db::get_transactions(conn)? {
+            let index = transaction_index(&mut transactions, row.txid, row.height, row.timestamp);
+            let tx = &mut transactions[index];
+            tx.net_value += row.value;
+            match row.address {
+                Some(address) if row.value < 0 => tx.sent.push(WalletPayment {
+                    account: account_index(row.account),
+                    address,
+                    value: row.value.unsigned_abs(),
+                    memo: text_memo(row.memo),
+                }),
+                _ => (),
+            }
+            rows.insert(row.id_tx, (index, row.value));
+        }
+
+        let memos: HashMap<(u32, u32), String> = db::get_received_memos(conn)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(id_tx, vout, body)| ((id_tx, vout), body))
+            .collect();
+        for note in db::get_received_notes(conn)? {
+            let Some(&(index, value)) = rows.get(&note.id_tx) else {
+                continue;
+            };
+            transactions[index].received.push(WalletNote {
+                pool: match note.orchard {
+                    true => WalletPool::Orchard,
+                    false => WalletPool::Sapling,
+                },
+                account: account_index(note.account),
+                output_index: Some(note.output_index),
+                address: None,
+                value: note.value as u64,
+                memo: text_memo(memos.get(&(note.id_tx, note.output_index)).cloned()),
+                // A note received by a transaction lowering the balance is change
+                is_change: value < 0,
+                spent: note.spent.is_some(),
+                spent_in: None,
+            });
+        }
+
+        for utxo in db::get_utxos(conn)? {
+            let index = transaction_index(&mut transactions, utxo.txid, utxo.height, utxo.time);
+            // Balance changes are only known for transactions with a YWallet row
+            if !rows.values().any(|&(i, _)| i == index) {
+                transactions[index].net_value += utxo.value;
+            }
+            transactions[index].received.push(WalletNote {
+                pool: WalletPool::Transparent,
+                account: account_index(utxo.account),
+                output_index: Some(utxo.idx),
+                address: None,
+                value: utxo.value as u64,
+                memo: None,
+                is_change: false,
+                spent: utxo.spent.is_some(),
+                spent_in: None,
+            });
+        }
+
+        transactions.sort_by_key(|tx| (tx.height, tx.timestamp));
+        Ok(transactions)
+    }
+}
+
+impl YWallet {
+    /// Reads the wallet from an open YWallet database.
+    pub fn from_connection(conn: &Connection) -> io::Result<Self> {
+        // get db schema version
+        let version = db::get_schema_version(conn).map_err(ParseError::from)?;
+        let network = db::get_network(conn);
+
+        // get available accounts
+        let acc = db::get_account_list(conn).map_err(|e| ParseError::Schema(e.to_string()))?;
+
+        let account_list = acc
+            .accounts
+            .ok_or_else(|| ParseError::Schema("Empty account list".to_string()))?;
+        let account_ids: Vec<u32> = account_list.iter().map(|a| a.id).collect();
+
+        let accounts = account_list
+            .iter()
+            .map(|a| -> io::Result<WalletAccount> {
+                // get account seed
+                let seed = Self::get_account_seed(conn, a.id)?;
+
+                // get all keys for this account
+                let zkeys =
+                    Self::get_account_zkeys(conn, a.id, seed.is_some(), network.network_type())?;
+                let mut tkeys = Self::get_account_tkeys(conn, a.id)?;
+                // The transparent key of a seed account is the first of its ZIP 32 account
+                if let (Some(_), Some(zkey)) = (&seed, zkeys.first()) {
+                    for tkey in tkeys.iter_mut() {
+                        tkey.derivation_path = Some(transparent_path(
+                            network.network_type().coin_type(),
+                            zkey.index,
+                            KeyScope::External,
+                            0,
+                        ));
+                    }
+                }
+                let okeys =
+                    Self::get_account_okeys(conn, a.id, seed.is_some(), network.network_type())?;
+
+                let keys = WalletKeys {
+                    tkeys,
+                    zkeys,
+                    okeys,
+                };
+
+                let birthday = db::get_account_birthday(conn, a.id);
+
+                Ok(WalletAccount {
+                    name: a.name.clone().unwrap_or(format!("Account {}", a.id)),
+                    seed,
+                    birthday,
+                    keys,
+                })
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let transactions = Self::read_transactions(conn, &account_ids)
+            .map_err(|e| ParseError::Schema(e.to_string()))?;
+
+        Ok(Self {
+            version,
+            network,
+            accounts,
+            transactions,
+        })
+    }
+}
+
+impl WalletParser for YWallet {
+    fn read(filename: &str) -> io::Result<Self> {
+        let conn = Connection::open(filename).map_err(ParseError::from)?;
+        Self::from_connection(&conn)
+    }
+
+    fn get_wallet_name(&self) -> String {
+        "YWallet".to_string()
+    }
+
+    fn get_wallet_version(&self) -> u64 {
+        self.version as u64
+    }
+
+    fn get_wallet_accounts(&self) -> std::io::Result<Vec<WalletAccount>> {
+        Ok(self.accounts.clone())
+    }
+
+    fn get_network(&self) -> Network {
+        self.network
+    }
+
+    fn print_internal(&self) {
+        println!("YWallet: {:#?}", self);
+    }
+
+    /// Seed of the first account that has one, accounts restored from keys have none.
+    fn get_wallet_seed(&self) -> Option<Vec<u8>> {
+        self.accounts.iter().find_map(|a| a.seed.clone())
+    }
+
+    /// Height of the oldest received note, no earlier than the Sapling activation.
+    fn get_birthday(&self) -> u64 {
+        let oldest = self
+            .accounts
+            .iter()
+            .map(|a| u64::from(u32::from(a.birthday)))
+            // Accounts that never received a note have no estimate
+            .filter(|&height| height > 0)
+            .min()
+            .unwrap_or_default();
+        oldest.max(estimate_birthday(self.network, None))
+    }
+
+    fn get_hd_account_count(&self) -> u32 {
+        self.accounts.len() as u32
+    }
+
+    fn get_hd_transparent_address_count(&self) -> u32 {
+        // YWallet only stores one transparent address per account
+        1
+    }
+
+    fn get_imported_keys(&self) -> WalletImportedKeys {
+        // Every YWallet account is stored with its own keys, imported ones included
+        WalletImportedKeys::default()
+    }
+
+    fn get_transactions(&self) -> Vec<WalletTransaction> {
+        self.transactions.clone()
+    }
+}
+
+/// Splits `account` into YWallet accounts, which hold one key of each pool. The n-th
+/// keys of each pool go together, external transparent keys first. Only the first
+/// YWallet account keeps the seed, as the others aren't derived at their index.
+/// Returns the accounts, and warnings about the keys left without a sapling key,
+/// which every YWallet account needs.
+fn split_account(account: &WalletAccount) -> (Vec<WalletAccount>, Vec<String>) {
+    let keys = &account.keys;
+    let mut tkeys: Vec<&WalletTKey> = keys.tkeys.iter().collect();
+    tkeys.sort_by_key(|k| k.scope != KeyScope::External);
+
+    let count = keys.zkeys.len().max(keys.okeys.len()).max(tkeys.len());
+    let mut accounts = vec![];
+    let mut warnings = vec![];
+    for n in 0..count {
+        let okey = keys.okeys.get(n);
+        let tkey = tkeys.get(n);
+        let Some(zkey) = keys.zkeys.get(n) else {
+            let addresses: Vec<&str> = okey
+                .map(|k| k.address.as_str())
+                .into_iter()
+                .chain(tkey.map(|k| k.address.as_str()))
+                .collect();
+            warnings.push(format!(
+                "{}: {} not exported, YWallet accounts need a sapling key",
+                account.name,
+                addresses.join(", ")
+            ));
+            continue;
+        };
+
+        accounts.push(WalletAccount {
+            name: match n {
+                0 => account.name.clone(),
+                n => format!("{} ({})", account.name, n + 1),
+            },
+            seed: account.seed.clone().filter(|_| n == 0),
+            birthday: account.birthday,
+            keys: WalletKeys {
+                tkeys: tkey.map(|k| (*k).clone()).into_iter().collect(),
+                zkeys: vec![zkey.clone()],
+                okeys: okey.cloned().into_iter().collect(),
+            },
+        });
+    }
+    (accounts, warnings)
+}
+
+impl WalletWriter for YWallet {
+    fn write(wallet: &Wallet, filename: &str) -> std::io::Result<Vec<String>> {
+        let path = Path::new(filename);
+
+        if path.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("File {} already exists, will not overwrite", filename),
+            ));
+        }
+
+        let conn = Connection::open(path).map_err(ParseError::from)?;
+        db::init_db(&conn)?;
+
+        let mut warnings = vec![];
+        let mut id = 1;
+        for account in &wallet.accounts {
+            let (accounts, skipped) = split_account(account);
+            for account in accounts {
+                db::create_account_with_keys(&conn, account, id, wallet.network.network_type())?;
+                id += 1;
+            }
+            warnings.extend(skipped);
+        }
+
+        Ok(warnings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::test_dir;
+    use crate::parser::zwl::ZwlWallet;
+
+    const PHRASE: &str = "clerk family rack dragon cannon wait vendor penalty absent country better coast expand true middle stable assist clerk tent phone toilet knee female kitchen";
+
+    #[test]
+    fn test_ywallet_seed_and_birthday() {
+        let wallet = ZwlWallet::from_seed_phrase(PHRASE, 1, Network::Mainnet).unwrap();
+        let conn = Connection::open_in_memory().unwrap();
+        db::init_db(&conn).unwrap();
+        db::create_account_with_keys(&conn, wallet.accounts[0].clone(), 1, NetworkType::Main)
+            .unwrap();
+
+        // Without received notes the birthday falls back to the Sapling activation
+        let ywallet = YWallet::from_connection(&conn).unwrap();
+        assert_eq!(ywallet.get_wallet_seed(), wallet.accounts[0].seed);
+        assert_eq!(ywallet.get_birthday(), 419_200);
+
+        conn.execute_batch(
+            "INSERT INTO received_notes (account, position, tx, height, output_index, diversifier, value, rcm, nf)
+                VALUES (1, 0, 1, 2000010, 0, x'00', 1, x'00', x'01'), (1, 1, 2, 2000000, 0, x'00', 1, x'00', x'02');",
+        )
+        .unwrap();
+        let ywallet = YWallet::from_connection(&conn).unwrap();
+        assert_eq!(ywallet.get_birthday(), 2_000_000);
+    }
+
+    #[test]
+    fn test_ywallet_corrupt() {
+        // Not a YWallet database
+        let err = YWallet::from_connection(&Connection::open_in_memory().unwrap()).unwrap_err();
+        assert!(matches!(ParseError::from(err), ParseError::Schema(_)));
+
+        let conn = Connection::open_in_memory().unwrap();
+        db::init_db(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO accounts (id_account, name, seed, aindex, ivk, address)
+                VALUES (1, 'Main', 'not a seed phrase', 0, 'zxviews1', 'zs1')",
+            [],
+        )
+        .unwrap();
+        let err = YWallet::from_connection(&conn).unwrap_err();
+        assert!(matches!(ParseError::from(err), ParseError::InvalidKey(_)));
+    }
+
+    #[test]
+    fn test_ywallet_history() {
+        let conn = Connection::open_in_memory().unwrap();
+        db::init_db(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO transactions (id_tx, account, txid, height, timestamp, value, address, memo)
+                VALUES (1, 1, x'0101010101010101010101010101010101010101010101010101010101010101', 2000000, 1700000000, 50000, NULL, NULL);
+             INSERT INTO transactions (id_tx, account, txid, height, timestamp, value, address, memo)
+                VALUES (2, 1, x'0202020202020202020202020202020202020202020202020202020202020202', 2000010, 1700001000, -20000, 'zs1recipient', 'Thanks');
+             INSERT INTO received_notes (account, position, tx, height, output_index, diversifier, value, rcm, nf, spent)
+                VALUES (1, 0, 1, 2000000, 0, x'00', 50000, x'00', x'01', 2000010);
+             INSERT INTO received_notes (account, position, tx, height, output_index, diversifier, value, rcm, nf, orchard)
+                VALUES (1, 1, 2, 2000010, 1, x'00', 20000, x'00', x'02', true);
+             INSERT INTO messages (account, sender, recipient, subject, body, timestamp, height, read, id_tx)
+                VALUES (1, NULL, 'zs1me', '', 'Hello', 1700000000, 2000000, false, 1);",
+        )
+        .unwrap();
+
+        let transactions = YWallet::read_transactions(&conn, &[1]).unwrap();
+        assert_eq!(transactions.len(), 2);
+
+        let received = &transactions[0];
+        assert_eq!(received.net_value, 50000);
+        assert_eq!(received.received[0].account, Some(0));
+        assert!(received.received[0].spent);
+        assert_eq!(received.memos().count(), 1);
+
+        let sent = &transactions[1];
+        assert_eq!(sent.net_value, -20000);
+        assert_eq!(sent.sent[0].address, "zs1recipient");
+        assert_eq!(sent.received[0].pool, WalletPool::Orchard);
+        assert!(sent.received[0].is_change);
+    }
+
+    #[test]
+    fn test_ywallet_write_all_keys() {
+        let mut wallet = ZwlWallet::from_seed_phrase(PHRASE, 3, Network::Mainnet).unwrap();
+        // One account with two sapling keys and three transparent keys
+        let mut account = wallet.accounts[0].clone();
+        for other in &wallet.accounts[1..] {
+            account.keys.tkeys.extend(other.keys.tkeys.iter().cloned());
+        }
+        account
+            .keys
+            .zkeys
+            .extend(wallet.accounts[1].keys.zkeys.iter().cloned());
+        let skipped = account.keys.tkeys[2].address.clone();
+        wallet.accounts = vec![account];
+
+        let path = test_dir("ywallet-write").join("wallet.db");
+        let warnings = wallet.write::<YWallet>(path.to_str().unwrap()).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains(&skipped));
+
+        let ywallet = YWallet::read(path.to_str().unwrap()).unwrap();
+        assert_eq!(ywallet.accounts.len(), 2);
+        assert_eq!(
+            ywallet.accounts[1].name,
+            format!("{} (2)", ywallet.accounts[0].name)
+        );
+        assert_eq!(ywallet.accounts[0].seed, wallet.accounts[0].seed);
+        assert_eq!(ywallet.accounts[1].seed, None);
+        for (written, expected) in ywallet.accounts.iter().zip(&wallet.accounts[0].keys.zkeys) {
+            assert_eq!(written.keys.zkeys[0].address, expected.address);
+        }
+
+        // Existing files are not overwritten
+        let err = wallet.write::<YWallet>(path.to_str().unwrap()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+    }
+}
@@ -12,14 +12,12 @@ use zcash_keys::{
         encode_extended_full_viewing_key, encode_extended_spending_key,
     },
 };
-use zcash_primitives::{
-    consensus::{BlockHeight, MainNetwork},
-    constants::mainnet::{
-        HRP_SAPLING_EXTENDED_FULL_VIEWING_KEY, HRP_SAPLING_EXTENDED_SPENDING_KEY,
-    },
-};
+use zcash_primitives::consensus::{BlockHeight, NetworkConstants, NetworkType};
 
-use crate::walletparsers::walletparsers::WalletAccount;
+use crate::parser::WalletAccount;
+use crate::parser::error::ParseError;
+use crate::parser::zashi::{has_column, has_table};
+use crate::settings::Network;
 
 #[derive(Debug)]
 pub struct AccountT {
@@ -32,14 +30,12 @@ pub struct AccountVecT {
     pub accounts: Option<Vec<AccountT>>,
 }
 
-pub fn get_schema_version(connection: &Connection) -> u32 {
-    let version: Option<u32> = connection
-        .query_row("SELECT version FROM schema_version LIMIT 1", [], |row| {
+pub fn get_schema_version(connection: &Connection) -> rusqlite::Result<u32> {
+    let version: Option<u32> =
+        connection.query_row("SELECT version FROM schema_version LIMIT 1", [], |row| {
             row.get(0)
-        })
-        .map_err(|_| "Fail")
-        .expect("No schema version");
-    version.unwrap_or(0)
+        })?;
+    Ok(version.unwrap_or(0))
 }
 
 pub fn get_account_list(conn: &Connection) -> Result<AccountVecT, Box<dyn Error>> {
@@ -116,9 +112,32 @@ pub fn get_account_zaddress(conn: &Connection, id: u32) -> Result<String, Box<dy
     Ok(address.unwrap_or_default())
 }
 
+/// YWallet keeps no network setting in the database, so the network is read from the
+/// viewing key prefix of the first account. Defaults to mainnet.
+pub fn get_network(conn: &Connection) -> Network {
+    let ivk: Option<String> = conn
+        .query_row(
+            "SELECT ivk FROM accounts WHERE ivk IS NOT NULL ORDER BY id_account LIMIT 1",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap_or_default();
+
+    Network::all()
+        .into_iter()
+        .find(|network| {
+            let hrp = network
+                .network_type()
+                .hrp_sapling_extended_full_viewing_key();
+            matches!(&ivk, Some(ivk) if ivk.starts_with(&format!("{}1", hrp)))
+        })
+        .unwrap_or_default()
+}
+
 pub fn get_account_z_keys(
     conn: &Connection,
     id: u32,
+    network: NetworkType,
 ) -> Result<
     (
         Option<ExtendedSpendingKey>,
@@ -138,12 +157,14 @@ pub fn get_account_z_keys(
         },
     )?;
 
-    let ivk =
-        decode_extended_full_viewing_key(HRP_SAPLING_EXTENDED_FULL_VIEWING_KEY, &ivk_str.unwrap())?;
+    let ivk = decode_extended_full_viewing_key(
+        network.hrp_sapling_extended_full_viewing_key(),
+        &ivk_str.ok_or("Account without a viewing key")?,
+    )?;
 
     let extsk = match sk_str {
         Some(s) => Some(decode_extended_spending_key(
-            HRP_SAPLING_EXTENDED_SPENDING_KEY,
+            network.hrp_sapling_extended_spending_key(),
             &s,
         )?),
         None => None,
@@ -155,6 +176,7 @@ pub fn get_account_z_keys(
 pub fn get_account_o_keys(
     conn: &Connection,
     id: u32,
+    network: NetworkType,
 ) -> Result<(Option<SpendingKey>, Option<FullViewingKey>, u32, String), Box<dyn Error>> {
     let (sk_blob, fvk_blob, index) = conn
         .query_row(
@@ -173,24 +195,26 @@ pub fn get_account_o_keys(
 
     let sk: Option<SpendingKey> = match sk_blob {
         Some(sk_bytes) => {
-            let sk = SpendingKey::from_bytes(sk_bytes).expect("Invalid sk");
+            let sk = Option::<SpendingKey>::from(SpendingKey::from_bytes(sk_bytes))
+                .ok_or("Invalid orchard sk")?;
             Some(sk)
         }
         None => None,
     };
 
-    let fvk = fvk_blob.map(|f| FullViewingKey::from_bytes(&f).expect("Invalid fvk"));
-
-    let address = if fvk.is_some() {
-        let o = fvk
-            .clone()
-            .unwrap()
-            .address_at(index.unwrap(), orchard_new::keys::Scope::External);
-        let ua = UnifiedAddress::from_receivers(Some(o), None, None).expect("Invalid oaddrs");
+    let fvk = match fvk_blob {
+        Some(f) => Some(FullViewingKey::from_bytes(&f).ok_or("Invalid orchard fvk")?),
+        None => None,
+    };
 
-        ua.encode(&MainNetwork)
-    } else {
-        String::new()
+    let address = match &fvk {
+        Some(fvk) => {
+            let o = fvk.address_at(index.unwrap_or(0), orchard_new::keys::Scope::External);
+            let ua = UnifiedAddress::from_receivers(Some(o), None, None)
+                .ok_or("Invalid orchard address")?;
+            ua.to_address(network).to_string()
+        }
+        None => String::new(),
     };
 
     Ok((sk, fvk, index.unwrap_or(0u32), address))
@@ -201,7 +225,7 @@ pub fn get_account_o_keys(
 pub fn get_account_birthday(conn: &Connection, id: u32) -> BlockHeight {
     let height = conn
         .query_row(
-            "SELECT height FROM received_notes WHERE account = ?1",
+            "SELECT MIN(height) FROM received_notes WHERE account = ?1",
             [id],
             |row| {
                 let height: Option<u32> = row.get(0)?;
@@ -211,11 +235,128 @@ pub fn get_account_birthday(conn: &Connection, id: u32) -> BlockHeight {
         .map_err(|_| "Fail to get note height");
 
     match height {
-        Ok(h) => BlockHeight::from_u32(h.expect("Invalid height")),
+        Ok(h) => BlockHeight::from_u32(h.unwrap_or(0)),
         Err(_) => BlockHeight::from_u32(0),
     }
 }
 
+#[derive(Debug)]
+pub struct TransactionT {
+    pub id_tx: u32,
+    pub account: u32,
+    pub txid: [u8; 32],
+    pub height: u32,
+    pub timestamp: i64,
+    /// Change of the account balance, fee included.
+    pub value: i64,
+    pub address: Option<String>,
+    pub memo: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct ReceivedNoteT {
+    pub account: u32,
+    pub id_tx: u32,
+    pub output_index: u32,
+    pub value: i64,
+    pub orchard: bool,
+    /// Height of the spending transaction.
+    pub spent: Option<u32>,
+}
+
+#[derive(Debug)]
+pub struct UtxoT {
+    pub account: u32,
+    pub txid: [u8; 32],
+    pub height: u32,
+    pub time: i64,
+    pub idx: u32,
+    pub value: i64,
+    pub spent: Option<u32>,
+}
+
+fn txid_bytes(bytes: Vec<u8>) -> rusqlite::Result<[u8; 32]> {
+    bytes.try_into().map_err(|_| {
+        rusqlite::Error::InvalidColumnType(0, "txid".into(), rusqlite::types::Type::Blob)
+    })
+}
+
+pub fn get_transactions(conn: &Connection) -> Result<Vec<TransactionT>, Box<dyn Error>> {
+    let mut stmt = conn.prepare(
+        "SELECT id_tx, account, txid, height, timestamp, value, address, memo FROM transactions ORDER BY height, id_tx",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(TransactionT {
+            id_tx: row.get(0)?,
+            account: row.get(1)?,
+            txid: txid_bytes(row.get(2)?)?,
+            height: row.get(3)?,
+            timestamp: row.get(4)?,
+            value: row.get(5)?,
+            address: row.get(6)?,
+            memo: row.get(7)?,
+        })
+    })?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+pub fn get_received_notes(conn: &Connection) -> Result<Vec<ReceivedNoteT>, Box<dyn Error>> {
+    // Databases created before orchard support have no `orchard` column
+    let query = match has_column(conn, "received_notes", "orchard") {
+        true => {
+            "SELECT account, tx, output_index, value, orchard, spent FROM received_notes ORDER BY id_note"
+        }
+        false => {
+            "SELECT account, tx, output_index, value, 0, spent FROM received_notes ORDER BY id_note"
+        }
+    };
+    let mut stmt = conn.prepare(query)?;
+    let rows = stmt.query_map([], |row| {
+        Ok(ReceivedNoteT {
+            account: row.get(0)?,
+            id_tx: row.get(1)?,
+            output_index: row.get(2)?,
+            value: row.get(3)?,
+            orchard: row.get(4)?,
+            spent: row.get(5)?,
+        })
+    })?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+pub fn get_utxos(conn: &Connection) -> Result<Vec<UtxoT>, Box<dyn Error>> {
+    if !has_table(conn, "utxos") {
+        return Ok(vec![]);
+    }
+    let mut stmt = conn.prepare(
+        "SELECT account, txid, height, time, idx, value, spent FROM utxos ORDER BY id_utxo",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(UtxoT {
+            account: row.get(0)?,
+            txid: txid_bytes(row.get(1)?)?,
+            height: row.get(2)?,
+            time: row.get(3)?,
+            idx: row.get(4)?,
+            value: row.get(5)?,
+            spent: row.get(6)?,
+        })
+    })?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+/// Memos of received notes, by transaction id and output index. Older databases
+/// have no output index, and use 0.
+pub fn get_received_memos(conn: &Connection) -> Result<Vec<(u32, u32, String)>, Box<dyn Error>> {
+    let query = match has_column(conn, "messages", "vout") {
+        true => "SELECT id_tx, vout, body FROM messages WHERE id_tx IS NOT NULL AND incoming",
+        false => "SELECT id_tx, 0, body FROM messages WHERE id_tx IS NOT NULL",
+    };
+    let mut stmt = conn.prepare(query)?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
 pub fn init_db(conn: &Connection) -> std::io::Result<()> {
     conn.execute(
         "CREATE TABLE IF NOT EXISTS schema_version (
@@ -223,7 +364,7 @@ pub fn init_db(conn: &Connection) -> std::io::Result<()> {
             version INTEGER NOT NULL)",
         [],
     )
-    .expect("Error creating schema table");
+    .map_err(ParseError::from)?;
 
     conn.execute(
         "CREATE TABLE accounts (
@@ -236,7 +377,7 @@ pub fn init_db(conn: &Connection) -> std::io::Result<()> {
             address TEXT NOT NULL)",
         [],
     )
-    .expect("Error creating accounts table");
+    .map_err(ParseError::from)?;
 
     conn.execute(
         "CREATE TABLE blocks (
@@ -245,7 +386,7 @@ pub fn init_db(conn: &Connection) -> std::io::Result<()> {
             timestamp INTEGER NOT NULL)",
         [],
     )
-    .expect("Error creating blocks table");
+    .map_err(ParseError::from)?;
 
     conn.execute(
         "CREATE TABLE transactions (
@@ -261,7 +402,7 @@ pub fn init_db(conn: &Connection) -> std::io::Result<()> {
             CONSTRAINT tx_account UNIQUE (height, tx_index, account))",
         [],
     )
-    .expect("Error creating transactions table");
+    .map_err(ParseError::from)?;
 
     conn.execute(
         "CREATE TABLE sapling_witnesses (
@@ -272,7 +413,7 @@ pub fn init_db(conn: &Connection) -> std::io::Result<()> {
             CONSTRAINT witness_height UNIQUE (note, height))",
         [],
     )
-    .expect("Error creating sapling_witnesses table");
+    .map_err(ParseError::from)?;
 
     conn.execute(
         "CREATE TABLE diversifiers (
@@ -280,7 +421,7 @@ pub fn init_db(conn: &Connection) -> std::io::Result<()> {
             diversifier_index BLOB NOT NULL)",
         [],
     )
-    .expect("Error creating diversifiers table");
+    .map_err(ParseError::from)?;
 
     conn.execute(
         "CREATE TABLE contacts (
@@ -290,19 +431,19 @@ pub fn init_db(conn: &Connection) -> std::io::Result<()> {
             dirty BOOL NOT NULL)",
         [],
     )
-    .expect("Error creating contacts table");
+    .map_err(ParseError::from)?;
 
     conn.execute("CREATE INDEX i_account ON accounts(address)", [])
-        .expect("Error creating i_account index");
+        .map_err(ParseError::from)?;
 
     conn.execute("CREATE INDEX i_contact ON contacts(address)", [])
-        .expect("Error creating i_contact index");
+        .map_err(ParseError::from)?;
 
     conn.execute("CREATE INDEX i_transaction ON transactions(account)", [])
-        .expect("Error creating i_transaction index");
+        .map_err(ParseError::from)?;
 
     conn.execute("CREATE INDEX i_witness ON sapling_witnesses(height)", [])
-        .expect("Error creating i_witness index");
+        .map_err(ParseError::from)?;
 
     conn.execute(
         "CREATE TABLE messages (
@@ -316,7 +457,7 @@ pub fn init_db(conn: &Connection) -> std::io::Result<()> {
             height INTEGER NOT NULL,
             read BOOL NOT NULL, id_tx INTEGER, incoming BOOL NOT NULL DEFAULT true, vout INTEGER NOT NULL DEFAULT(0))",
         [],
-    ).expect("Error creating messages table");
+    ).map_err(ParseError::from)?;
 
     conn.execute(
         "CREATE TABLE orchard_addrs(
@@ -325,7 +466,7 @@ pub fn init_db(conn: &Connection) -> std::io::Result<()> {
             fvk BLOB NOT NULL)",
         [],
     )
-    .expect("Error creating orchard_addrs table");
+    .map_err(ParseError::from)?;
 
     conn.execute(
         "CREATE TABLE ua_settings(
@@ -335,7 +476,7 @@ pub fn init_db(conn: &Connection) -> std::io::Result<()> {
             orchard BOOL NOT NULL)",
         [],
     )
-    .expect("Error creating ua_settings table");
+    .map_err(ParseError::from)?;
 
     conn.execute(
         "CREATE TABLE sapling_tree(
@@ -343,7 +484,7 @@ pub fn init_db(conn: &Connection) -> std::io::Result<()> {
             tree BLOB NOT NULL)",
         [],
     )
-    .expect("Error creating sapling_tree table");
+    .map_err(ParseError::from)?;
 
     conn.execute(
         "CREATE TABLE orchard_tree(
@@ -351,7 +492,7 @@ pub fn init_db(conn: &Connection) -> std::io::Result<()> {
             tree BLOB NOT NULL)",
         [],
     )
-    .expect("Error creating orchard_tree table");
+    .map_err(ParseError::from)?;
 
     conn.execute(
         "CREATE TABLE received_notes (
@@ -372,7 +513,7 @@ pub fn init_db(conn: &Connection) -> std::io::Result<()> {
             CONSTRAINT tx_output UNIQUE (tx, orchard, output_index))",
         [],
     )
-    .expect("Error creating received_notes table");
+    .map_err(ParseError::from)?;
 
     conn.execute(
         "CREATE TABLE orchard_witnesses (
@@ -383,13 +524,13 @@ pub fn init_db(conn: &Connection) -> std::io::Result<()> {
             CONSTRAINT witness_height UNIQUE (note, height))",
         [],
     )
-    .expect("Error creating orchard_witnesses table");
+    .map_err(ParseError::from)?;
 
     conn.execute(
         "CREATE INDEX i_orchard_witness ON orchard_witnesses(height)",
         [],
     )
-    .expect("Error creating i_orchard_witnessx index");
+    .map_err(ParseError::from)?;
 
     conn.execute(
         "CREATE TABLE send_templates (
@@ -405,7 +546,7 @@ pub fn init_db(conn: &Connection) -> std::io::Result<()> {
             body TEXT NOT NULL)",
         [],
     )
-    .expect("Error creating send_templates table");
+    .map_err(ParseError::from)?;
 
     conn.execute(
         "CREATE TABLE properties (
@@ -413,7 +554,7 @@ pub fn init_db(conn: &Connection) -> std::io::Result<()> {
             value TEXT NOT NULL)",
         [],
     )
-    .expect("Error creating properties table");
+    .map_err(ParseError::from)?;
 
     conn.execute(
         "CREATE TABLE taddrs (
@@ -422,7 +563,7 @@ pub fn init_db(conn: &Connection) -> std::io::Result<()> {
             address TEXT NOT NULL, balance INTEGER, height INTEGER NOT NULL DEFAULT 0)",
         [],
     )
-    .expect("Error creating taddrs table");
+    .map_err(ParseError::from)?;
 
     conn.execute(
         "CREATE TABLE hw_wallets(
@@ -430,7 +571,7 @@ pub fn init_db(conn: &Connection) -> std::io::Result<()> {
             ledger BOOL NOT NULL)",
         [],
     )
-    .expect("Error creating hw_wallets table");
+    .map_err(ParseError::from)?;
 
     conn.execute(
         "CREATE TABLE accounts2 (
@@ -438,7 +579,7 @@ pub fn init_db(conn: &Connection) -> std::io::Result<()> {
             saved BOOL NOT NULL)",
         [],
     )
-    .expect("Error creating accounts2 table");
+    .map_err(ParseError::from)?;
 
     conn.execute(
         "CREATE TABLE account_properties (
@@ -448,14 +589,14 @@ pub fn init_db(conn: &Connection) -> std::io::Result<()> {
             PRIMARY KEY (account, name))",
         [],
     )
-    .expect("Error creating account_properties table");
+    .map_err(ParseError::from)?;
 
     conn.execute(
         "CREATE TABLE transparent_checkpoints (
             height INTEGER PRIMARY KEY)",
         [],
     )
-    .expect("Error creating transparent_checkpoints table");
+    .map_err(ParseError::from)?;
 
     conn.execute(
         "CREATE TABLE block_times (
@@ -463,7 +604,7 @@ pub fn init_db(conn: &Connection) -> std::io::Result<()> {
             timestamp INTEGER NOT NULL)",
         [],
     )
-    .expect("Error creating block_times table");
+    .map_err(ParseError::from)?;
 
     conn.execute(
         "CREATE TABLE transparent_tins (
@@ -474,7 +615,7 @@ pub fn init_db(conn: &Connection) -> std::io::Result<()> {
             PRIMARY KEY (id_tx, idx))",
         [],
     )
-    .expect("Error creating transparent_tins table");
+    .map_err(ParseError::from)?;
 
     conn.execute(
         "CREATE TABLE transparent_touts (
@@ -482,7 +623,7 @@ pub fn init_db(conn: &Connection) -> std::io::Result<()> {
             address TEXT NOT NULL)",
         [],
     )
-    .expect("Error creating transparent_touts table");
+    .map_err(ParseError::from)?;
 
     conn.execute(
         "CREATE TABLE utxos (
@@ -496,7 +637,7 @@ pub fn init_db(conn: &Connection) -> std::io::Result<()> {
             spent INTEGER)",
         [],
     )
-    .expect("Error creating utxos table");
+    .map_err(ParseError::from)?;
 
     conn.execute(
         "CREATE TABLE swaps(
@@ -515,7 +656,7 @@ pub fn init_db(conn: &Connection) -> std::io::Result<()> {
             to_image TEXT NOT NULL)",
         [],
     )
-    .expect("Error creating swaps table");
+    .map_err(ParseError::from)?;
 
     conn.execute(
         "CREATE TABLE tins(
@@ -528,21 +669,21 @@ pub fn init_db(conn: &Connection) -> std::io::Result<()> {
             spent INTEGER)",
         [],
     )
-    .expect("Error creating tins table");
+    .map_err(ParseError::from)?;
 
     conn.execute(
         "CREATE UNIQUE INDEX transactions_txid
         ON transactions (account, txid)",
         [],
     )
-    .expect("Error creating transactions_txid index");
+    .map_err(ParseError::from)?;
 
     // add shcema version
     conn.execute(
         "INSERT INTO schema_version (id, version) VALUES (?1, ?2)",
         (1, 15),
     )
-    .expect("Unable to insert values");
+    .map_err(ParseError::from)?;
 
     Ok(())
 }
@@ -551,31 +692,39 @@ pub fn create_account_with_keys(
     conn: &Connection,
     account: WalletAccount,
     id: usize,
+    network: NetworkType,
 ) -> std::io::Result<()> {
     let seed = match account.seed {
         Some(s) => {
-            let mnemonic = <Mnemonic<English>>::from_entropy(s).expect("Invalid seed entropy");
+            let mnemonic = <Mnemonic<English>>::from_entropy(s)
+                .map_err(|e| ParseError::InvalidKey(format!("Invalid seed entropy: {}", e)))?;
             let phrase = mnemonic.phrase().to_string();
             Some(phrase)
         }
         None => None,
     };
 
+    // YWallet stores a single key per pool for each account, the writer splits
+    // wallet accounts holding more
+    let zkey = account.keys.zkeys.first();
+    let okey = account.keys.okeys.first();
+    let tkey = account.keys.tkeys.first();
+
     // Handle sapling keys first
-    let (extsk, ivk, address) = match account.keys.zkeys {
+    let (extsk, ivk, address) = match zkey {
         Some(z) => {
             let mut sk = String::new();
 
             let ivk = encode_extended_full_viewing_key(
-                HRP_SAPLING_EXTENDED_FULL_VIEWING_KEY,
-                &z.clone().fvk,
+                network.hrp_sapling_extended_full_viewing_key(),
+                &z.fvk,
             );
-            let address = z.clone().address;
+            let address = z.address.clone();
 
-            if z.extsk.is_some() {
+            if let Some(extsk) = &z.extsk {
                 sk = encode_extended_spending_key(
-                    HRP_SAPLING_EXTENDED_SPENDING_KEY,
-                    &z.extsk.unwrap(),
+                    network.hrp_sapling_extended_spending_key(),
+                    extsk,
                 );
             }
             (Some(sk), Some(ivk), Some(address))
@@ -588,44 +737,37 @@ pub fn create_account_with_keys(
         "INSERT INTO accounts (id_account, name, seed, aindex, sk, ivk, address) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
         (
             id as u32,
-            account.name,
+            &account.name,
             seed,
             id,
             extsk,
             ivk,
             address
         )
-    ).expect("Unable to insert values");
+    ).map_err(ParseError::from)?;
 
     // Then handle orchard keys
-    if account.keys.okeys.is_some() {
-        let sk = account
-            .keys
-            .okeys
-            .clone()
-            .unwrap()
-            .sk
-            .expect("Invalid orchard sk");
-        let fvk = account
-            .keys
-            .okeys
-            .clone()
-            .unwrap()
-            .fvk
-            .expect("Invalid orchard fvk");
+    if let Some(okey) = okey {
+        let (Some(sk), Some(fvk)) = (okey.sk, okey.fvk.as_ref()) else {
+            return Err(ParseError::InvalidKey(format!(
+                "Orchard key of account {} has no spending key",
+                account.name
+            ))
+            .into());
+        };
 
         // insert orchard_addrs table
         conn.execute(
             "INSERT INTO orchard_addrs (account, sk, fvk) VALUES (?1, ?2, ?3)",
             (id as u32, sk.to_bytes(), fvk.to_bytes()),
         )
-        .expect("Unable to insert values");
+        .map_err(ParseError::from)?;
     }
 
     // Add transparent addresses and keys
-    if account.keys.tkeys.is_some() {
-        let pk = account.keys.tkeys.clone().unwrap().pk;
-        let taddress = account.keys.tkeys.unwrap().address;
+    if let Some(tkey) = tkey {
+        let pk = tkey.pk;
+        let taddress = tkey.address.clone();
 
         // insert taddrs table
         conn.execute(
@@ -637,7 +779,7 @@ pub fn create_account_with_keys(
                 0,
             ),
         )
-        .expect("Unable to insert values");
+        .map_err(ParseError::from)?;
     }
 
     // db extra configuration
@@ -645,19 +787,14 @@ pub fn create_account_with_keys(
         "INSERT INTO accounts2 (account, saved) VALUES (?1, ?2)",
         (id as u32, 0),
     )
-    .expect("Unable to insert values");
+    .map_err(ParseError::from)?;
 
     // configure ua settings
     conn.execute(
         "INSERT INTO ua_settings (account, transparent, sapling, orchard) VALUES (?1, ?2, ?3, ?4)",
-        (
-            id as u32,
-            0,
-            1,
-            if account.keys.okeys.is_some() { 1 } else { 0 },
-        ),
+        (id as u32, 0, 1, if okey.is_some() { 1 } else { 0 }),
     )
-    .expect("Unable to insert values");
+    .map_err(ParseError::from)?;
 
     Ok(())
 }
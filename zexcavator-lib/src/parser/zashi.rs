@@ -0,0 +1,592 @@
+//! # Zashi Parser
+//!
+//! Reads the SQLite databases of `zcash_client_sqlite`, the wallet backend of Zashi
+//! and of the other wallets built on `zcash_client_backend`.
+//!
+//! ## Overview
+//! The parser extracts the following information from the database:
+//! - **Accounts**: the `accounts` table, with the UFVK (or only the UIVK) of each
+//!   account, its ZIP 32 account index and seed fingerprint, and its birthday height.
+//! - **Network**: from the encoding of the account viewing keys.
+//! - **Note inventory**: the unspent notes and transparent outputs of each account,
+//!   from `sapling_received_notes`, `orchard_received_notes` and
+//!   `transparent_received_outputs`.
+//!
+//! ## Caveats
+//! - The database holds no seed or spending key. The seed phrase must be given with
+//!   [`WalletParser::set_mnemonic`], and is checked against the account UFVKs. Until
+//!   then, only the viewing keys and note inventory of the wallet are known.
+//! - Accounts derived from another seed, and imported viewing key accounts, can't be
+//!   swept and are only reported.
+//! - Databases older than the `account_kind` migration have no seed fingerprint, their
+//!   accounts are identified by their account index.
+
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+
+use bip0039::{English, Mnemonic};
+use rusqlite::{Connection, OpenFlags};
+use zcash_address::unified::{Encoding, Ufvk, Uivk};
+use zcash_keys::keys::{UnifiedFullViewingKey, UnifiedSpendingKey};
+use zcash_primitives::zip32::AccountId;
+use zip32::fingerprint::SeedFingerprint;
+
+use super::{WalletAccount, WalletImportedKeys, WalletKeys, WalletParser};
+use crate::settings::Network;
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+fn sql_error(e: rusqlite::Error) -> io::Error {
+    invalid(format!("Database error: {}", e))
+}
+
+pub(super) fn has_table(conn: &Connection, table: &str) -> bool {
+    conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        [table],
+        |row| row.get::<_, u32>(0),
+    )
+    .is_ok_and(|count| count > 0)
+}
+
+pub(super) fn has_column(conn: &Connection, table: &str, column: &str) -> bool {
+    conn.prepare(&format!("SELECT {} FROM {} LIMIT 0", column, table))
+        .is_ok()
+}
+
+/// Whether the SQLite database at `path` is a `zcash_client_sqlite` wallet.
+pub fn is_client_sqlite(path: &Path) -> bool {
+    match Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY) {
+        Ok(conn) => {
+            has_table(&conn, "sapling_received_notes") && has_column(&conn, "accounts", "ufvk")
+        }
+        Err(_) => false,
+    }
+}
+
+/// Unspent notes, or transparent outputs, of an account in one pool.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PoolBalance {
+    pub count: u32,
+    /// Value in zatoshis.
+    pub value: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NoteInventory {
+    pub transparent: PoolBalance,
+    pub sapling: PoolBalance,
+    pub orchard: PoolBalance,
+}
+
+impl NoteInventory {
+    pub fn total(&self) -> u64 {
+        self.transparent.value + self.sapling.value + self.orchard.value
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ClientAccount {
+    pub id: u32,
+    pub name: Option<String>,
+    /// ZIP 32 account index of accounts derived from a seed.
+    pub hd_account_index: Option<u32>,
+    pub seed_fingerprint: Option<[u8; 32]>,
+    /// Full viewing key, absent for accounts imported from an incoming viewing key.
+    pub ufvk: Option<UnifiedFullViewingKey>,
+    pub uivk: Option<String>,
+    pub birthday_height: u32,
+    pub notes: NoteInventory,
+}
+
+impl ClientAccount {
+    /// Whether the account is derived from `seed`, checked against its UFVK.
+    fn is_derived_from(&self, network: Network, seed: &[u8]) -> bool {
+        let (Some(index), Some(ufvk)) = (self.hd_account_index, &self.ufvk) else {
+            return false;
+        };
+        match (self.seed_fingerprint, SeedFingerprint::from_seed(seed)) {
+            (Some(expected), Some(fingerprint)) if expected != fingerprint.to_bytes() => {
+                return false;
+            }
+            _ => (),
+        }
+
+        let Ok(account) = AccountId::try_from(index) else {
+            return false;
+        };
+        let Ok(usk) = UnifiedSpendingKey::from_seed(&network.params(), seed, account) else {
+            return false;
+        };
+        let derived = usk.to_unified_full_viewing_key();
+        let sapling_matches = ufvk
+            .sapling()
+            .is_none_or(|k| derived.sapling().map(|d| d.to_bytes()) == Some(k.to_bytes()));
+        let orchard_matches = ufvk
+            .orchard()
+            .is_none_or(|k| derived.orchard().map(|d| d.to_bytes()) == Some(k.to_bytes()));
+
+        (ufvk.sapling().is_some() || ufvk.orchard().is_some()) && sapling_matches && orchard_matches
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ZashiWallet {
+    pub network: Network,
+    pub accounts: Vec<ClientAccount>,
+    /// `zcash_client_sqlite` schema migrations applied to the database.
+    pub migrations: u32,
+    /// Seed phrase given by the user, checked against the accounts.
+    mnemonic: Option<String>,
+}
+
+impl ZashiWallet {
+    pub fn from_connection(conn: &Connection) -> io::Result<Self> {
+        let (mut accounts, network) = Self::read_accounts(conn)?;
+
+        let inventory = [
+            (
+                "transparent_received_outputs",
+                "value_zat",
+                "transparent_received_output_spends",
+                "transparent_received_output_id",
+            ),
+            (
+                "sapling_received_notes",
+                "value",
+                "sapling_received_note_spends",
+                "sapling_received_note_id",
+            ),
+            (
+                "orchard_received_notes",
+                "value",
+                "orchard_received_note_spends",
+                "orchard_received_note_id",
+            ),
+        ]
+        .map(|(table, value, spends, spend_column)| {
+            Self::read_unspent(conn, table, value, spends, spend_column)
+        });
+        for account in accounts.iter_mut() {
+            let balance = |pool: &HashMap<u32, PoolBalance>| {
+                pool.get(&account.id).copied().unwrap_or_default()
+            };
+            account.notes = NoteInventory {
+                transparent: balance(&inventory[0]),
+                sapling: balance(&inventory[1]),
+                orchard: balance(&inventory[2]),
+            };
+        }
+
+        let migrations = if has_table(conn, "schemer_migrations") {
+            conn.query_row("SELECT COUNT(*) FROM schemer_migrations", [], |row| {
+                row.get(0)
+            })
+            .map_err(sql_error)?
+        } else {
+            0
+        };
+
+        Ok(Self {
+            network,
+            accounts,
+            migrations,
+            mnemonic: None,
+        })
+    }
+
+    /// Reads the accounts and the network of their viewing keys.
+    fn read_accounts(conn: &Connection) -> io::Result<(Vec<ClientAccount>, Network)> {
+        // `account_kind` replaced the ZIP 32 account number used as the account id
+        let query = match (
+            has_column(conn, "accounts", "account_kind"),
+            has_column(conn, "accounts", "name"),
+        ) {
+            (true, true) => {
+                "SELECT id, name, hd_account_index, hd_seed_fingerprint, ufvk, uivk, birthday_height FROM accounts"
+            }
+            (true, false) => {
+                "SELECT id, NULL, hd_account_index, hd_seed_fingerprint, ufvk, uivk, birthday_height FROM accounts"
+            }
+            (false, _) => {
+                "SELECT account, NULL, account, NULL, ufvk, NULL, birthday_height FROM accounts"
+            }
+        };
+
+        let mut stmt = conn.prepare(query).map_err(sql_error)?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, u32>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, Option<u32>>(2)?,
+                    row.get::<_, Option<Vec<u8>>>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, Option<String>>(5)?,
+                    row.get::<_, u32>(6)?,
+                ))
+            })
+            .map_err(sql_error)?;
+
+        let mut accounts = vec![];
+        let mut network = None;
+        for row in rows {
+            let (id, name, hd_account_index, fingerprint, ufvk, uivk, birthday_height) =
+                row.map_err(sql_error)?;
+            if let Some((network_type, _)) = uivk.as_deref().and_then(|u| Uivk::decode(u).ok()) {
+                network.get_or_insert(Network::from_network_type(network_type));
+            }
+            let ufvk = ufvk
+                .map(|encoded| {
+                    let (network_type, ufvk) = Ufvk::decode(&encoded)
+                        .map_err(|e| invalid(format!("Invalid UFVK of account {}: {}", id, e)))?;
+                    network.get_or_insert(Network::from_network_type(network_type));
+                    UnifiedFullViewingKey::parse(&ufvk)
+                        .map_err(|e| invalid(format!("Invalid UFVK of account {}: {}", id, e)))
+                })
+                .transpose()?;
+            let seed_fingerprint = fingerprint
+                .map(|f| {
+                    f.try_into()
+                        .map_err(|_| invalid(format!("Invalid seed fingerprint of account {}", id)))
+                })
+                .transpose()?;
+
+            accounts.push(ClientAccount {
+                id,
+                name,
+                hd_account_index,
+                seed_fingerprint,
+                uivk,
+                ufvk,
+                birthday_height,
+                notes: NoteInventory::default(),
+            });
+        }
+
+        Ok((accounts, network.unwrap_or_default()))
+    }
+
+    /// Unspent outputs of `table` by account. Tables missing from older schemas are
+    /// treated as empty.
+    fn read_unspent(
+        conn: &Connection,
+        table: &str,
+        value: &str,
+        spends: &str,
+        spend_column: &str,
+    ) -> HashMap<u32, PoolBalance> {
+        if !has_table(conn, table) {
+            return HashMap::new();
+        }
+
+        let account = match has_column(conn, table, "account_id") {
+            true => "account_id",
+            false => "account",
+        };
+        let unspent = if has_table(conn, spends) {
+            format!("id NOT IN (SELECT {} FROM {})", spend_column, spends)
+        } else if has_column(conn, table, "spent") {
+            "spent IS NULL".to_string()
+        } else {
+            "1".to_string()
+        };
+        let query = format!(
+            "SELECT {account}, COUNT(*), COALESCE(SUM({value}), 0) FROM {table} WHERE {unspent} GROUP BY {account}"
+        );
+
+        let Ok(mut stmt) = conn.prepare(&query) else {
+            return HashMap::new();
+        };
+        stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, u32>(0)?,
+                PoolBalance {
+                    count: row.get(1)?,
+                    value: row.get::<_, i64>(2)? as u64,
+                },
+            ))
+        })
+        .map(|rows| rows.filter_map(Result::ok).collect())
+        .unwrap_or_default()
+    }
+
+    fn seed(&self) -> Option<[u8; 64]> {
+        let phrase = self.mnemonic.as_deref()?;
+        Some(<Mnemonic<English>>::from_phrase(phrase).ok()?.to_seed(""))
+    }
+
+    /// Accounts derived from the seed phrase given with
+    /// [`WalletParser::set_mnemonic`].
+    fn hd_accounts(&self) -> Vec<&ClientAccount> {
+        match self.seed() {
+            Some(seed) => self
+                .accounts
+                .iter()
+                .filter(|a| a.is_derived_from(self.network, &seed))
+                .collect(),
+            None => vec![],
+        }
+    }
+}
+
+impl WalletParser for ZashiWallet {
+    fn read(filename: &str) -> io::Result<Self> {
+        let conn = Connection::open_with_flags(filename, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(sql_error)?;
+        Self::from_connection(&conn)
+    }
+
+    fn get_wallet_name(&self) -> String {
+        "Zashi".to_string()
+    }
+
+    /// Number of schema migrations applied to the database.
+    fn get_wallet_version(&self) -> u64 {
+        self.migrations as u64
+    }
+
+    fn get_network(&self) -> Network {
+        self.network
+    }
+
+    /// Entropy of the seed phrase given with [`WalletParser::set_mnemonic`], which
+    /// can be a 12, 15, 18, 21 or 24 word phrase.
+    fn get_wallet_seed(&self) -> Option<Vec<u8>> {
+        self.mnemonic
+            .as_deref()
+            .and_then(|phrase| <Mnemonic<English>>::from_phrase(phrase).ok())
+            .map(|m| m.entropy().to_vec())
+    }
+
+    fn get_wallet_accounts(&self) -> io::Result<Vec<WalletAccount>> {
+        let Some(seed) = self.seed() else {
+            return Ok(vec![]);
+        };
+        let entropy = self.get_wallet_seed();
+
+        Ok(self
+            .hd_accounts()
+            .into_iter()
+            .filter_map(|account| {
+                let index = account.hd_account_index?;
+                Some(WalletAccount {
+                    name: account
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| format!("Account {}", index + 1)),
+                    seed: entropy.clone(),
+                    birthday: account.birthday_height.into(),
+                    keys: WalletKeys::from_seed(self.network, &seed, index),
+                })
+            })
+            .collect())
+    }
+
+    fn get_birthday(&self) -> u64 {
+        let hd_accounts = self.hd_accounts();
+        let accounts = match hd_accounts.is_empty() {
+            true => self.accounts.iter().collect(),
+            false => hd_accounts,
+        };
+        accounts
+            .iter()
+            .map(|a| a.birthday_height as u64)
+            .min()
+            .unwrap_or_default()
+    }
+
+    fn get_hd_account_count(&self) -> u32 {
+        self.hd_accounts()
+            .iter()
+            .filter_map(|a| a.hd_account_index)
+            .max()
+            .map_or(1, |n| n + 1)
+    }
+
+    fn get_hd_transparent_address_count(&self) -> u32 {
+        1
+    }
+
+    /// The database holds no spending key, so nothing but the seed can be swept.
+    fn get_imported_keys(&self) -> WalletImportedKeys {
+        WalletImportedKeys::default()
+    }
+
+    fn requires_mnemonic(&self) -> bool {
+        self.mnemonic.is_none()
+    }
+
+    fn set_mnemonic(&mut self, phrase: &str) -> io::Result<()> {
+        let mnemonic = <Mnemonic<English>>::from_phrase(phrase.trim())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Invalid seed phrase"))?;
+        let seed = mnemonic.to_seed("");
+        if !self
+            .accounts
+            .iter()
+            .any(|a| a.is_derived_from(self.network, &seed))
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "Seed phrase doesn't match any account of the wallet",
+            ));
+        }
+
+        self.mnemonic = Some(mnemonic.phrase().to_string());
+        Ok(())
+    }
+
+    fn print_internal(&self) {
+        println!(
+            "Zashi wallet: {} migrations, network {}, {} accounts",
+            self.migrations,
+            self.network,
+            self.accounts.len()
+        );
+        for account in &self.accounts {
+            let kind = match account.hd_account_index {
+                Some(index) => format!("ZIP 32 account {}", index),
+                None => "imported".to_string(),
+            };
+            println!(
+                "  Account {} ({}{}), birthday {}, ufvk: {}",
+                account.id,
+                kind,
+                account
+                    .name
+                    .as_deref()
+                    .map(|n| format!(", {}", n))
+                    .unwrap_or_default(),
+                account.birthday_height,
+                account.ufvk.is_some()
+            );
+            let notes = &account.notes;
+            println!(
+                "    unspent: {} transparent ({} zats), {} sapling ({} zats), {} orchard ({} zats)",
+                notes.transparent.count,
+                notes.transparent.value,
+                notes.sapling.count,
+                notes.sapling.value,
+                notes.orchard.count,
+                notes.orchard.value
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zcash_primitives::consensus::MainNetwork;
+
+    const PHRASE: &str = "clerk family rack dragon cannon wait vendor penalty absent country better coast expand true middle stable assist clerk tent phone toilet knee female kitchen";
+    const OTHER_PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    fn database() -> Connection {
+        database_for(PHRASE)
+    }
+
+    fn database_for(phrase: &str) -> Connection {
+        let seed = <Mnemonic<English>>::from_phrase(phrase)
+            .unwrap()
+            .to_seed("");
+        let ufvk = UnifiedSpendingKey::from_seed(&MainNetwork, &seed, AccountId::ZERO)
+            .unwrap()
+            .to_unified_full_viewing_key();
+        let fingerprint = SeedFingerprint::from_seed(&seed).unwrap().to_bytes();
+
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE accounts (
+                id INTEGER PRIMARY KEY, name TEXT, account_kind INTEGER NOT NULL,
+                hd_seed_fingerprint BLOB, hd_account_index INTEGER, ufvk TEXT,
+                uivk TEXT NOT NULL, birthday_height INTEGER NOT NULL);
+            CREATE TABLE sapling_received_notes (id INTEGER PRIMARY KEY, account_id INTEGER, value INTEGER);
+            CREATE TABLE sapling_received_note_spends (sapling_received_note_id INTEGER, transaction_id INTEGER);
+            CREATE TABLE orchard_received_notes (id INTEGER PRIMARY KEY, account_id INTEGER, value INTEGER);
+            INSERT INTO sapling_received_notes VALUES (1, 1, 5000), (2, 1, 7000), (3, 2, 100);
+            INSERT INTO sapling_received_note_spends VALUES (2, 9);
+            INSERT INTO orchard_received_notes VALUES (1, 1, 250000);",
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO accounts VALUES (1, 'Zashi', 0, ?1, 0, ?2, ?3, 2100000)",
+            rusqlite::params![
+                fingerprint.to_vec(),
+                ufvk.encode(&MainNetwork),
+                ufvk.to_unified_incoming_viewing_key().encode(&MainNetwork)
+            ],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO accounts VALUES (2, 'Keystone', 1, NULL, NULL, NULL, ?1, 2500000)",
+            [ufvk.to_unified_incoming_viewing_key().encode(&MainNetwork)],
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_zashi_accounts() {
+        let mut wallet = ZashiWallet::from_connection(&database()).unwrap();
+        assert_eq!(wallet.get_network(), Network::Mainnet);
+        assert_eq!(wallet.accounts.len(), 2);
+        assert_eq!(
+            wallet.accounts[0].notes.sapling,
+            PoolBalance {
+                count: 1,
+                value: 5000
+            }
+        );
+        assert_eq!(wallet.accounts[0].notes.total(), 255000);
+        assert_eq!(wallet.accounts[1].hd_account_index, None);
+
+        // Spending keys need the seed phrase
+        assert!(wallet.requires_mnemonic());
+        assert_eq!(wallet.get_wallet_seed(), None);
+        assert!(wallet.get_wallet_accounts().unwrap().is_empty());
+        let err = wallet.set_mnemonic(OTHER_PHRASE).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        assert!(wallet.requires_mnemonic());
+
+        wallet.set_mnemonic(PHRASE).unwrap();
+        assert!(!wallet.requires_mnemonic());
+        let entropy = <Mnemonic<English>>::from_phrase(PHRASE)
+            .unwrap()
+            .entropy()
+            .to_vec();
+        assert_eq!(wallet.get_wallet_seed(), Some(entropy));
+        assert_eq!(wallet.get_birthday(), 2100000);
+        assert_eq!(wallet.get_hd_account_count(), 1);
+        let accounts = wallet.get_wallet_accounts().unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].name, "Zashi");
+        assert!(accounts[0].keys.zkeys[0].address.starts_with("zs1"));
+        assert!(accounts[0].keys.tkeys[0].address.starts_with("t1"));
+    }
+
+    #[test]
+    fn test_zashi_corrupt() {
+        // Not a zcash_client_sqlite database
+        assert!(ZashiWallet::from_connection(&Connection::open_in_memory().unwrap()).is_err());
+
+        let conn = database();
+        conn.execute(
+            "UPDATE accounts SET ufvk = 'uview1corrupt' WHERE id = 1",
+            [],
+        )
+        .unwrap();
+        assert!(ZashiWallet::from_connection(&conn).is_err());
+    }
+
+    #[test]
+    fn test_zashi_short_phrase() {
+        // Other zcash_client_sqlite wallets can use 12 word phrases
+        let mut wallet = ZashiWallet::from_connection(&database_for(OTHER_PHRASE)).unwrap();
+        wallet.set_mnemonic(OTHER_PHRASE).unwrap();
+        assert_eq!(wallet.get_wallet_seed().map(|s| s.len()), Some(16));
+    }
+}
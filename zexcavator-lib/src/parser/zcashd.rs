@@ -0,0 +1,945 @@
+//! # zcashd Parser
+//!
+//! Reads the `wallet.dat` files of zcashd. The file is a Berkeley DB btree, read by
+//! the [`bdb`] module without linking libdb. Every record is keyed by its type,
+//! followed by type specific data, both serialized like the zcashd network format.
+//!
+//! ## Overview
+//! The parser extracts the following records:
+//! - **Seed**: the BIP-39 mnemonic (`mnemonicphrase`) of zcashd 4.7 and later, or the
+//!   legacy random seed (`hdseed`) of older wallets.
+//! - **Transparent keys**: `key` records, with their derivation path from `keymeta`.
+//! - **Sapling keys**: extended spending keys (`sapzkey`) with their derivation path
+//!   from `sapzkeymeta`, and watch-only extended full viewing keys (`sapextfvk`).
+//! - **Network**: the `networkinfo` record.
+//! - **Birthday**: estimated from the creation time of the oldest key.
+//!
+//! Keys derived from the mnemonic are grouped by ZIP 32 account, every other key is
+//! an imported key.
+//!
+//! Encrypted wallets hold their secrets in `ckey`, `csapzkey`, `chdseed` and
+//! `cmnemonicphrase` records instead, decrypted with the wallet passphrase by
+//! [`WalletParser::decrypt`], see [`crypter`]. Until then, only the viewing keys of
+//! the wallet are known.
+//!
+//! The text dumps of `z_exportwallet` and `dumpwallet` are read by [`dump`].
+//!
+//! ## Caveats
+//! - The legacy seed isn't a BIP-39 seed, so keys derived from it are reported as
+//!   imported keys and [`WalletParser::get_wallet_seed`] is `None`. Wallets with only
+//!   a legacy seed can't be synced from their seed.
+//! - Sprout keys (`zkey`) can't be synced and are only counted.
+//! - zcashd stores no birthday height. It is estimated from the key creation times,
+//!   with a margin of a week.
+
+pub mod bdb;
+pub mod crypter;
+pub mod dump;
+
+use std::collections::BTreeMap;
+use std::io::{self, Cursor, Read};
+use std::path::Path;
+
+use bip0039::{English, Mnemonic};
+use byteorder::{LittleEndian, ReadBytesExt};
+use ripemd::Ripemd160;
+use sapling::PaymentAddress;
+use sapling::zip32::{ExtendedFullViewingKey, ExtendedSpendingKey};
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+use sha2::{Digest, Sha256};
+use zcash_client_backend::encoding::{encode_payment_address, encode_transparent_address};
+use zcash_encoding::Vector;
+use zcash_primitives::consensus::{BlockHeight, NetworkConstants, NetworkUpgrade, Parameters};
+use zcash_primitives::legacy::TransparentAddress;
+
+use super::{
+    KeyScope, WalletAccount, WalletImportedKeys, WalletKeyType, WalletKeys, WalletParser,
+    WalletTKey, WalletZKey,
+};
+use crate::settings::Network;
+use crypter::MasterKey;
+
+/// ZIP 32 purpose of sapling derivation paths.
+const SAPLING_PURPOSE: u32 = 32;
+/// BIP 44 purpose of transparent derivation paths.
+const TRANSPARENT_PURPOSE: u32 = 44;
+/// First `CKeyMetadata` version holding the derivation path and seed fingerprint.
+const KEY_METADATA_VERSION_WITH_HDDATA: i32 = 10;
+/// Blocks subtracted from the estimated birthday, about a week of blocks.
+const BIRTHDAY_MARGIN: u32 = 8064;
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+fn read_string<R: Read>(mut reader: R) -> io::Result<String> {
+    let bytes = Vector::read(&mut reader, |r| r.read_u8())?;
+    String::from_utf8(bytes).map_err(|_| invalid("Invalid UTF-8 string"))
+}
+
+fn read_u256<R: Read>(mut reader: R) -> io::Result<[u8; 32]> {
+    let mut bytes = [0u8; 32];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn extfvk_bytes(extfvk: &ExtendedFullViewingKey) -> Vec<u8> {
+    let mut bytes = vec![];
+    extfvk
+        .write(&mut bytes)
+        .expect("writing to a Vec can't fail");
+    bytes
+}
+
+/// P2PKH address of a transparent public key, compressed or not.
+pub(crate) fn transparent_address(network: Network, pubkey: &[u8]) -> String {
+    let sha = Sha256::digest(pubkey);
+    let hash: [u8; 20] = Ripemd160::digest(sha).into();
+    let network = network.network_type();
+    encode_transparent_address(
+        &network.b58_pubkey_address_prefix(),
+        &network.b58_script_address_prefix(),
+        &TransparentAddress::PublicKeyHash(hash),
+    )
+}
+
+/// Default sapling address of a viewing key.
+pub(crate) fn sapling_address(network: Network, extfvk: &ExtendedFullViewingKey) -> String {
+    let (_, address) = extfvk.default_address();
+    encode_payment_address(
+        network.network_type().hrp_sapling_payment_address(),
+        &address,
+    )
+}
+
+/// Estimates the block height at unix time `time`, from the genesis time and the
+/// block target spacing before and after Blossom.
+fn height_at(network: Network, time: i64) -> u32 {
+    let (genesis_time, blossom_height) = match network {
+        Network::Mainnet => (1_477_641_360, 653_600),
+        Network::Testnet => (1_477_648_033, 584_000),
+        Network::Regtest => return 0,
+    };
+
+    let elapsed = (time - genesis_time).max(0) as u64;
+    let blossom_time = blossom_height as u64 * 150;
+    let height = match elapsed {
+        e if e < blossom_time => e / 150,
+        e => blossom_height as u64 + (e - blossom_time) / 75,
+    };
+    height.min(u32::MAX as u64) as u32
+}
+
+/// Birthday height of a wallet whose oldest key was created at unix time `oldest`:
+/// a week before the estimated height, and never below the sapling activation
+/// height, which is also used when the creation time is unknown.
+pub(crate) fn estimate_birthday(network: Network, oldest: Option<i64>) -> u64 {
+    let sapling_activation = network
+        .params()
+        .activation_height(NetworkUpgrade::Sapling)
+        .map_or(0, u32::from);
+
+    let height = match oldest {
+        Some(time) => height_at(network, time).saturating_sub(BIRTHDAY_MARGIN),
+        None => 0,
+    };
+    height.max(sapling_activation) as u64
+}
+
+/// Secret key of a DER encoded OpenSSL EC private key, as written by zcashd:
+/// `SEQUENCE { INTEGER 1, OCTET STRING <secret>, ... }`.
+fn der_secret_key(der: &[u8]) -> Option<SecretKey> {
+    if *der.first()? != 0x30 {
+        return None;
+    }
+    // Skip the sequence length, in short or long form
+    let start = match *der.get(1)? {
+        len if len & 0x80 != 0 => 2 + (len & 0x7f) as usize,
+        _ => 2,
+    };
+    let rest = der.get(start..)?;
+    match rest {
+        [0x02, 0x01, 0x01, 0x04, 0x20, secret @ ..] if secret.len() >= 32 => {
+            SecretKey::from_slice(&secret[..32]).ok()
+        }
+        _ => None,
+    }
+}
+
+/// zcashd `CKeyMetadata`.
+#[derive(Debug, Clone, Default)]
+pub struct KeyMetadata {
+    pub create_time: i64,
+    /// Derivation path such as `m/32'/133'/0'`, empty for keys not derived from a seed.
+    pub hd_keypath: String,
+    pub seed_fp: Option<[u8; 32]>,
+}
+
+impl KeyMetadata {
+    fn read<R: Read>(mut reader: R) -> io::Result<Self> {
+        let version = reader.read_i32::<LittleEndian>()?;
+        let create_time = reader.read_i64::<LittleEndian>()?;
+        if version < KEY_METADATA_VERSION_WITH_HDDATA {
+            return Ok(Self {
+                create_time,
+                ..Default::default()
+            });
+        }
+
+        Ok(Self {
+            create_time,
+            hd_keypath: read_string(&mut reader)?,
+            seed_fp: Some(read_u256(&mut reader)?),
+        })
+    }
+
+    /// Derivation path of an HD key, as zcashd wrote it.
+    fn hd_path(&self) -> Option<String> {
+        (!self.hd_keypath.is_empty()).then(|| self.hd_keypath.clone())
+    }
+
+    /// Components of the derivation path, without the hardened markers.
+    fn path(&self) -> Option<Vec<u32>> {
+        let mut components = self.hd_keypath.split('/');
+        if components.next()? != "m" {
+            return None;
+        }
+        components
+            .map(|c| c.trim_end_matches(['\'', 'h']).parse().ok())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TransparentKey {
+    pub pubkey: Vec<u8>,
+    pub secret: SecretKey,
+    pub metadata: Option<KeyMetadata>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SaplingKey {
+    pub extsk: Option<ExtendedSpendingKey>,
+    pub extfvk: ExtendedFullViewingKey,
+    pub metadata: Option<KeyMetadata>,
+    /// Addresses zcashd gave out for this key, from its `sapzaddr` records.
+    pub addresses: Vec<PaymentAddress>,
+}
+
+/// Secret encrypted under the wallet master key, with the hash its IV is taken from.
+#[derive(Debug, Clone)]
+pub struct CryptedSecret {
+    pub iv_hash: [u8; 32],
+    pub crypted: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ZcashdWallet {
+    /// zcashd client version that last wrote the wallet.
+    pub version: u64,
+    pub network: Network,
+    /// Mnemonic of the wallet and the fingerprint of its seed.
+    pub mnemonic: Option<(String, [u8; 32])>,
+    /// Legacy random seed of wallets created before zcashd 4.7.
+    pub legacy_seed: Option<Vec<u8>>,
+    pub hd_chain_create_time: Option<i64>,
+    pub tkeys: Vec<TransparentKey>,
+    pub zkeys: Vec<SaplingKey>,
+    pub sprout_key_count: usize,
+    /// Master keys of an encrypted wallet, until it is decrypted.
+    pub master_keys: Vec<MasterKey>,
+    pub crypted_mnemonic: Option<CryptedSecret>,
+    pub crypted_legacy_seed: Option<CryptedSecret>,
+    /// Encrypted transparent keys, with their public key and metadata.
+    pub crypted_tkeys: Vec<(Vec<u8>, CryptedSecret, Option<KeyMetadata>)>,
+    /// Encrypted sapling spending keys, by index in `zkeys`.
+    pub crypted_zkeys: Vec<(usize, CryptedSecret)>,
+}
+
+impl ZcashdWallet {
+    /// Builds the wallet from the key/value records of the `main` database.
+    pub fn from_records(records: &[(Vec<u8>, Vec<u8>)]) -> io::Result<Self> {
+        let mut wallet = Self {
+            version: 0,
+            network: Network::Mainnet,
+            mnemonic: None,
+            legacy_seed: None,
+            hd_chain_create_time: None,
+            tkeys: vec![],
+            zkeys: vec![],
+            sprout_key_count: 0,
+            master_keys: vec![],
+            crypted_mnemonic: None,
+            crypted_legacy_seed: None,
+            crypted_tkeys: vec![],
+            crypted_zkeys: vec![],
+        };
+
+        let mut secret_keys = BTreeMap::new();
+        let mut key_metadata = BTreeMap::new();
+        let mut sapling_keys = BTreeMap::new();
+        let mut sapling_metadata = BTreeMap::new();
+        let mut sapling_addresses: BTreeMap<[u8; 32], Vec<PaymentAddress>> = BTreeMap::new();
+        let mut viewing_keys = vec![];
+        let mut crypted_keys = vec![];
+        let mut crypted_sapling_keys = vec![];
+
+        for (key, value) in records {
+            let mut key = Cursor::new(key.as_slice());
+            let mut value = Cursor::new(value.as_slice());
+            let Ok(record_type) = read_string(&mut key) else {
+                continue;
+            };
+
+            match record_type.as_str() {
+                "version" => wallet.version = value.read_i32::<LittleEndian>()? as u64,
+                "networkinfo" => {
+                    let _name = read_string(&mut value)?;
+                    let network = read_string(&mut value)?;
+                    wallet.network = network
+                        .parse()
+                        .map_err(|_| invalid(format!("Unknown network {}", network)))?;
+                }
+                "mnemonicphrase" => {
+                    let seed_fp = read_u256(&mut key)?;
+                    let language = value.read_u32::<LittleEndian>()?;
+                    if language != 0 {
+                        return Err(invalid("Only English mnemonics are supported"));
+                    }
+                    wallet.mnemonic = Some((read_string(&mut value)?, seed_fp));
+                }
+                "cmnemonicphrase" => {
+                    let seed_fp = read_u256(&mut key)?;
+                    wallet.crypted_mnemonic = Some(CryptedSecret {
+                        iv_hash: seed_fp,
+                        crypted: Vector::read(&mut value, |r| r.read_u8())?,
+                    });
+                }
+                "hdseed" => {
+                    wallet.legacy_seed = Some(Vector::read(&mut value, |r| r.read_u8())?);
+                }
+                "chdseed" => {
+                    let seed_fp = read_u256(&mut key)?;
+                    wallet.crypted_legacy_seed = Some(CryptedSecret {
+                        iv_hash: seed_fp,
+                        crypted: Vector::read(&mut value, |r| r.read_u8())?,
+                    });
+                }
+                "hdchain" => {
+                    let _version = value.read_i32::<LittleEndian>()?;
+                    let _seed_fp = read_u256(&mut value)?;
+                    wallet.hd_chain_create_time = Some(value.read_i64::<LittleEndian>()?);
+                }
+                "key" => {
+                    let pubkey = Vector::read(&mut key, |r| r.read_u8())?;
+                    let der = Vector::read(&mut value, |r| r.read_u8())?;
+                    let secret = der_secret_key(&der)
+                        .ok_or_else(|| invalid("Invalid transparent private key"))?;
+                    secret_keys.insert(pubkey, secret);
+                }
+                "ckey" => {
+                    let pubkey = Vector::read(&mut key, |r| r.read_u8())?;
+                    let crypted = Vector::read(&mut value, |r| r.read_u8())?;
+                    crypted_keys.push((pubkey, crypted));
+                }
+                "keymeta" => {
+                    let pubkey = Vector::read(&mut key, |r| r.read_u8())?;
+                    key_metadata.insert(pubkey, KeyMetadata::read(&mut value)?);
+                }
+                "sapzkey" => {
+                    let ivk = read_u256(&mut key)?;
+                    sapling_keys.insert(ivk, ExtendedSpendingKey::read(&mut value)?);
+                }
+                "csapzkey" => {
+                    let ivk = read_u256(&mut key)?;
+                    let extfvk = ExtendedFullViewingKey::read(&mut value)?;
+                    let crypted = Vector::read(&mut value, |r| r.read_u8())?;
+                    crypted_sapling_keys.push((ivk, extfvk, crypted));
+                }
+                "sapzkeymeta" => {
+                    let ivk = read_u256(&mut key)?;
+                    sapling_metadata.insert(ivk, KeyMetadata::read(&mut value)?);
+                }
+                "sapzaddr" => {
+                    let mut bytes = [0u8; 43];
+                    key.read_exact(&mut bytes)?;
+                    let address = PaymentAddress::from_bytes(&bytes)
+                        .ok_or_else(|| invalid("Invalid sapling address"))?;
+                    let ivk = read_u256(&mut value)?;
+                    sapling_addresses.entry(ivk).or_default().push(address);
+                }
+                "sapextfvk" => viewing_keys.push(ExtendedFullViewingKey::read(&mut key)?),
+                "zkey" | "czkey" => wallet.sprout_key_count += 1,
+                "mkey" => wallet.master_keys.push(MasterKey::read(&mut value)?),
+                _ => (),
+            }
+        }
+
+        wallet.tkeys = secret_keys
+            .into_iter()
+            .map(|(pubkey, secret)| TransparentKey {
+                metadata: key_metadata.get(&pubkey).cloned(),
+                pubkey,
+                secret,
+            })
+            .collect();
+
+        // Viewing keys of spending keys are stored as `sapextfvk` too
+        let mut zkeys: Vec<SaplingKey> = sapling_keys
+            .into_iter()
+            .map(|(ivk, extsk)| SaplingKey {
+                extfvk: extsk.to_extended_full_viewing_key(),
+                extsk: Some(extsk),
+                metadata: sapling_metadata.get(&ivk).cloned(),
+                addresses: sapling_addresses.get(&ivk).cloned().unwrap_or_default(),
+            })
+            .collect();
+        for (ivk, extfvk, crypted) in crypted_sapling_keys {
+            wallet.crypted_zkeys.push((
+                zkeys.len(),
+                CryptedSecret {
+                    iv_hash: crypter::sapling_fvk_fingerprint(&extfvk),
+                    crypted,
+                },
+            ));
+            zkeys.push(SaplingKey {
+                extsk: None,
+                extfvk,
+                metadata: sapling_metadata.get(&ivk).cloned(),
+                addresses: sapling_addresses.get(&ivk).cloned().unwrap_or_default(),
+            });
+        }
+        for extfvk in viewing_keys {
+            if !zkeys
+                .iter()
+                .any(|z| extfvk_bytes(&z.extfvk) == extfvk_bytes(&extfvk))
+            {
+                let ivk = extfvk.fvk.vk.ivk().to_repr();
+                zkeys.push(SaplingKey {
+                    extsk: None,
+                    extfvk,
+                    metadata: None,
+                    addresses: sapling_addresses.get(&ivk).cloned().unwrap_or_default(),
+                });
+            }
+        }
+        wallet.zkeys = zkeys;
+
+        wallet.crypted_tkeys = crypted_keys
+            .into_iter()
+            .map(|(pubkey, crypted)| {
+                let secret = CryptedSecret {
+                    iv_hash: crypter::pubkey_hash(&pubkey),
+                    crypted,
+                };
+                let metadata = key_metadata.get(&pubkey).cloned();
+                (pubkey, secret, metadata)
+            })
+            .collect();
+
+        Ok(wallet)
+    }
+
+    /// Decrypts every encrypted secret with the wallet master key. Each decrypted key
+    /// is checked against its public part, so a wrong master key is detected.
+    fn decrypt_secrets(&mut self, master_key: &[u8]) -> io::Result<()> {
+        let wrong_key = crypter::wrong_passphrase;
+        let secp = Secp256k1::new();
+
+        let mut tkeys = vec![];
+        for (pubkey, secret, metadata) in &self.crypted_tkeys {
+            let plaintext = crypter::decrypt_secret(master_key, &secret.iv_hash, &secret.crypted)?;
+            let secret = SecretKey::from_slice(&plaintext).map_err(|_| wrong_key())?;
+            let public = PublicKey::from_secret_key(&secp, &secret);
+            if public.serialize().as_slice() != pubkey.as_slice()
+                && public.serialize_uncompressed().as_slice() != pubkey.as_slice()
+            {
+                return Err(wrong_key());
+            }
+            tkeys.push(TransparentKey {
+                pubkey: pubkey.clone(),
+                secret,
+                metadata: metadata.clone(),
+            });
+        }
+
+        let mut extsks = vec![];
+        for (index, secret) in &self.crypted_zkeys {
+            let plaintext = crypter::decrypt_secret(master_key, &secret.iv_hash, &secret.crypted)?;
+            let extsk = ExtendedSpendingKey::read(plaintext.as_slice()).map_err(|_| wrong_key())?;
+            let extfvk = &self.zkeys[*index].extfvk;
+            if extfvk_bytes(&extsk.to_extended_full_viewing_key()) != extfvk_bytes(extfvk) {
+                return Err(wrong_key());
+            }
+            extsks.push((*index, extsk));
+        }
+
+        let mnemonic = match &self.crypted_mnemonic {
+            Some(secret) => {
+                let plaintext =
+                    crypter::decrypt_secret(master_key, &secret.iv_hash, &secret.crypted)?;
+                let mut reader = Cursor::new(plaintext.as_slice());
+                let language = reader.read_u32::<LittleEndian>()?;
+                if language != 0 {
+                    return Err(invalid("Only English mnemonics are supported"));
+                }
+                let phrase = read_string(&mut reader).map_err(|_| wrong_key())?;
+                <Mnemonic<English>>::from_phrase(phrase.as_str()).map_err(|_| wrong_key())?;
+                Some((phrase, secret.iv_hash))
+            }
+            None => self.mnemonic.clone(),
+        };
+
+        let legacy_seed = match &self.crypted_legacy_seed {
+            Some(secret) => Some(crypter::decrypt_secret(
+                master_key,
+                &secret.iv_hash,
+                &secret.crypted,
+            )?),
+            None => self.legacy_seed.clone(),
+        };
+
+        self.tkeys.extend(tkeys);
+        for (index, extsk) in extsks {
+            self.zkeys[index].extsk = Some(extsk);
+        }
+        self.mnemonic = mnemonic;
+        self.legacy_seed = legacy_seed;
+        self.master_keys.clear();
+        self.crypted_tkeys.clear();
+        self.crypted_zkeys.clear();
+        self.crypted_mnemonic = None;
+        self.crypted_legacy_seed = None;
+
+        Ok(())
+    }
+
+    /// Whether a key with this metadata was derived from the wallet mnemonic.
+    fn is_hd(&self, metadata: Option<&KeyMetadata>) -> bool {
+        match (&self.mnemonic, metadata.and_then(|m| m.seed_fp)) {
+            (Some((_, mnemonic_fp)), Some(seed_fp)) => *mnemonic_fp == seed_fp,
+            _ => false,
+        }
+    }
+
+    /// BIP-39 entropy of the mnemonic.
+    fn mnemonic_entropy(&self) -> Option<Vec<u8>> {
+        let (phrase, _) = self.mnemonic.as_ref()?;
+        let mnemonic = <Mnemonic<English>>::from_phrase(phrase.as_str()).ok()?;
+        Some(mnemonic.entropy().to_vec())
+    }
+
+    fn wallet_tkey(
+        &self,
+        key: &TransparentKey,
+        key_type: WalletKeyType,
+        scope: KeyScope,
+        index: u32,
+    ) -> WalletTKey {
+        WalletTKey {
+            pk: key.secret,
+            key_type,
+            index,
+            address: transparent_address(self.network, &key.pubkey),
+            scope,
+            derivation_path: key.metadata.as_ref().and_then(KeyMetadata::hd_path),
+        }
+    }
+
+    fn wallet_zkey(&self, key: &SaplingKey, key_type: WalletKeyType, index: u32) -> WalletZKey {
+        let (_, default_address) = key.extfvk.default_address();
+        let hrp = self.network.network_type().hrp_sapling_payment_address();
+        WalletZKey {
+            extsk: key.extsk.clone(),
+            fvk: key.extfvk.clone(),
+            key_type,
+            index,
+            address: encode_payment_address(hrp, &default_address),
+            diversified_addresses: key
+                .addresses
+                .iter()
+                .filter(|address| **address != default_address)
+                .map(|address| encode_payment_address(hrp, address))
+                .collect(),
+            scope: KeyScope::External,
+            derivation_path: key.metadata.as_ref().and_then(KeyMetadata::hd_path),
+        }
+    }
+
+    /// ZIP 32 account of a sapling key derived from the mnemonic, at `m/32'/133'/n'`.
+    fn sapling_account(&self, key: &SaplingKey) -> Option<u32> {
+        if !self.is_hd(key.metadata.as_ref()) {
+            return None;
+        }
+        match key.metadata.as_ref()?.path()?.as_slice() {
+            [SAPLING_PURPOSE, _, account] => Some(*account),
+            _ => None,
+        }
+    }
+
+    /// Account, scope and address index of a transparent key derived from the
+    /// mnemonic, at `m/44'/133'/n'/0/i` or `m/44'/133'/n'/1/i` for change addresses.
+    fn transparent_index(&self, key: &TransparentKey) -> Option<(u32, KeyScope, u32)> {
+        if !self.is_hd(key.metadata.as_ref()) {
+            return None;
+        }
+        match key.metadata.as_ref()?.path()?.as_slice() {
+            [TRANSPARENT_PURPOSE, _, account, 0, index] => {
+                Some((*account, KeyScope::External, *index))
+            }
+            [TRANSPARENT_PURPOSE, _, account, 1, index] => {
+                Some((*account, KeyScope::Internal, *index))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl WalletParser for ZcashdWallet {
+    fn read(filename: &str) -> io::Result<Self> {
+        let db = bdb::Database::open(Path::new(filename))?;
+        Self::from_records(&db.records()?)
+    }
+
+    fn is_encrypted(&self) -> bool {
+        !self.master_keys.is_empty()
+    }
+
+    /// Decrypts the wallet secrets with the passphrase. zcashd can hold several master
+    /// keys, the first one the passphrase decrypts is used.
+    fn decrypt(&mut self, password: &str) -> io::Result<()> {
+        let mut error = None;
+        for master_key in self.master_keys.clone() {
+            let result = master_key
+                .decrypt(password)
+                .and_then(|key| self.decrypt_secrets(&key));
+            match result {
+                Ok(()) => return Ok(()),
+                Err(e) => error = Some(e),
+            }
+        }
+
+        Err(error.unwrap_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "Wallet has no master key")
+        }))
+    }
+
+    fn get_wallet_name(&self) -> String {
+        "zcashd".to_string()
+    }
+
+    fn get_wallet_version(&self) -> u64 {
+        self.version
+    }
+
+    fn get_network(&self) -> Network {
+        self.network
+    }
+
+    /// The legacy HD seed of wallets created before zcashd 4.7 is not a BIP-39 seed,
+    /// so only wallets with a mnemonic have one.
+    fn get_wallet_seed(&self) -> Option<Vec<u8>> {
+        self.mnemonic_entropy()
+    }
+
+    fn get_wallet_accounts(&self) -> io::Result<Vec<WalletAccount>> {
+        let Some(seed) = self.mnemonic_entropy() else {
+            return Ok(vec![]);
+        };
+
+        let birthday = BlockHeight::from_u32(self.get_birthday() as u32);
+        let accounts = (0..self.get_hd_account_count())
+            .map(|account| {
+                let mut tkeys: Vec<_> = self
+                    .tkeys
+                    .iter()
+                    .filter_map(|k| match self.transparent_index(k) {
+                        Some((a, scope, index)) if a == account => Some((scope, index, k)),
+                        _ => None,
+                    })
+                    .collect();
+                tkeys.sort_by_key(|(scope, index, _)| (*scope, *index));
+                let tkeys = tkeys
+                    .into_iter()
+                    .map(|(scope, index, k)| {
+                        self.wallet_tkey(k, WalletKeyType::HdDerived, scope, index)
+                    })
+                    .collect();
+                let zkeys = self
+                    .zkeys
+                    .iter()
+                    .filter(|k| self.sapling_account(k) == Some(account))
+                    .map(|k| self.wallet_zkey(k, WalletKeyType::HdDerived, account))
+                    .collect();
+
+                WalletAccount {
+                    name: format!("Account {}", account + 1),
+                    seed: Some(seed.clone()),
+                    birthday,
+                    keys: WalletKeys {
+                        tkeys,
+                        zkeys,
+                        okeys: vec![],
+                    },
+                }
+            })
+            .collect();
+
+        Ok(accounts)
+    }
+
+    fn get_birthday(&self) -> u64 {
+        let oldest = self
+            .tkeys
+            .iter()
+            .filter_map(|k| k.metadata.as_ref())
+            .chain(self.zkeys.iter().filter_map(|k| k.metadata.as_ref()))
+            .map(|m| m.create_time)
+            .chain(self.hd_chain_create_time)
+            // Keys of old wallets have no creation time
+            .filter(|&time| time > 0)
+            .min();
+
+        estimate_birthday(self.network, oldest)
+    }
+
+    fn get_hd_account_count(&self) -> u32 {
+        self.zkeys
+            .iter()
+            .filter_map(|k| self.sapling_account(k))
+            .chain(
+                self.tkeys
+                    .iter()
+                    .filter_map(|k| self.transparent_index(k).map(|(a, _, _)| a)),
+            )
+            .max()
+            .map_or(1, |n| n + 1)
+    }
+
+    fn get_hd_transparent_address_count(&self) -> u32 {
+        self.tkeys
+            .iter()
+            .filter_map(|k| match self.transparent_index(k) {
+                Some((_, KeyScope::External, index)) => Some(index),
+                _ => None,
+            })
+            .max()
+            .map_or(1, |n| n + 1)
+    }
+
+    fn get_imported_keys(&self) -> WalletImportedKeys {
+        let tkeys = self
+            .tkeys
+            .iter()
+            .filter(|k| self.transparent_index(k).is_none())
+            .map(|k| self.wallet_tkey(k, WalletKeyType::Imported, KeyScope::External, 0))
+            .collect();
+        let zkeys = self
+            .zkeys
+            .iter()
+            .filter(|k| self.sapling_account(k).is_none())
+            .map(|k| self.wallet_zkey(k, WalletKeyType::Imported, 0))
+            .collect();
+
+        WalletImportedKeys {
+            tkeys,
+            zkeys,
+            okeys: vec![],
+        }
+    }
+
+    fn print_internal(&self) {
+        println!(
+            "zcashd: version {}, network {}, mnemonic: {}, legacy seed: {}, {} transparent keys, {} sapling keys, {} sprout keys, encrypted: {}",
+            self.version,
+            self.network,
+            self.mnemonic.is_some(),
+            self.legacy_seed.is_some(),
+            self.tkeys.len(),
+            self.zkeys.len(),
+            self.sprout_key_count,
+            self.is_encrypted()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PHRASE: &str = "clerk family rack dragon cannon wait vendor penalty absent country better coast expand true middle stable assist clerk tent phone toilet knee female kitchen";
+
+    fn string(s: &str) -> Vec<u8> {
+        let mut bytes = vec![s.len() as u8];
+        bytes.extend_from_slice(s.as_bytes());
+        bytes
+    }
+
+    fn record(record_type: &str, key: &[u8], value: Vec<u8>) -> (Vec<u8>, Vec<u8>) {
+        ([string(record_type), key.to_vec()].concat(), value)
+    }
+
+    fn metadata(path: &str, seed_fp: [u8; 32]) -> Vec<u8> {
+        let mut value = vec![];
+        value.extend_from_slice(&KEY_METADATA_VERSION_WITH_HDDATA.to_le_bytes());
+        value.extend_from_slice(&1_600_000_000i64.to_le_bytes());
+        value.extend(string(path));
+        value.extend_from_slice(&seed_fp);
+        value
+    }
+
+    #[test]
+    fn test_zcashd_records() {
+        let seed_fp = [1u8; 32];
+        let mnemonic = <Mnemonic<English>>::from_phrase(PHRASE).unwrap();
+        let extsk = ExtendedSpendingKey::master(&mnemonic.to_seed(""));
+        let mut extsk_bytes = vec![];
+        extsk.write(&mut extsk_bytes).unwrap();
+        let (_, diversified) = extsk
+            .to_extended_full_viewing_key()
+            .find_address(zip32::DiversifierIndex::from(10u32))
+            .unwrap();
+
+        let secret = [0x11u8; 32];
+        let der = [
+            &[0x30, 0x81, 0xd3, 0x02, 0x01, 0x01, 0x04, 0x20][..],
+            &secret,
+        ]
+        .concat();
+        let pubkey = [2u8; 33];
+        let pubkey_key = [&[33u8][..], &pubkey].concat();
+
+        let records = vec![
+            record("version", &[], 6_000_050i32.to_le_bytes().to_vec()),
+            record(
+                "networkinfo",
+                &[],
+                [string("Zcash"), string("test")].concat(),
+            ),
+            record(
+                "mnemonicphrase",
+                &seed_fp,
+                [
+                    &0u32.to_le_bytes()[..],
+                    &[PHRASE.len() as u8],
+                    PHRASE.as_bytes(),
+                ]
+                .concat(),
+            ),
+            record("sapzkey", &[3u8; 32], extsk_bytes),
+            record("sapzkeymeta", &[3u8; 32], metadata("m/32'/1'/1'", seed_fp)),
+            record("sapzaddr", &diversified.to_bytes(), [3u8; 32].to_vec()),
+            record("key", &pubkey_key, [&[der.len() as u8][..], &der].concat()),
+            record("keymeta", &pubkey_key, metadata("", [0u8; 32])),
+            record("zkey", &[], vec![]),
+        ];
+
+        let wallet = ZcashdWallet::from_records(&records).unwrap();
+        assert_eq!(wallet.get_wallet_version(), 6_000_050);
+        assert_eq!(wallet.get_network(), Network::Testnet);
+        assert_eq!(wallet.get_wallet_seed(), Some(mnemonic.entropy().to_vec()));
+        assert_eq!(wallet.sprout_key_count, 1);
+        assert!(!wallet.is_encrypted());
+
+        // The sapling key belongs to the second account
+        assert_eq!(wallet.get_hd_account_count(), 2);
+        let accounts = wallet.get_wallet_accounts().unwrap();
+        assert!(accounts[0].keys.zkeys.is_empty());
+        let zkey = &accounts[1].keys.zkeys[0];
+        assert!(zkey.address.starts_with("ztestsapling1"));
+        assert_eq!(zkey.derivation_path.as_deref(), Some("m/32'/1'/1'"));
+        assert_eq!(zkey.diversified_addresses.len(), 1);
+
+        // The transparent key wasn't derived from the mnemonic
+        let imported = wallet.get_imported_keys();
+        assert_eq!(imported.tkeys.len(), 1);
+        assert_eq!(imported.tkeys[0].pk.secret_bytes(), secret);
+        assert!(imported.tkeys[0].address.starts_with("tm"));
+        assert!(imported.zkeys.is_empty());
+    }
+
+    #[test]
+    fn test_zcashd_encrypted() {
+        let master_key = [5u8; 32];
+        let salt = [8u8; 8];
+        let (key, iv) = crypter::bytes_to_key_sha512(b"passphrase", &salt, 100);
+        let crypted_master_key = crypter::encrypt(&key, &iv, &master_key);
+        let mkey = [
+            &[crypted_master_key.len() as u8][..],
+            &crypted_master_key,
+            &[salt.len() as u8],
+            &salt,
+            &0u32.to_le_bytes(),
+            &100u32.to_le_bytes(),
+            &[0],
+        ]
+        .concat();
+
+        let secret = SecretKey::from_slice(&[0x22u8; 32]).unwrap();
+        let pubkey = PublicKey::from_secret_key(&Secp256k1::new(), &secret).serialize();
+        let crypted = crypter::encrypt(
+            &master_key,
+            &crypter::pubkey_hash(&pubkey)[..16],
+            &secret.secret_bytes(),
+        );
+
+        let records = vec![
+            record("mkey", &1u32.to_le_bytes(), mkey),
+            record(
+                "ckey",
+                &[&[33u8][..], &pubkey].concat(),
+                [&[crypted.len() as u8][..], &crypted].concat(),
+            ),
+        ];
+
+        let mut wallet = ZcashdWallet::from_records(&records).unwrap();
+        assert!(wallet.is_encrypted());
+        assert!(wallet.get_imported_keys().tkeys.is_empty());
+
+        let err = wallet.decrypt("wrong").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        assert!(wallet.is_encrypted());
+
+        wallet.decrypt("passphrase").unwrap();
+        assert!(!wallet.is_encrypted());
+        let imported = wallet.get_imported_keys();
+        assert_eq!(imported.tkeys.len(), 1);
+        assert_eq!(imported.tkeys[0].pk, secret);
+    }
+
+    #[test]
+    fn test_zcashd_legacy_seed() {
+        let records = vec![record(
+            "hdseed",
+            &[9u8; 32],
+            [&[32u8][..], &[7u8; 32]].concat(),
+        )];
+
+        // The legacy seed is no BIP-39 seed and must not be synced as one
+        let wallet = ZcashdWallet::from_records(&records).unwrap();
+        assert_eq!(wallet.legacy_seed, Some(vec![7u8; 32]));
+        assert_eq!(wallet.get_wallet_seed(), None);
+    }
+
+    #[test]
+    fn test_zcashd_truncated() {
+        // Seed record cut off after 3 of its 32 bytes
+        let records = vec![record(
+            "hdseed",
+            &[9u8; 32],
+            [&[32u8][..], &[7u8; 3]].concat(),
+        )];
+        let err = ZcashdWallet::from_records(&records).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_zcashd_birthday() {
+        let wallet = ZcashdWallet::from_records(&[]).unwrap();
+        assert_eq!(wallet.get_wallet_seed(), None);
+        // Without keys, the birthday is the sapling activation height
+        assert_eq!(wallet.get_birthday(), 419_200);
+        // 2020-01-01, a few weeks after Blossom activated
+        assert!((675_000..690_000).contains(&height_at(Network::Mainnet, 1_577_836_800)));
+    }
+}
@@ -0,0 +1,358 @@
+//! # Berkeley DB reader
+//!
+//! Minimal read-only reader for the Berkeley DB btree files written by zcashd,
+//! so `wallet.dat` can be read without linking libdb.
+//!
+//! A btree file is a sequence of fixed size pages. Page 0 is the metadata page,
+//! holding the page size and the root page of the tree. zcashd stores its records
+//! in a subdatabase named `main`: the root tree then maps subdatabase names to the
+//! metadata page of each subdatabase.
+//!
+//! Leaf pages hold the key/value pairs, values too large for a page are stored on
+//! a chain of overflow pages. Both byte orders are supported, encrypted databases
+//! are not (zcashd encrypts the wallet secrets, not the database).
+
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+
+const BTREE_MAGIC: u32 = 0x0005_3162;
+
+// Page types
+const P_IBTREE: u8 = 3;
+const P_LBTREE: u8 = 5;
+const P_OVERFLOW: u8 = 7;
+const P_BTREEMETA: u8 = 9;
+
+// Item types
+const B_KEYDATA: u8 = 1;
+const B_OVERFLOW: u8 = 3;
+const B_DELETE: u8 = 0x80;
+
+/// Metadata flag set when the file holds subdatabases.
+const BTM_SUBDB: u32 = 0x20;
+
+const PAGE_HEADER_SIZE: usize = 26;
+/// Offset of the root page number in a btree metadata page.
+const META_ROOT_OFFSET: usize = 88;
+
+/// Name of the subdatabase holding the zcashd wallet records.
+const MAIN_SUBDB: &[u8] = b"main";
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+/// Whether the file at `path` starts with a Berkeley DB btree metadata page.
+pub fn is_berkeley_db(path: &Path) -> bool {
+    let mut header = [0u8; 26];
+    match File::open(path).and_then(|mut f| f.read_exact(&mut header)) {
+        Ok(()) => {
+            (LittleEndian::read_u32(&header[12..16]) == BTREE_MAGIC
+                || BigEndian::read_u32(&header[12..16]) == BTREE_MAGIC)
+                && header[25] == P_BTREEMETA
+        }
+        Err(_) => false,
+    }
+}
+
+/// A Berkeley DB btree file loaded in memory.
+pub struct Database {
+    data: Vec<u8>,
+    page_size: usize,
+    big_endian: bool,
+}
+
+impl Database {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Self::from_bytes(std::fs::read(path)?)
+    }
+
+    pub fn from_bytes(data: Vec<u8>) -> io::Result<Self> {
+        if data.len() < 512 {
+            return Err(invalid("File is too small to be a Berkeley DB database"));
+        }
+
+        let big_endian = if LittleEndian::read_u32(&data[12..16]) == BTREE_MAGIC {
+            false
+        } else if BigEndian::read_u32(&data[12..16]) == BTREE_MAGIC {
+            true
+        } else {
+            return Err(invalid("Not a Berkeley DB btree database"));
+        };
+
+        // Encryption algorithm of the database
+        if data[24] != 0 {
+            return Err(invalid("Encrypted Berkeley DB databases are not supported"));
+        }
+
+        let mut db = Self {
+            data,
+            page_size: 0,
+            big_endian,
+        };
+        let page_size = db.u32_at(&db.data, 20)? as usize;
+        if !page_size.is_power_of_two() || !(512..=65536).contains(&page_size) {
+            return Err(invalid(format!("Invalid page size {}", page_size)));
+        }
+        db.page_size = page_size;
+
+        Ok(db)
+    }
+
+    fn u16_at(&self, buf: &[u8], offset: usize) -> io::Result<u16> {
+        let bytes = slice(buf, offset, 2)?;
+        Ok(match self.big_endian {
+            true => BigEndian::read_u16(bytes),
+            false => LittleEndian::read_u16(bytes),
+        })
+    }
+
+    fn u32_at(&self, buf: &[u8], offset: usize) -> io::Result<u32> {
+        let bytes = slice(buf, offset, 4)?;
+        Ok(match self.big_endian {
+            true => BigEndian::read_u32(bytes),
+            false => LittleEndian::read_u32(bytes),
+        })
+    }
+
+    fn page(&self, pgno: u32) -> io::Result<&[u8]> {
+        slice(&self.data, pgno as usize * self.page_size, self.page_size)
+            .map_err(|_| invalid(format!("Page {} is out of bounds", pgno)))
+    }
+
+    fn page_type(page: &[u8]) -> u8 {
+        page[25]
+    }
+
+    /// Root page of the tree described by the metadata page `pgno`.
+    fn root(&self, pgno: u32) -> io::Result<u32> {
+        let meta = self.page(pgno)?;
+        if Self::page_type(meta) != P_BTREEMETA {
+            return Err(invalid(format!(
+                "Page {} is not a btree metadata page",
+                pgno
+            )));
+        }
+        self.u32_at(meta, META_ROOT_OFFSET)
+    }
+
+    /// Key/value records of the database. For files holding subdatabases, these are
+    /// the records of the `main` subdatabase, like zcashd's `wallet.dat`.
+    pub fn records(&self) -> io::Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let meta = self.page(0)?;
+        let records = self.tree_records(self.root(0)?)?;
+        if self.u32_at(meta, 48)? & BTM_SUBDB == 0 {
+            return Ok(records);
+        }
+
+        let (_, value) = records
+            .iter()
+            .find(|(key, _)| key == MAIN_SUBDB)
+            .ok_or_else(|| invalid("Database has no main subdatabase"))?;
+
+        // The metadata page number is usually stored in the byte order of the file,
+        // fall back to the other one if it doesn't point to a metadata page.
+        let pgno = self.u32_at(value, 0)?;
+        let root = self.root(pgno).or_else(|_| self.root(pgno.swap_bytes()))?;
+
+        self.tree_records(root)
+    }
+
+    /// Records of the tree rooted at `root`, in key order.
+    fn tree_records(&self, root: u32) -> io::Result<Vec<(Vec<u8>, Vec<u8>)>> {
+        let mut records = vec![];
+        let mut visited = HashSet::new();
+        let mut stack = vec![root];
+
+        while let Some(pgno) = stack.pop() {
+            if !visited.insert(pgno) {
+                return Err(invalid(format!("Page {} is referenced twice", pgno)));
+            }
+
+            let page = self.page(pgno)?;
+            let entries = self.u16_at(page, 20)? as usize;
+            match Self::page_type(page) {
+                P_IBTREE => {
+                    let mut children = vec![];
+                    for i in 0..entries {
+                        let offset = self.u16_at(page, PAGE_HEADER_SIZE + 2 * i)? as usize;
+                        children.push(self.u32_at(page, offset + 4)?);
+                    }
+                    stack.extend(children.into_iter().rev());
+                }
+                P_LBTREE => {
+                    for i in (0..entries).step_by(2) {
+                        let key = self.item(page, i)?;
+                        let value = self.item(page, i + 1)?;
+                        match (key, value) {
+                            (Some(key), Some(value)) => records.push((key, value)),
+                            _ => continue,
+                        }
+                    }
+                }
+                page_type => {
+                    return Err(invalid(format!(
+                        "Unexpected page type {} for btree page {}",
+                        page_type, pgno
+                    )));
+                }
+            }
+        }
+
+        Ok(records)
+    }
+
+    /// Item `index` of a leaf page, `None` if it was deleted.
+    fn item(&self, page: &[u8], index: usize) -> io::Result<Option<Vec<u8>>> {
+        let offset = self.u16_at(page, PAGE_HEADER_SIZE + 2 * index)? as usize;
+        let len = self.u16_at(page, offset)? as usize;
+        let item_type = slice(page, offset + 2, 1)?[0];
+        if item_type & B_DELETE != 0 {
+            return Ok(None);
+        }
+
+        match item_type {
+            B_KEYDATA => Ok(Some(slice(page, offset + 3, len)?.to_vec())),
+            B_OVERFLOW => {
+                let pgno = self.u32_at(page, offset + 4)?;
+                let total_len = self.u32_at(page, offset + 8)? as usize;
+                self.overflow(pgno, total_len).map(Some)
+            }
+            item_type => Err(invalid(format!("Unsupported item type {}", item_type))),
+        }
+    }
+
+    /// Reads a value stored on the chain of overflow pages starting at `pgno`.
+    fn overflow(&self, mut pgno: u32, total_len: usize) -> io::Result<Vec<u8>> {
+        let mut data = Vec::with_capacity(total_len);
+        let mut visited = HashSet::new();
+        while data.len() < total_len {
+            if pgno == 0 || !visited.insert(pgno) {
+                return Err(invalid("Truncated overflow chain"));
+            }
+
+            let page = self.page(pgno)?;
+            if Self::page_type(page) != P_OVERFLOW {
+                return Err(invalid(format!("Page {} is not an overflow page", pgno)));
+            }
+            let len = self.u16_at(page, 22)? as usize;
+            data.extend_from_slice(slice(page, PAGE_HEADER_SIZE, len)?);
+            pgno = self.u32_at(page, 16)?;
+        }
+        data.truncate(total_len);
+
+        Ok(data)
+    }
+}
+
+fn slice(buf: &[u8], offset: usize, len: usize) -> io::Result<&[u8]> {
+    buf.get(offset..offset + len)
+        .ok_or_else(|| invalid("Record out of page bounds"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE_SIZE: usize = 512;
+
+    fn meta_page(pgno: u32, root: u32, flags: u32) -> Vec<u8> {
+        let mut page = vec![0u8; PAGE_SIZE];
+        LittleEndian::write_u32(&mut page[8..12], pgno);
+        LittleEndian::write_u32(&mut page[12..16], BTREE_MAGIC);
+        LittleEndian::write_u32(&mut page[20..24], PAGE_SIZE as u32);
+        page[25] = P_BTREEMETA;
+        LittleEndian::write_u32(&mut page[48..52], flags);
+        LittleEndian::write_u32(&mut page[META_ROOT_OFFSET..META_ROOT_OFFSET + 4], root);
+        page
+    }
+
+    /// Leaf page holding `items`, written from the end of the page like libdb does.
+    fn leaf_page(pgno: u32, items: &[Vec<u8>]) -> Vec<u8> {
+        let mut page = vec![0u8; PAGE_SIZE];
+        LittleEndian::write_u32(&mut page[8..12], pgno);
+        LittleEndian::write_u16(&mut page[20..22], items.len() as u16);
+        page[24] = 1;
+        page[25] = P_LBTREE;
+
+        let mut end = PAGE_SIZE;
+        for (i, item) in items.iter().enumerate() {
+            end -= item.len();
+            page[end..end + item.len()].copy_from_slice(item);
+            let index = PAGE_HEADER_SIZE + 2 * i;
+            LittleEndian::write_u16(&mut page[index..index + 2], end as u16);
+        }
+        page
+    }
+
+    fn keydata(data: &[u8]) -> Vec<u8> {
+        let mut item = vec![0u8; 3];
+        LittleEndian::write_u16(&mut item[0..2], data.len() as u16);
+        item[2] = B_KEYDATA;
+        item.extend_from_slice(data);
+        item
+    }
+
+    fn overflow_item(pgno: u32, len: usize) -> Vec<u8> {
+        let mut item = vec![0u8; 12];
+        item[2] = B_OVERFLOW;
+        LittleEndian::write_u32(&mut item[4..8], pgno);
+        LittleEndian::write_u32(&mut item[8..12], len as u32);
+        item
+    }
+
+    fn overflow_page(pgno: u32, next: u32, data: &[u8]) -> Vec<u8> {
+        let mut page = vec![0u8; PAGE_SIZE];
+        LittleEndian::write_u32(&mut page[8..12], pgno);
+        LittleEndian::write_u32(&mut page[16..20], next);
+        LittleEndian::write_u16(&mut page[22..24], data.len() as u16);
+        page[25] = P_OVERFLOW;
+        page[PAGE_HEADER_SIZE..PAGE_HEADER_SIZE + data.len()].copy_from_slice(data);
+        page
+    }
+
+    #[test]
+    fn test_read_subdatabase() {
+        let large = vec![7u8; 600];
+        let max = PAGE_SIZE - PAGE_HEADER_SIZE;
+
+        let mut subdb_pgno = vec![0u8; 4];
+        LittleEndian::write_u32(&mut subdb_pgno, 2);
+        let pages = [
+            meta_page(0, 1, BTM_SUBDB),
+            leaf_page(1, &[keydata(MAIN_SUBDB), keydata(&subdb_pgno)]),
+            meta_page(2, 3, 0),
+            leaf_page(
+                3,
+                &[
+                    keydata(b"key1"),
+                    keydata(b"value1"),
+                    keydata(b"key2"),
+                    overflow_item(4, large.len()),
+                ],
+            ),
+            overflow_page(4, 5, &large[..max]),
+            overflow_page(5, 0, &large[max..]),
+        ]
+        .concat();
+
+        let db = Database::from_bytes(pages).unwrap();
+        let records = db.records().unwrap();
+        assert_eq!(
+            records,
+            vec![
+                (b"key1".to_vec(), b"value1".to_vec()),
+                (b"key2".to_vec(), large),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reject_other_files() {
+        assert!(Database::from_bytes(vec![0u8; 1024]).is_err());
+    }
+}
@@ -0,0 +1,171 @@
+//! # zcashd wallet encryption
+//!
+//! zcashd encrypts the wallet secrets, like Bitcoin Core, with a random master key:
+//!
+//! - **Master key** (`mkey`): encrypted with AES-256-CBC, under a key and IV derived
+//!   from the passphrase and a salt by iterating SHA-512 (`EVP_BytesToKey`).
+//! - **Secrets** (`ckey`, `csapzkey`, `chdseed`, `cmnemonicphrase`): encrypted with
+//!   AES-256-CBC under the master key. The IV is the first 16 bytes of a hash
+//!   identifying the secret, such as the double SHA-256 of a transparent public key.
+
+use std::io::{self, Read};
+
+use aes::Aes256;
+use byteorder::{LittleEndian, ReadBytesExt};
+use cbc::cipher::block_padding::Pkcs7;
+use cbc::cipher::{BlockDecryptMut, KeyIvInit};
+use sapling::zip32::ExtendedFullViewingKey;
+use sha2::{Digest, Sha256, Sha512};
+use zcash_encoding::Vector;
+
+/// `nDerivationMethod` of `EVP_BytesToKey` with SHA-512.
+const DERIVATION_METHOD_SHA512: u32 = 0;
+const KEY_SIZE: usize = 32;
+const IV_SIZE: usize = 16;
+/// BLAKE2b personalization of sapling full viewing key fingerprints.
+const SAPLING_FVK_FINGERPRINT_PERSONALIZATION: &[u8; 16] = b"ZcashSaplingFVFP";
+
+pub fn wrong_passphrase() -> io::Error {
+    io::Error::new(io::ErrorKind::PermissionDenied, "Wrong wallet passphrase")
+}
+
+/// zcashd `CMasterKey`, the `mkey` record.
+#[derive(Debug, Clone)]
+pub struct MasterKey {
+    pub crypted_key: Vec<u8>,
+    pub salt: Vec<u8>,
+    pub derivation_method: u32,
+    pub derive_iterations: u32,
+}
+
+impl MasterKey {
+    pub fn read<R: Read>(mut reader: R) -> io::Result<Self> {
+        let crypted_key = Vector::read(&mut reader, |r| r.read_u8())?;
+        let salt = Vector::read(&mut reader, |r| r.read_u8())?;
+        let derivation_method = reader.read_u32::<LittleEndian>()?;
+        let derive_iterations = reader.read_u32::<LittleEndian>()?;
+
+        Ok(Self {
+            crypted_key,
+            salt,
+            derivation_method,
+            derive_iterations,
+        })
+    }
+
+    /// Decrypts the master key with `passphrase`.
+    pub fn decrypt(&self, passphrase: &str) -> io::Result<Vec<u8>> {
+        if self.derivation_method != DERIVATION_METHOD_SHA512 {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "Unsupported key derivation method {}",
+                    self.derivation_method
+                ),
+            ));
+        }
+
+        let (key, iv) =
+            bytes_to_key_sha512(passphrase.as_bytes(), &self.salt, self.derive_iterations);
+        match decrypt(&key, &iv, &self.crypted_key) {
+            Ok(master_key) if master_key.len() == KEY_SIZE => Ok(master_key),
+            _ => Err(wrong_passphrase()),
+        }
+    }
+}
+
+/// `EVP_BytesToKey` with SHA-512, as used by zcashd: the passphrase and salt are
+/// hashed, then the hash is rehashed `iterations - 1` times.
+pub(super) fn bytes_to_key_sha512(
+    passphrase: &[u8],
+    salt: &[u8],
+    iterations: u32,
+) -> ([u8; KEY_SIZE], [u8; IV_SIZE]) {
+    let mut hash = Sha512::new()
+        .chain_update(passphrase)
+        .chain_update(salt)
+        .finalize();
+    for _ in 1..iterations {
+        hash = Sha512::digest(hash);
+    }
+
+    let mut key = [0u8; KEY_SIZE];
+    let mut iv = [0u8; IV_SIZE];
+    key.copy_from_slice(&hash[..KEY_SIZE]);
+    iv.copy_from_slice(&hash[KEY_SIZE..KEY_SIZE + IV_SIZE]);
+    (key, iv)
+}
+
+fn decrypt(key: &[u8], iv: &[u8], data: &[u8]) -> io::Result<Vec<u8>> {
+    let decryptor = cbc::Decryptor::<Aes256>::new_from_slices(key, iv)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Invalid key or IV size"))?;
+    let mut buf = data.to_vec();
+    let plaintext = decryptor
+        .decrypt_padded_mut::<Pkcs7>(&mut buf)
+        .map_err(|_| wrong_passphrase())?;
+    Ok(plaintext.to_vec())
+}
+
+/// Decrypts a secret encrypted under the master key, with the IV taken from `iv_hash`.
+pub fn decrypt_secret(
+    master_key: &[u8],
+    iv_hash: &[u8; 32],
+    crypted: &[u8],
+) -> io::Result<Vec<u8>> {
+    decrypt(master_key, &iv_hash[..IV_SIZE], crypted)
+}
+
+/// Double SHA-256 of a transparent public key, the IV hash of its `ckey` record.
+pub fn pubkey_hash(pubkey: &[u8]) -> [u8; 32] {
+    Sha256::digest(Sha256::digest(pubkey)).into()
+}
+
+/// Fingerprint of a sapling full viewing key, the IV hash of its `csapzkey` record.
+pub fn sapling_fvk_fingerprint(extfvk: &ExtendedFullViewingKey) -> [u8; 32] {
+    let hash = blake2b_simd::Params::new()
+        .hash_length(32)
+        .personal(SAPLING_FVK_FINGERPRINT_PERSONALIZATION)
+        .hash(&extfvk.fvk.to_bytes());
+    let mut fingerprint = [0u8; 32];
+    fingerprint.copy_from_slice(hash.as_bytes());
+    fingerprint
+}
+
+#[cfg(test)]
+pub(super) fn encrypt(key: &[u8], iv: &[u8], data: &[u8]) -> Vec<u8> {
+    use cbc::cipher::BlockEncryptMut;
+
+    cbc::Encryptor::<Aes256>::new_from_slices(key, iv)
+        .unwrap()
+        .encrypt_padded_vec_mut::<Pkcs7>(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_master_key() {
+        let master_key = [9u8; KEY_SIZE];
+        let salt = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        let (key, iv) = bytes_to_key_sha512(b"correct horse", &salt, 25000);
+
+        let mkey = MasterKey {
+            crypted_key: encrypt(&key, &iv, &master_key),
+            salt,
+            derivation_method: DERIVATION_METHOD_SHA512,
+            derive_iterations: 25000,
+        };
+        assert_eq!(mkey.decrypt("correct horse").unwrap(), master_key);
+        let err = mkey.decrypt("battery staple").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+
+        let pubkey = [2u8; 33];
+        let iv_hash = pubkey_hash(&pubkey);
+        let crypted = encrypt(&master_key, &iv_hash[..IV_SIZE], &[7u8; 32]);
+        assert_eq!(
+            decrypt_secret(&master_key, &iv_hash, &crypted).unwrap(),
+            [7u8; 32]
+        );
+    }
+}
@@ -0,0 +1,586 @@
+//! # zcashd wallet dump Parser
+//!
+//! Reads the text files written by zcashd's `z_exportwallet` and `dumpwallet`:
+//!
+//! ```text
+//! # Wallet dump created by Zcash v5.4.0
+//! # * Created on 2023-01-01T00:00:00Z
+//! #
+//! # Emergency Recovery Phrase
+//! # - recovery_phrase="abandon abandon ... art"
+//! # - language=English
+//! #
+//! <WIF> 2019-03-01T12:00:00Z label=savings # addr=t1...
+//! <WIF> 2019-03-01T12:00:00Z change=1 # addr=t1... hdkeypath=m/44'/133'/0'/0/0
+//! # Sapling keys
+//! secret-extended-key-main1... 2019-03-01T12:00:00Z # zaddr=zs1... (hdkeypath=m/32'/133'/0')
+//! # End of dump
+//! ```
+//!
+//! ## Overview
+//! - **Seed**: the emergency recovery phrase of zcashd 4.7 and later, or the legacy
+//!   `HDSeed` of older dumps.
+//! - **Keys**: WIF transparent keys, with their label, and sapling extended spending
+//!   keys. Keys whose derivation path matches the recovery phrase are grouped by
+//!   ZIP 32 account, every other key is an imported key.
+//! - **Birthday**: estimated from the timestamp of the oldest key.
+//!
+//! ## Caveats
+//! - Sprout keys can't be synced and are only counted.
+//! - The legacy seed isn't a BIP-39 seed, see the [`super`] caveats.
+
+use std::io;
+
+use bip0039::{English, Mnemonic};
+use chrono::DateTime;
+use sapling::zip32::ExtendedSpendingKey;
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+use zcash_keys::encoding::decode_extended_spending_key;
+use zcash_primitives::consensus::NetworkConstants;
+use zcash_primitives::legacy::keys::{AccountPrivKey, NonHardenedChildIndex};
+use zcash_primitives::zip32::{AccountId, ChildIndex};
+
+use super::{estimate_birthday, sapling_address, transparent_address};
+use crate::parser::{
+    KeyScope, WalletAccount, WalletImportedKeys, WalletKeyType, WalletKeys, WalletParser,
+    WalletTKey, WalletZKey,
+};
+use crate::settings::Network;
+
+/// First line of every zcashd wallet dump.
+pub(crate) const DUMP_HEADER: &str = "# Wallet dump created by Zcash";
+
+const WIF_PREFIX_MAIN: u8 = 0x80;
+const WIF_PREFIX_TEST: u8 = 0xef;
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+/// Decodes the `%XX` escapes zcashd writes in labels.
+fn decode_dump_string(s: &str) -> String {
+    let mut bytes = vec![];
+    let mut rest = s.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        let escaped = tail
+            .get(..2)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (b, escaped) {
+            (b'%', Some(byte)) => {
+                bytes.push(byte);
+                rest = &tail[2..];
+            }
+            _ => {
+                bytes.push(b);
+                rest = tail;
+            }
+        }
+    }
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+/// Parses a `m/44'/133'/0'/0/5` derivation path, without the hardened markers.
+fn parse_path(path: &str) -> Option<Vec<u32>> {
+    let mut components = path.split('/');
+    if components.next()? != "m" {
+        return None;
+    }
+    components
+        .map(|c| c.trim_end_matches(['\'', 'h']).parse().ok())
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct DumpTKey {
+    pub secret: SecretKey,
+    pub compressed: bool,
+    pub time: Option<i64>,
+    pub label: Option<String>,
+    pub hd_keypath: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DumpZKey {
+    pub extsk: ExtendedSpendingKey,
+    pub time: Option<i64>,
+    pub hd_keypath: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ZcashdDump {
+    /// zcashd version that wrote the dump, e.g. `v5.4.0`.
+    pub zcashd_version: String,
+    pub network: Network,
+    pub recovery_phrase: Option<String>,
+    pub legacy_seed: Option<Vec<u8>>,
+    pub tkeys: Vec<DumpTKey>,
+    pub zkeys: Vec<DumpZKey>,
+    pub sprout_key_count: usize,
+    /// BIP-39 seed of the recovery phrase, derived once as it takes 2048 PBKDF2 rounds.
+    bip39_seed: Option<[u8; 64]>,
+}
+
+impl ZcashdDump {
+    /// Parses the text of a wallet dump.
+    pub fn parse(text: &str) -> io::Result<Self> {
+        let mut lines = text.lines();
+        let zcashd_version = match lines.next() {
+            Some(header) if header.starts_with(DUMP_HEADER) => {
+                header[DUMP_HEADER.len()..].trim().to_string()
+            }
+            _ => return Err(invalid("Not a zcashd wallet dump")),
+        };
+
+        let mut dump = Self {
+            zcashd_version,
+            network: Network::Mainnet,
+            recovery_phrase: None,
+            legacy_seed: None,
+            tkeys: vec![],
+            zkeys: vec![],
+            sprout_key_count: 0,
+            bip39_seed: None,
+        };
+
+        for (number, line) in lines.enumerate() {
+            let line = line.trim();
+            match line.strip_prefix('#') {
+                Some(comment) => dump.read_comment(comment),
+                None if line.is_empty() => (),
+                None => dump
+                    .read_key(line)
+                    .map_err(|e| invalid(format!("Line {}: {}", number + 2, e)))?,
+            }
+        }
+        dump.bip39_seed = dump.mnemonic().map(|m| m.to_seed(""));
+
+        Ok(dump)
+    }
+
+    /// Reads the seed from the header comments.
+    fn read_comment(&mut self, comment: &str) {
+        let comment = comment.trim().trim_start_matches("- ");
+        if let Some(phrase) = comment.strip_prefix("recovery_phrase=") {
+            self.recovery_phrase = Some(phrase.trim_matches('"').to_string());
+            return;
+        }
+
+        for token in comment.split_whitespace() {
+            if let Some(("HDSeed" | "seed", seed)) = token.split_once('=') {
+                self.legacy_seed = hex::decode(seed).ok();
+            }
+        }
+    }
+
+    /// Reads a key line: `<key> <time> [label=..|change=1|reserve=1] # <comment>`.
+    fn read_key(&mut self, line: &str) -> io::Result<()> {
+        let (data, comment) = line.split_once('#').unwrap_or((line, ""));
+        let mut tokens = data.split_whitespace();
+        let key = tokens.next().unwrap_or_default();
+        let time = tokens
+            .next()
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+            .map(|t| t.timestamp());
+        let label = tokens
+            .filter_map(|t| t.strip_prefix("label="))
+            .map(decode_dump_string)
+            .next();
+        let hd_keypath = comment
+            .split_whitespace()
+            .map(|t| t.trim_matches(['(', ')']))
+            .find_map(|t| t.strip_prefix("hdkeypath="))
+            .map(str::to_string);
+
+        if key.starts_with("secret-extended-key-") {
+            let (hrp, _) = key.split_once('1').unwrap_or_default();
+            self.network = match hrp {
+                "secret-extended-key-main" => Network::Mainnet,
+                "secret-extended-key-test" => Network::Testnet,
+                "secret-extended-key-regtest" => Network::Regtest,
+                _ => return Err(invalid(format!("Unknown sapling key prefix {}", hrp))),
+            };
+            let extsk = decode_extended_spending_key(hrp, key)
+                .map_err(|e| invalid(format!("Invalid sapling key: {}", e)))?;
+            self.zkeys.push(DumpZKey {
+                extsk,
+                time,
+                hd_keypath,
+            });
+        } else if key.starts_with("SK") || key.starts_with("ST") {
+            self.sprout_key_count += 1;
+        } else {
+            let payload = bs58::decode(key)
+                .with_check(None)
+                .into_vec()
+                .map_err(|_| invalid("Invalid WIF key"))?;
+            let (network, secret, compressed) = match payload.as_slice() {
+                [prefix, secret @ .., 0x01] if secret.len() == 32 => (*prefix, secret, true),
+                [prefix, secret @ ..] if secret.len() == 32 => (*prefix, secret, false),
+                _ => return Err(invalid("Invalid WIF key length")),
+            };
+            self.network = match network {
+                WIF_PREFIX_MAIN => Network::Mainnet,
+                WIF_PREFIX_TEST if self.network == Network::Regtest => Network::Regtest,
+                WIF_PREFIX_TEST => Network::Testnet,
+                prefix => return Err(invalid(format!("Unknown WIF prefix {:#x}", prefix))),
+            };
+            self.tkeys.push(DumpTKey {
+                secret: SecretKey::from_slice(secret)
+                    .map_err(|_| invalid("Invalid transparent key"))?,
+                compressed,
+                time,
+                label,
+                hd_keypath,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn mnemonic(&self) -> Option<Mnemonic<English>> {
+        <Mnemonic<English>>::from_phrase(self.recovery_phrase.as_deref()?).ok()
+    }
+
+    /// ZIP 32 account of a sapling key, if it is derived from the recovery phrase at
+    /// `m/32'/<coin_type>'/n'`.
+    fn sapling_account(&self, key: &DumpZKey) -> Option<u32> {
+        let seed = self.bip39_seed.as_ref()?;
+        let coin_type = self.network.network_type().coin_type();
+        match parse_path(key.hd_keypath.as_deref()?)?.as_slice() {
+            [32, c, account] if *c == coin_type => {
+                let derived = ExtendedSpendingKey::master(seed)
+                    .derive_child(ChildIndex::hardened(32))
+                    .derive_child(ChildIndex::hardened(coin_type))
+                    .derive_child(ChildIndex::hardened(*account));
+                (derived.to_bytes() == key.extsk.to_bytes()).then_some(*account)
+            }
+            _ => None,
+        }
+    }
+
+    /// Account, scope and address index of a transparent key, if it is derived from
+    /// the recovery phrase at `m/44'/<coin_type>'/n'/0/i`, or `.../1/i` for change.
+    fn transparent_index(&self, key: &DumpTKey) -> Option<(u32, KeyScope, u32)> {
+        let seed = self.bip39_seed.as_ref()?;
+        let coin_type = self.network.network_type().coin_type();
+        let (account, scope, index) = match parse_path(key.hd_keypath.as_deref()?)?.as_slice() {
+            [44, c, account, 0, index] if *c == coin_type => (*account, KeyScope::External, *index),
+            [44, c, account, 1, index] if *c == coin_type => (*account, KeyScope::Internal, *index),
+            _ => return None,
+        };
+        let account_key = AccountPrivKey::from_seed(
+            &self.network.params(),
+            seed,
+            AccountId::try_from(account).ok()?,
+        )
+        .ok()?;
+        let child = NonHardenedChildIndex::from_index(index)?;
+        let derived = match scope {
+            KeyScope::External => account_key.derive_external_secret_key(child),
+            KeyScope::Internal => account_key.derive_internal_secret_key(child),
+        }
+        .ok()?;
+        (derived == key.secret).then_some((account, scope, index))
+    }
+
+    fn wallet_tkey(
+        &self,
+        key: &DumpTKey,
+        key_type: WalletKeyType,
+        scope: KeyScope,
+        index: u32,
+    ) -> WalletTKey {
+        let public = PublicKey::from_secret_key(&Secp256k1::new(), &key.secret);
+        let address = match key.compressed {
+            true => transparent_address(self.network, &public.serialize()),
+            false => transparent_address(self.network, &public.serialize_uncompressed()),
+        };
+        WalletTKey {
+            pk: key.secret,
+            key_type,
+            index,
+            address,
+            scope,
+            derivation_path: key.hd_keypath.clone(),
+        }
+    }
+
+    fn wallet_zkey(&self, key: &DumpZKey, key_type: WalletKeyType, index: u32) -> WalletZKey {
+        let fvk = key.extsk.to_extended_full_viewing_key();
+        WalletZKey {
+            address: sapling_address(self.network, &fvk),
+            extsk: Some(key.extsk.clone()),
+            fvk,
+            key_type,
+            index,
+            diversified_addresses: vec![],
+            scope: KeyScope::External,
+            derivation_path: key.hd_keypath.clone(),
+        }
+    }
+}
+
+impl WalletParser for ZcashdDump {
+    fn read(filename: &str) -> io::Result<Self> {
+        Self::parse(&std::fs::read_to_string(filename)?)
+    }
+
+    fn get_wallet_name(&self) -> String {
+        "zcashd dump".to_string()
+    }
+
+    /// zcashd client version of the dump, `1000000 * major + 10000 * minor + 100 * patch`.
+    fn get_wallet_version(&self) -> u64 {
+        let parts: Vec<u64> = self
+            .zcashd_version
+            .trim_start_matches('v')
+            .split(['.', '-'])
+            .map_while(|p| p.parse().ok())
+            .collect();
+        match parts.as_slice() {
+            [major, minor, patch, ..] => major * 1_000_000 + minor * 10_000 + patch * 100,
+            _ => 0,
+        }
+    }
+
+    fn get_network(&self) -> Network {
+        self.network
+    }
+
+    /// The legacy HD seed is not a BIP-39 seed, so only dumps with a recovery phrase
+    /// have one.
+    fn get_wallet_seed(&self) -> Option<Vec<u8>> {
+        self.mnemonic().map(|m| m.entropy().to_vec())
+    }
+
+    fn get_wallet_accounts(&self) -> io::Result<Vec<WalletAccount>> {
+        let Some(mnemonic) = self.mnemonic() else {
+            return Ok(vec![]);
+        };
+
+        let birthday = (self.get_birthday() as u32).into();
+        let accounts = (0..self.get_hd_account_count())
+            .map(|account| {
+                let mut tkeys: Vec<_> = self
+                    .tkeys
+                    .iter()
+                    .filter_map(|k| match self.transparent_index(k) {
+                        Some((a, scope, index)) if a == account => Some((scope, index, k)),
+                        _ => None,
+                    })
+                    .collect();
+                tkeys.sort_by_key(|(scope, index, _)| (*scope, *index));
+                let tkeys = tkeys
+                    .into_iter()
+                    .map(|(scope, index, k)| {
+                        self.wallet_tkey(k, WalletKeyType::HdDerived, scope, index)
+                    })
+                    .collect();
+                let zkeys = self
+                    .zkeys
+                    .iter()
+                    .filter(|k| self.sapling_account(k) == Some(account))
+                    .map(|k| self.wallet_zkey(k, WalletKeyType::HdDerived, account))
+                    .collect();
+
+                WalletAccount {
+                    name: format!("Account {}", account + 1),
+                    seed: Some(mnemonic.entropy().to_vec()),
+                    birthday,
+                    keys: WalletKeys {
+                        tkeys,
+                        zkeys,
+                        okeys: vec![],
+                    },
+                }
+            })
+            .collect();
+
+        Ok(accounts)
+    }
+
+    fn get_birthday(&self) -> u64 {
+        let oldest = self
+            .tkeys
+            .iter()
+            .filter_map(|k| k.time)
+            .chain(self.zkeys.iter().filter_map(|k| k.time))
+            .filter(|&time| time > 0)
+            .min();
+
+        estimate_birthday(self.network, oldest)
+    }
+
+    fn get_hd_account_count(&self) -> u32 {
+        self.zkeys
+            .iter()
+            .filter_map(|k| self.sapling_account(k))
+            .chain(
+                self.tkeys
+                    .iter()
+                    .filter_map(|k| self.transparent_index(k).map(|(a, _, _)| a)),
+            )
+            .max()
+            .map_or(1, |n| n + 1)
+    }
+
+    fn get_hd_transparent_address_count(&self) -> u32 {
+        self.tkeys
+            .iter()
+            .filter_map(|k| match self.transparent_index(k) {
+                Some((_, KeyScope::External, index)) => Some(index),
+                _ => None,
+            })
+            .max()
+            .map_or(1, |n| n + 1)
+    }
+
+    fn get_imported_keys(&self) -> WalletImportedKeys {
+        let tkeys = self
+            .tkeys
+            .iter()
+            .filter(|k| self.transparent_index(k).is_none())
+            .map(|k| self.wallet_tkey(k, WalletKeyType::Imported, KeyScope::External, 0))
+            .collect();
+        let zkeys = self
+            .zkeys
+            .iter()
+            .filter(|k| self.sapling_account(k).is_none())
+            .map(|k| self.wallet_zkey(k, WalletKeyType::Imported, 0))
+            .collect();
+
+        WalletImportedKeys {
+            tkeys,
+            zkeys,
+            okeys: vec![],
+        }
+    }
+
+    fn print_internal(&self) {
+        println!(
+            "zcashd dump: {}, network {}, recovery phrase: {}, legacy seed: {}, {} transparent keys, {} sapling keys, {} sprout keys",
+            self.zcashd_version,
+            self.network,
+            self.recovery_phrase.is_some(),
+            self.legacy_seed.is_some(),
+            self.tkeys.len(),
+            self.zkeys.len(),
+            self.sprout_key_count
+        );
+        for key in self.tkeys.iter().filter(|k| k.label.is_some()) {
+            println!(
+                "  {}: {}",
+                self.wallet_tkey(key, WalletKeyType::Imported, KeyScope::External, 0)
+                    .address,
+                key.label.as_deref().unwrap_or_default()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zcash_keys::encoding::encode_extended_spending_key;
+
+    const PHRASE: &str = "clerk family rack dragon cannon wait vendor penalty absent country better coast expand true middle stable assist clerk tent phone toilet knee female kitchen";
+
+    fn wif(secret: &SecretKey) -> String {
+        let payload = [&[WIF_PREFIX_MAIN][..], &secret.secret_bytes(), &[0x01]].concat();
+        bs58::encode(payload).with_check().into_string()
+    }
+
+    #[test]
+    fn test_zcashd_dump() {
+        let mnemonic = <Mnemonic<English>>::from_phrase(PHRASE).unwrap();
+        let seed = mnemonic.to_seed("");
+        let hd_extsk = ExtendedSpendingKey::master(&seed)
+            .derive_child(ChildIndex::hardened(32))
+            .derive_child(ChildIndex::hardened(133))
+            .derive_child(ChildIndex::hardened(1));
+        let imported_extsk = ExtendedSpendingKey::master(&[7u8; 32]);
+        let hd_tkey = AccountPrivKey::from_seed(
+            &Network::Mainnet.params(),
+            &seed,
+            AccountId::try_from(0).unwrap(),
+        )
+        .unwrap()
+        .derive_external_secret_key(NonHardenedChildIndex::from_index(2).unwrap())
+        .unwrap();
+        let imported_tkey = SecretKey::from_slice(&[0x33u8; 32]).unwrap();
+        let hrp = "secret-extended-key-main";
+
+        let text = [
+            "# Wallet dump created by Zcash v5.4.0".to_string(),
+            "# * Created on 2023-01-01T00:00:00Z".to_string(),
+            "#".to_string(),
+            "# Emergency Recovery Phrase".to_string(),
+            format!("# - recovery_phrase=\"{}\"", PHRASE),
+            "# - language=English".to_string(),
+            "".to_string(),
+            format!(
+                "{} 2020-06-01T00:00:00Z label=My%20savings # addr=t1...",
+                wif(&imported_tkey)
+            ),
+            format!(
+                "{} 2021-06-01T00:00:00Z change=1 # addr=t1... hdkeypath=m/44'/133'/0'/0/2",
+                wif(&hd_tkey)
+            ),
+            "# Sapling keys".to_string(),
+            format!(
+                "{} 2021-06-01T00:00:00Z # zaddr=zs1... (hdkeypath=m/32'/133'/1')",
+                encode_extended_spending_key(hrp, &hd_extsk)
+            ),
+            format!(
+                "{} 2021-06-01T00:00:00Z # zaddr=zs1... (hdkeypath=m/32'/133'/0')",
+                encode_extended_spending_key(hrp, &imported_extsk)
+            ),
+            "# End of dump".to_string(),
+        ]
+        .join("\n");
+
+        let dump = ZcashdDump::parse(&text).unwrap();
+        assert_eq!(dump.get_wallet_version(), 5_040_000);
+        assert_eq!(dump.get_network(), Network::Mainnet);
+        assert_eq!(dump.get_wallet_seed(), Some(mnemonic.entropy().to_vec()));
+        assert_eq!(dump.tkeys[0].label.as_deref(), Some("My savings"));
+
+        assert_eq!(dump.get_hd_account_count(), 2);
+        assert_eq!(dump.get_hd_transparent_address_count(), 3);
+        let accounts = dump.get_wallet_accounts().unwrap();
+        assert_eq!(accounts[0].keys.tkeys[0].pk, hd_tkey);
+        assert_eq!(
+            accounts[0].keys.tkeys[0].derivation_path.as_deref(),
+            Some("m/44'/133'/0'/0/2")
+        );
+        assert!(accounts[1].keys.zkeys[0].address.starts_with("zs1"));
+
+        // A derivation path that doesn't match the recovery phrase is not trusted
+        let imported = dump.get_imported_keys();
+        assert_eq!(imported.tkeys.len(), 1);
+        assert_eq!(imported.tkeys[0].pk, imported_tkey);
+        assert!(imported.tkeys[0].address.starts_with("t1"));
+        assert_eq!(imported.zkeys.len(), 1);
+
+        // The oldest key was created in June 2020
+        assert!((830_000..900_000).contains(&dump.get_birthday()));
+    }
+
+    #[test]
+    fn test_reject_other_text() {
+        assert!(ZcashdDump::parse("hello").is_err());
+    }
+
+    #[test]
+    fn test_zcashd_dump_truncated() {
+        let key = wif(&SecretKey::from_slice(&[0x33u8; 32]).unwrap());
+        // Dump cut off in the middle of a key
+        let text = format!(
+            "# Wallet dump created by Zcash v5.4.0\n{} 2020-06-01",
+            &key[..key.len() / 2]
+        );
+        let err = ZcashdDump::parse(&text).unwrap_err();
+        assert!(err.to_string().starts_with("Line 2"));
+    }
+}
@@ -0,0 +1,508 @@
+//! # ZeWIF Parser
+//!
+//! Reads `.zewif` files, the CBOR encoded ZeWIF envelopes written by our exporter or
+//! by other tools such as `zmigrate`, so they can be synced like any wallet file.
+//!
+//! ## Overview
+//! The parser reads the first wallet of the document:
+//! - **Seed**: the BIP-39 mnemonic of the wallet, of any length. When the
+//!   org.zingolabs extension is attached, its seed must match the mnemonic.
+//! - **Accounts**: accounts with a ZIP 32 account index are derived from the seed,
+//!   the others hold imported keys. Without a BIP-39 seed every account is read as
+//!   imported keys.
+//! - **Keys**: transparent secret keys and sapling extended spending keys found on
+//!   the account addresses.
+//!
+//! Password protected files are decrypted with [`WalletParser::decrypt`]. When the
+//! file is an SSKR share, the shares found next to it are recombined first.
+//! Watch-only exports are rejected, as they hold no seed.
+//!
+//! ## Caveats
+//! - Wallets without a BIP-39 mnemonic, such as zcashd wallets holding a legacy seed
+//!   or only imported keys, are read but can't be synced from a seed phrase.
+//! - Accounts without a birthday height are scanned from the Sapling activation.
+//! - Addresses holding only viewing keys, and orchard keys, can't be synced from a
+//!   ZeWIF file yet.
+
+use std::io;
+use std::path::Path;
+
+use bc_envelope::Envelope;
+use bip0039::{English, Mnemonic};
+use sapling::zip32::ExtendedSpendingKey;
+use secp256k1::SecretKey;
+use zcash_primitives::consensus::BlockHeight;
+use zewif::sapling::SaplingAddress;
+use zewif::{
+    Account, ProtocolAddress, SeedMaterial, TransparentSpendAuthority, Zewif, transparent,
+};
+
+use super::zcashd::estimate_birthday;
+use super::{
+    KeyScope, WalletAccount, WalletImportedKeys, WalletKeyType, WalletKeys, WalletParser,
+    WalletTKey, WalletZKey,
+};
+use crate::export::extension::ZingolabsExtension;
+use crate::export::{protect, read_zewif, watch_only};
+use crate::settings::Network;
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.into())
+}
+
+/// BIP-39 binary seed of the mnemonic with the given entropy, as stored in the
+/// org.zingolabs extension.
+fn binary_seed(entropy: &[u8]) -> io::Result<[u8; 64]> {
+    Ok(<Mnemonic<English>>::from_entropy(entropy.to_vec())
+        .map_err(|e| invalid(format!("Invalid mnemonic: {}", e)))?
+        .to_seed(""))
+}
+
+#[derive(Debug, Clone)]
+pub struct ZewifFile {
+    pub network: Network,
+    /// BIP-39 entropy of the wallet mnemonic, if it has one.
+    pub seed: Option<Vec<u8>>,
+    pub export_height: u32,
+    pub accounts: Vec<WalletAccount>,
+    /// Highest ZIP 32 account index of the HD accounts, plus one.
+    pub hd_account_count: u32,
+    /// Transparent addresses to scan per HD account.
+    pub transparent_address_count: u32,
+    pub imported_keys: WalletImportedKeys,
+    /// Password protected envelope, until it is decrypted.
+    pub locked: Option<Envelope>,
+}
+
+impl ZewifFile {
+    fn network_of(network: zewif::Network) -> Network {
+        match network {
+            zewif::Network::Main => Network::Mainnet,
+            zewif::Network::Test => Network::Testnet,
+            zewif::Network::Regtest => Network::Regtest,
+        }
+    }
+
+    /// Entropy of the BIP-39 mnemonic. Legacy zcashd seeds aren't BIP-39 seeds, they
+    /// are read as no seed like a missing seed material.
+    fn read_seed(seed_material: Option<&SeedMaterial>) -> io::Result<Option<Vec<u8>>> {
+        let phrase = match seed_material {
+            Some(SeedMaterial::Bip39Mnemonic(mnemonic)) => mnemonic.mnemonic().to_string(),
+            _ => return Ok(None),
+        };
+        let mnemonic = <Mnemonic<English>>::from_phrase(phrase.as_str())
+            .map_err(|e| invalid(format!("Invalid mnemonic: {}", e)))?;
+
+        Ok(Some(mnemonic.entropy().to_vec()))
+    }
+
+    fn transparent_key(
+        address: &transparent::Address,
+        key_type: WalletKeyType,
+    ) -> Option<WalletTKey> {
+        let pk = match address.spend_authority() {
+            Some(TransparentSpendAuthority::SpendingKey(key)) => {
+                SecretKey::from_slice(key.as_ref()).ok()?
+            }
+            _ => return None,
+        };
+        let (scope, index) = address
+            .derivation_info()
+            .map(|info| {
+                let scope = match u32::from(info.change()) {
+                    0 => KeyScope::External,
+                    _ => KeyScope::Internal,
+                };
+                (scope, u32::from(info.address_index()))
+            })
+            .unwrap_or_default();
+
+        Some(WalletTKey {
+            pk,
+            key_type,
+            index,
+            address: address.address().to_string(),
+            scope,
+            derivation_path: None,
+        })
+    }
+
+    fn sapling_key(
+        address: &SaplingAddress,
+        key_type: WalletKeyType,
+        index: u32,
+    ) -> Option<WalletZKey> {
+        let extsk = ExtendedSpendingKey::from_bytes(address.spending_key()?.as_ref()).ok()?;
+
+        Some(WalletZKey {
+            fvk: extsk.to_extended_full_viewing_key(),
+            extsk: Some(extsk),
+            key_type,
+            index,
+            address: address.address().to_string(),
+            diversified_addresses: vec![],
+            scope: KeyScope::External,
+            derivation_path: address.hd_derivation_path().map(|p| p.to_string()),
+        })
+    }
+
+    /// Keys with spending authority found on the account addresses.
+    fn account_keys(
+        account: &Account,
+        key_type: WalletKeyType,
+    ) -> (Vec<WalletTKey>, Vec<WalletZKey>) {
+        let index = account.zip32_account_id().unwrap_or_default();
+        let mut tkeys = vec![];
+        let mut zkeys = vec![];
+        for address in account.addresses().values() {
+            match address.address() {
+                ProtocolAddress::Transparent(t) => {
+                    tkeys.extend(Self::transparent_key(t, key_type.clone()))
+                }
+                ProtocolAddress::Sapling(z) => {
+                    let Some(zkey) = Self::sapling_key(z, key_type.clone(), index) else {
+                        continue;
+                    };
+                    // Addresses of the same key are diversified addresses
+                    match zkeys.iter_mut().find(|k| k.fvk == zkey.fvk) {
+                        Some(key) => key.diversified_addresses.push(zkey.address),
+                        None => zkeys.push(zkey),
+                    }
+                }
+                _ => (),
+            }
+        }
+        (tkeys, zkeys)
+    }
+
+    /// Placeholder for a password protected file, filled by [`WalletParser::decrypt`].
+    fn locked(envelope: Envelope) -> Self {
+        Self {
+            network: Network::default(),
+            seed: None,
+            export_height: 0,
+            accounts: vec![],
+            hd_account_count: 0,
+            transparent_address_count: 1,
+            imported_keys: WalletImportedKeys::default(),
+            locked: Some(envelope),
+        }
+    }
+
+    fn from_envelope(envelope: Envelope) -> io::Result<Self> {
+        if watch_only::is_watch_only(&envelope) {
+            return Err(invalid(
+                "Watch-only ZeWIF exports hold no seed or spending keys and can't be synced",
+            ));
+        }
+
+        let extension = ZingolabsExtension::from_attachments(&envelope)
+            .map_err(|e| invalid(format!("Invalid org.zingolabs extension: {:#}", e)))?;
+        let zewif =
+            Zewif::try_from(envelope).map_err(|e| invalid(format!("Invalid ZeWIF: {}", e)))?;
+
+        let wallet = zewif
+            .wallets()
+            .first()
+            .ok_or_else(|| invalid("ZeWIF file has no wallet"))?;
+
+        let network = Self::network_of(wallet.network());
+        let seed = Self::read_seed(wallet.seed_material())?;
+        if let (Some(extension), Some(seed)) = (&extension, &seed) {
+            if extension.seed[..] != binary_seed(seed)?[..] {
+                return Err(invalid(
+                    "Seed of the org.zingolabs extension doesn't match the wallet mnemonic",
+                ));
+            }
+        }
+
+        // Accounts can only be derived again from a BIP-39 seed
+        let is_hd = |a: &&Account| seed.is_some() && a.zip32_account_id().is_some();
+        let mut hd_accounts: Vec<&Account> = wallet.accounts().iter().filter(is_hd).collect();
+        hd_accounts.sort_by_key(|a| a.zip32_account_id());
+        let hd_account_count = hd_accounts
+            .iter()
+            .filter_map(|a| a.zip32_account_id())
+            .map(|id| id + 1)
+            .max()
+            .unwrap_or_default();
+
+        let mut accounts = vec![];
+        let mut transparent_address_count = 1;
+        for account in hd_accounts {
+            let (tkeys, zkeys) = Self::account_keys(account, WalletKeyType::HdDerived);
+            if let Some(max) = tkeys.iter().map(|k| k.index + 1).max() {
+                transparent_address_count = transparent_address_count.max(max);
+            }
+            accounts.push(WalletAccount {
+                name: account.name().to_string(),
+                seed: seed.clone(),
+                birthday: account
+                    .birthday_height()
+                    .map(|h| BlockHeight::from_u32(u32::from(h)))
+                    .unwrap_or(BlockHeight::from_u32(0)),
+                keys: WalletKeys {
+                    tkeys,
+                    zkeys,
+                    okeys: vec![],
+                },
+            });
+        }
+
+        let mut imported_keys = WalletImportedKeys::default();
+        for account in wallet.accounts().iter().filter(|a| !is_hd(a)) {
+            let (tkeys, zkeys) = Self::account_keys(account, WalletKeyType::Imported);
+            imported_keys.tkeys.extend(tkeys);
+            imported_keys.zkeys.extend(zkeys);
+        }
+
+        Ok(Self {
+            network,
+            seed,
+            export_height: u32::from(zewif.export_height()),
+            accounts,
+            hd_account_count,
+            transparent_address_count,
+            imported_keys,
+            locked: None,
+        })
+    }
+}
+
+impl WalletParser for ZewifFile {
+    fn read(filename: &str) -> io::Result<Self> {
+        let envelope = read_zewif(Path::new(filename)).map_err(|e| invalid(format!("{:#}", e)))?;
+        if protect::is_locked(&envelope) {
+            return Ok(Self::locked(envelope));
+        }
+        Self::from_envelope(envelope)
+    }
+
+    fn is_encrypted(&self) -> bool {
+        self.locked.is_some()
+    }
+
+    fn decrypt(&mut self, password: &str) -> io::Result<()> {
+        let Some(locked) = &self.locked else {
+            return Ok(());
+        };
+        let envelope = protect::unlock(locked, password)
+            .map_err(|e| io::Error::new(io::ErrorKind::PermissionDenied, format!("{:#}", e)))?;
+        *self = Self::from_envelope(envelope)?;
+        Ok(())
+    }
+
+    fn get_wallet_name(&self) -> String {
+        "ZeWIF".to_string()
+    }
+
+    fn get_wallet_version(&self) -> u64 {
+        0
+    }
+
+    fn get_network(&self) -> Network {
+        self.network
+    }
+
+    fn get_wallet_seed(&self) -> Option<Vec<u8>> {
+        self.seed.clone()
+    }
+
+    fn get_wallet_accounts(&self) -> io::Result<Vec<WalletAccount>> {
+        Ok(self.accounts.clone())
+    }
+
+    /// Oldest account birthday, no earlier than the Sapling activation height.
+    fn get_birthday(&self) -> u64 {
+        self.accounts
+            .iter()
+            .map(|a| u64::from(u32::from(a.birthday)))
+            .min()
+            .unwrap_or_default()
+            .max(estimate_birthday(self.network, None))
+    }
+
+    fn get_hd_account_count(&self) -> u32 {
+        self.hd_account_count
+    }
+
+    fn get_hd_transparent_address_count(&self) -> u32 {
+        self.transparent_address_count
+    }
+
+    fn get_imported_keys(&self) -> WalletImportedKeys {
+        self.imported_keys.clone()
+    }
+
+    fn print_internal(&self) {
+        println!("ZeWIF: {:#?}", self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bc_envelope::prelude::CBOREncodable;
+    use zewif::{Address, BlockHeight as ZewifHeight, ZewifWallet};
+
+    use crate::export::convert::tests::test_zewif;
+    use crate::export::extension::SourceMetadata;
+    use crate::export::protect::Protection;
+    use crate::export::write_zewif;
+    use crate::parser::test_dir;
+
+    const PHRASE: &str = "clerk family rack dragon cannon wait vendor penalty absent country better coast expand true middle stable assist clerk tent phone toilet knee female kitchen";
+    const SHORT_PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    fn entropy(phrase: &str) -> Vec<u8> {
+        <Mnemonic<English>>::from_phrase(phrase)
+            .unwrap()
+            .entropy()
+            .to_vec()
+    }
+
+    #[test]
+    fn test_zewif_roundtrip() {
+        let wallet =
+            ZewifFile::from_envelope(Envelope::from(test_zewif(PHRASE, 2_000_000))).unwrap();
+        assert_eq!(wallet.get_network(), Network::Mainnet);
+        assert_eq!(wallet.get_wallet_seed(), Some(entropy(PHRASE)));
+        assert_eq!(wallet.export_height, 2_001_000);
+        assert_eq!(wallet.get_birthday(), 2_000_000);
+        assert_eq!(wallet.get_hd_account_count(), 1);
+        assert!(wallet.get_imported_keys().is_empty());
+
+        let accounts = wallet.get_wallet_accounts().unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert!(accounts[0].keys.zkeys[0].extsk.is_some());
+        assert!(accounts[0].keys.zkeys[0].address.starts_with("zs1"));
+        assert!(accounts[0].keys.tkeys[0].address.starts_with("t1"));
+    }
+
+    #[test]
+    fn test_zewif_short_phrase() {
+        let wallet =
+            ZewifFile::from_envelope(Envelope::from(test_zewif(SHORT_PHRASE, 2_000_000))).unwrap();
+        assert_eq!(wallet.get_wallet_seed(), Some(entropy(SHORT_PHRASE)));
+        assert_eq!(wallet.get_hd_account_count(), 1);
+    }
+
+    #[test]
+    fn test_zewif_imported_keys_only() {
+        let mut address = transparent::Address::new("t1imported".to_string());
+        address.set_spend_authority(TransparentSpendAuthority::SpendingKey([0x11u8; 32].into()));
+        let mut account = Account::new();
+        account.set_name("Imported keys");
+        account.add_address(Address::new(ProtocolAddress::Transparent(address)));
+        // Without a BIP-39 seed, as in legacy zcashd wallets, HD accounts can't be
+        // derived again and their keys are read as imported keys
+        account.set_zip32_account_id(0);
+        let mut zewif_wallet = ZewifWallet::new(zewif::Network::Main);
+        zewif_wallet.add_account(account);
+        let mut zewif = Zewif::new(ZewifHeight::from_u32(2_500_000));
+        zewif.add_wallet(zewif_wallet);
+
+        let wallet = ZewifFile::from_envelope(Envelope::from(zewif)).unwrap();
+        assert_eq!(wallet.get_wallet_seed(), None);
+        assert_eq!(wallet.get_hd_account_count(), 0);
+        assert!(wallet.get_wallet_accounts().unwrap().is_empty());
+        let imported = wallet.get_imported_keys();
+        assert_eq!(imported.tkeys.len(), 1);
+        assert_eq!(imported.tkeys[0].address, "t1imported");
+        // Without accounts, the birthday is the sapling activation height
+        assert_eq!(wallet.get_birthday(), 419_200);
+    }
+
+    #[test]
+    fn test_zewif_extension_seed() {
+        let envelope = Envelope::from(test_zewif(PHRASE, 2_000_000));
+        let extension = |seed: Vec<u8>| ZingolabsExtension {
+            seed,
+            recovery_phrase: PHRASE.to_string(),
+            metadata: SourceMetadata::default(),
+        };
+
+        let matching =
+            extension(binary_seed(&entropy(PHRASE)).unwrap().to_vec()).attach(envelope.clone());
+        assert!(ZewifFile::from_envelope(matching).is_ok());
+
+        let mismatch = extension(vec![0u8; 64]).attach(envelope.clone());
+        let err = ZewifFile::from_envelope(mismatch).unwrap_err();
+        assert!(err.to_string().contains("doesn't match"));
+
+        // The entropy alone isn't the binary seed
+        let mismatch = extension(entropy(PHRASE)).attach(envelope);
+        let err = ZewifFile::from_envelope(mismatch).unwrap_err();
+        assert!(err.to_string().contains("doesn't match"));
+    }
+
+    #[test]
+    fn test_zewif_reject_watch_only() {
+        let envelope = watch_only::elide_secrets(&Envelope::from(test_zewif(PHRASE, 2_000_000)));
+        let err = ZewifFile::from_envelope(envelope).unwrap_err();
+        assert!(err.to_string().contains("Watch-only"));
+    }
+
+    #[test]
+    fn test_zewif_locked() {
+        let dir = test_dir("locked");
+        let path = dir.join("wallet.zewif");
+        let envelope = Envelope::from(test_zewif(PHRASE, 2_000_000));
+        write_zewif(
+            &envelope,
+            &path,
+            &[Protection::Password("hunter2".to_string())],
+        )
+        .unwrap();
+
+        let mut wallet = ZewifFile::read(path.to_str().unwrap()).unwrap();
+        assert!(wallet.is_encrypted());
+        assert_eq!(wallet.get_wallet_seed(), None);
+        let err = wallet.decrypt("wrong").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+
+        wallet.decrypt("hunter2").unwrap();
+        assert!(!wallet.is_encrypted());
+        assert_eq!(wallet.get_wallet_seed(), Some(entropy(PHRASE)));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_zewif_shares() {
+        let dir = test_dir("shares");
+        let envelope = Envelope::from(test_zewif(PHRASE, 2_000_000));
+        let shares = write_zewif(
+            &envelope,
+            &dir.join("wallet.zewif"),
+            &[Protection::Shares {
+                threshold: 2,
+                count: 3,
+            }],
+        )
+        .unwrap();
+        assert_eq!(shares.len(), 3);
+
+        // Any two shares recombine the export
+        std::fs::remove_file(&shares[1]).unwrap();
+        let wallet = ZewifFile::read(shares[0].to_str().unwrap()).unwrap();
+        assert_eq!(wallet.get_wallet_seed(), Some(entropy(PHRASE)));
+
+        std::fs::remove_file(&shares[2]).unwrap();
+        assert!(ZewifFile::read(shares[0].to_str().unwrap()).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_zewif_corrupt() {
+        let envelope = Envelope::new("not a wallet");
+        assert!(ZewifFile::from_envelope(envelope.clone()).is_err());
+
+        // File cut off in the middle of the envelope
+        let dir = test_dir("truncated");
+        let path = dir.join("wallet.zewif");
+        let data = envelope.to_cbor_data();
+        std::fs::write(&path, &data[..data.len() / 2]).unwrap();
+        assert!(ZewifFile::read(path.to_str().unwrap()).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,432 @@
+//! # Zingolib Parser
+//!
+//! Reads the `zingo-wallet.dat` files written by zingo-cli and Zingo PC before the
+//! pepper-sync rewrite of zingolib, which can no longer load them.
+//!
+//! ## Overview
+//! The file is written linearly, like the ZecWallet Lite files it descends from:
+//! - **Wallet version**: `u64`. Versions up to 25 are ZecWallet Lite files, read by
+//!   [`super::zwl`].
+//! - **Wallet capability**: the unified spending or viewing key of the wallet, and the
+//!   receivers of every address it generated.
+//! - **Transaction map**: not parsed, see the caveats.
+//! - **Chain name**, **wallet options**, **birthday**, **verified tree** and
+//!   **price info**.
+//! - **Seed**: the mnemonic entropy, followed since version 28 by the ZIP 32 account
+//!   index of the wallet.
+//!
+//! Synced wallets are converted to ZeWIF by [`crate::export`], like every other wallet.
+//!
+//! ## Caveats
+//! - The transaction map changed with nearly every zingolib release, and holds nothing
+//!   that can't be found again by syncing the seed. The parser skips it, locating the
+//!   chain name record that follows it instead: the chain name candidate whose trailing
+//!   records parse up to the end of the file is taken.
+//! - Wallets created from a viewing key hold no seed and can't be swept.
+
+use std::io::{self, Cursor, ErrorKind, Read};
+
+use bip0039::{English, Mnemonic};
+use byteorder::{LittleEndian, ReadBytesExt};
+use zcash_client_backend::proto::service::TreeState;
+use zcash_encoding::{Optional, Vector};
+
+use super::error::ParseError;
+use super::zwl::data::WalletZecPriceInfo;
+use super::{WalletAccount, WalletImportedKeys, WalletKeys, WalletParser};
+use crate::export::extension::{PriceInfo, SourceMetadata};
+use crate::settings::Network;
+
+/// First wallet version written by zingolib rather than ZecWallet Lite.
+pub(super) const FIRST_ZINGOLIB_VERSION: u64 = 26;
+/// Last wallet version of the legacy zingolib format.
+pub(super) const LAST_LEGACY_VERSION: u64 = 32;
+/// Receiver flag of addresses with a transparent receiver.
+const TRANSPARENT_RECEIVER: u8 = 0b100;
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, msg.into())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyStore {
+    /// Unified spending key, derived from the seed.
+    Spend,
+    /// Unified full viewing key of a wallet created from a viewing key.
+    View,
+    Empty,
+}
+
+/// The parts of the zingolib `WalletCapability` needed for recovery.
+#[derive(Debug, Clone)]
+pub struct WalletCapability {
+    pub version: u8,
+    pub key_store: Option<KeyStore>,
+    /// Receivers of every address generated by the wallet, `None` when they could not
+    /// be read.
+    pub receiver_selections: Option<Vec<u8>>,
+}
+
+impl WalletCapability {
+    /// Reads the capability of versions 3 and 4. Older versions store legacy keys,
+    /// which are derived from the seed anyway, so only their version is read.
+    fn read<R: Read>(mut reader: R) -> Self {
+        let version = reader.read_u8().unwrap_or_default();
+        let (key_store, receiver_selections) = match version {
+            3 | 4 => {
+                let key_store = Self::read_key_store(&mut reader, version).ok();
+                let selections = key_store.and_then(|_| {
+                    Vector::read(&mut reader, |r| {
+                        let _version = r.read_u8()?;
+                        r.read_u8()
+                    })
+                    .ok()
+                });
+                (key_store, selections)
+            }
+            _ => (None, None),
+        };
+
+        Self {
+            version,
+            key_store,
+            receiver_selections,
+        }
+    }
+
+    fn read_key_store<R: Read>(mut reader: R, version: u8) -> io::Result<KeyStore> {
+        if version >= 4 {
+            let _rejection_addresses = reader.read_u32::<LittleEndian>()?;
+        }
+        let _version = reader.read_u8()?;
+        match reader.read_u8()? {
+            0 => {
+                Vector::read(&mut reader, |r| r.read_u8())?;
+                Ok(KeyStore::Spend)
+            }
+            1 => {
+                super::zwl::ZwlWallet::read_string(&mut reader)?;
+                Ok(KeyStore::View)
+            }
+            2 => Ok(KeyStore::Empty),
+            t => Err(invalid(format!("Unknown key store type {}", t))),
+        }
+    }
+}
+
+/// zingolib `WalletOptions`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ZingolibWalletOptions {
+    /// Memo download policy: 0 for no memos, 1 for wallet memos, 2 for all memos.
+    pub download_memos: u8,
+    /// Transactions with more outputs are not trial decrypted.
+    pub transaction_size_filter: Option<u32>,
+}
+
+impl ZingolibWalletOptions {
+    fn read<R: Read>(mut reader: R) -> io::Result<Self> {
+        let version = reader.read_u64::<LittleEndian>()?;
+        let download_memos = match reader.read_u8()? {
+            v @ 0..=2 => v,
+            v => return Err(invalid(format!("Bad download option {}", v))),
+        };
+        let transaction_size_filter = if version > 1 {
+            Optional::read(&mut reader, |r| r.read_u32::<LittleEndian>())?
+        } else {
+            Some(500)
+        };
+
+        Ok(Self {
+            download_memos,
+            transaction_size_filter,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Zingolib {
+    pub version: u64,
+    pub capability: WalletCapability,
+    pub chain: String,
+    pub options: ZingolibWalletOptions,
+    pub birthday: u64,
+    pub verified_tree: Option<TreeState>,
+    pub price_info: WalletZecPriceInfo,
+    pub seed_bytes: Vec<u8>,
+    /// ZIP 32 account index of the wallet.
+    pub account_index: u32,
+}
+
+/// The records following the transaction map.
+struct Trailer {
+    chain: String,
+    options: ZingolibWalletOptions,
+    birthday: u64,
+    verified_tree: Option<TreeState>,
+    price_info: WalletZecPriceInfo,
+    seed_bytes: Vec<u8>,
+    account_index: u32,
+}
+
+impl Trailer {
+    fn read(data: &[u8], version: u64) -> io::Result<Self> {
+        let mut reader = Cursor::new(data);
+        let chain = super::zwl::ZwlWallet::read_string(&mut reader)?;
+        if chain.parse::<Network>().is_err() {
+            return Err(invalid(format!("Unknown chain {}", chain)));
+        }
+        let options = ZingolibWalletOptions::read(&mut reader)?;
+        let birthday = reader.read_u64::<LittleEndian>()?;
+        let verified_tree = Optional::read(&mut reader, |r| {
+            use prost::Message;
+
+            let buf = Vector::read(r, |r| r.read_u8())?;
+            TreeState::decode(&buf[..])
+                .map_err(|e| invalid(format!("Invalid verified tree: {}", e)))
+        })?;
+        let price_info = WalletZecPriceInfo::read(&mut reader)?;
+        let seed_bytes = Vector::read(&mut reader, |r| r.read_u8())?;
+        let account_index = if !seed_bytes.is_empty() && version >= 28 {
+            reader.read_u32::<LittleEndian>()?
+        } else {
+            0
+        };
+
+        if reader.position() != data.len() as u64 {
+            return Err(invalid("Trailing data after the wallet seed"));
+        }
+
+        Ok(Self {
+            chain,
+            options,
+            birthday,
+            verified_tree,
+            price_info,
+            seed_bytes,
+            account_index,
+        })
+    }
+
+    /// Finds the chain name record following the transaction map, the last one whose
+    /// trailing records parse up to the end of the file.
+    fn find(data: &[u8], version: u64) -> io::Result<Self> {
+        let names: [&[u8]; 3] = [b"main", b"test", b"regtest"];
+        (0..data.len())
+            .rev()
+            .filter(|&offset| {
+                names.iter().any(|name| {
+                    let len = (name.len() as u64).to_le_bytes();
+                    data[offset..].starts_with(&len) && data[offset + 8..].starts_with(name)
+                })
+            })
+            .find_map(|offset| Self::read(&data[offset..], version).ok())
+            .ok_or_else(|| invalid("Could not find the wallet seed after the transaction map"))
+    }
+}
+
+impl Zingolib {
+    pub fn from_bytes(data: &[u8]) -> io::Result<Self> {
+        let mut reader = Cursor::new(data);
+        let version = reader
+            .read_u64::<LittleEndian>()
+            .map_err(|e| ParseError::at_offset(e, reader.position()))?;
+        if !(FIRST_ZINGOLIB_VERSION..=LAST_LEGACY_VERSION).contains(&version) {
+            return Err(invalid(format!(
+                "Not a legacy zingolib wallet, version {}",
+                version
+            )));
+        }
+
+        let capability = WalletCapability::read(&mut reader);
+        let trailer = Trailer::find(&data[reader.position() as usize..], version)?;
+
+        Ok(Self {
+            version,
+            capability,
+            chain: trailer.chain,
+            options: trailer.options,
+            birthday: trailer.birthday,
+            verified_tree: trailer.verified_tree,
+            price_info: trailer.price_info,
+            seed_bytes: trailer.seed_bytes,
+            account_index: trailer.account_index,
+        })
+    }
+
+    fn mnemonic(&self) -> Option<Mnemonic<English>> {
+        <Mnemonic<English>>::from_entropy(self.seed_bytes.clone()).ok()
+    }
+}
+
+impl WalletParser for Zingolib {
+    fn read(filename: &str) -> io::Result<Self> {
+        Self::from_bytes(&std::fs::read(filename)?)
+    }
+
+    fn get_wallet_name(&self) -> String {
+        "Zingolib".to_string()
+    }
+
+    fn get_wallet_version(&self) -> u64 {
+        self.version
+    }
+
+    fn get_network(&self) -> Network {
+        self.chain.parse().unwrap_or_default()
+    }
+
+    fn get_wallet_seed(&self) -> Option<Vec<u8>> {
+        self.mnemonic().map(|m| m.entropy().to_vec())
+    }
+
+    fn get_wallet_accounts(&self) -> io::Result<Vec<WalletAccount>> {
+        let Some(mnemonic) = self.mnemonic() else {
+            return Ok(vec![]);
+        };
+
+        Ok(vec![WalletAccount {
+            name: format!("Account {}", self.account_index + 1),
+            seed: Some(self.seed_bytes.clone()),
+            birthday: (self.birthday as u32).into(),
+            keys: WalletKeys::from_seed(
+                self.get_network(),
+                &mnemonic.to_seed(""),
+                self.account_index,
+            ),
+        }])
+    }
+
+    fn get_birthday(&self) -> u64 {
+        self.birthday
+    }
+
+    fn get_hd_account_count(&self) -> u32 {
+        self.account_index + 1
+    }
+
+    /// Every address with a transparent receiver took the next transparent child index.
+    fn get_hd_transparent_address_count(&self) -> u32 {
+        self.capability
+            .receiver_selections
+            .as_ref()
+            .map(|selections| {
+                selections
+                    .iter()
+                    .filter(|&&receivers| receivers & TRANSPARENT_RECEIVER != 0)
+                    .count() as u32
+            })
+            .unwrap_or_default()
+            .max(1)
+    }
+
+    /// zingolib wallets hold a single unified key, there are no imported keys.
+    fn get_imported_keys(&self) -> WalletImportedKeys {
+        WalletImportedKeys::default()
+    }
+
+    fn get_metadata(&self) -> SourceMetadata {
+        SourceMetadata {
+            wallet_options: None,
+            price_info: Some(PriceInfo {
+                currency: self.price_info.currency.clone(),
+                last_historical_prices_fetched_at: self
+                    .price_info
+                    .last_historical_prices_fetched_at,
+                historical_prices_retry_count: self.price_info.historical_prices_retry_count,
+            }),
+        }
+    }
+
+    fn print_internal(&self) {
+        println!(
+            "Zingolib wallet version {}, chain {}, capability v{} {:?}, birthday {}, account {}, seed: {}, options: {:?}",
+            self.version,
+            self.chain,
+            self.capability.version,
+            self.capability.key_store,
+            self.birthday,
+            self.account_index,
+            !self.seed_bytes.is_empty(),
+            self.options
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use byteorder::WriteBytesExt;
+
+    fn write_string(out: &mut Vec<u8>, s: &str) {
+        out.write_u64::<LittleEndian>(s.len() as u64).unwrap();
+        out.extend_from_slice(s.as_bytes());
+    }
+
+    fn wallet_file(version: u64) -> Vec<u8> {
+        let mut out = vec![];
+        out.write_u64::<LittleEndian>(version).unwrap();
+
+        // Capability v4: spending key and two addresses, one with a transparent receiver
+        out.write_u8(4).unwrap();
+        out.write_u32::<LittleEndian>(0).unwrap();
+        out.extend_from_slice(&[0, 0]);
+        Vector::write(&mut out, &[7u8; 40], |w, b| w.write_u8(*b)).unwrap();
+        Vector::write(&mut out, &[0b111u8, 0b011], |w, r| {
+            w.write_u8(1)?;
+            w.write_u8(*r)
+        })
+        .unwrap();
+
+        // Transaction map, with a memo that looks like a chain name record
+        out.extend_from_slice(&[22, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3]);
+        write_string(&mut out, "main");
+        out.extend_from_slice(&[9u8; 64]);
+
+        write_string(&mut out, "main");
+        out.write_u64::<LittleEndian>(2).unwrap();
+        out.write_u8(2).unwrap();
+        Optional::write(&mut out, Some(1000u32), |w, v| {
+            w.write_u32::<LittleEndian>(v)
+        })
+        .unwrap();
+        out.write_u64::<LittleEndian>(1_700_000).unwrap();
+        Optional::write(&mut out, None::<u8>, |w, v| w.write_u8(v)).unwrap();
+        WalletZecPriceInfo::new().write(&mut out).unwrap();
+        Vector::write(&mut out, &[1u8; 32], |w, b| w.write_u8(*b)).unwrap();
+        out.write_u32::<LittleEndian>(2).unwrap();
+        out
+    }
+
+    #[test]
+    fn test_zingolib_wallet() {
+        let wallet = Zingolib::from_bytes(&wallet_file(30)).unwrap();
+        assert_eq!(wallet.get_network(), Network::Mainnet);
+        assert_eq!(wallet.get_birthday(), 1_700_000);
+        assert_eq!(wallet.get_wallet_seed(), Some(vec![1u8; 32]));
+        assert_eq!(wallet.options.transaction_size_filter, Some(1000));
+        assert_eq!(wallet.capability.key_store, Some(KeyStore::Spend));
+        assert_eq!(wallet.get_hd_account_count(), 3);
+        assert_eq!(wallet.get_hd_transparent_address_count(), 1);
+
+        let accounts = wallet.get_wallet_accounts().unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].keys.zkeys[0].index, 2);
+    }
+
+    #[test]
+    fn test_zingolib_truncated() {
+        let data = wallet_file(30);
+        let err = Zingolib::from_bytes(&data[..4]).unwrap_err();
+        assert!(matches!(
+            ParseError::from(err),
+            ParseError::Truncated { .. }
+        ));
+        // The seed record can't be found once the end of the file is cut off
+        assert!(Zingolib::from_bytes(&data[..data.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_reject_zecwallet_versions() {
+        assert!(Zingolib::from_bytes(&wallet_file(25)).is_err());
+    }
+}
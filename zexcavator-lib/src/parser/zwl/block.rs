@@ -35,10 +35,14 @@ impl CompactBlockData {
         let _tree = if tree.size() == 0 { None } else { Some(tree) };
 
         // read version
-        let _version = reader.read_u64::<LittleEndian>()?;
-
-        // read "ecb" (encoded compact block?)
-        let ecb = Vector::read(&mut reader, |r| r.read_u8()).unwrap_or_default();
+        let version = reader.read_u64::<LittleEndian>()?;
+
+        // read "ecb" (encoded compact block), which was added in version 12
+        let ecb = if version <= 11 {
+            vec![]
+        } else {
+            Vector::read(&mut reader, |r| r.read_u8())?
+        };
 
         Ok(Self { ecb, height })
     }
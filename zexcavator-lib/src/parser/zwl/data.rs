@@ -5,6 +5,8 @@ use std::{
 };
 use zcash_encoding::Optional;
 
+use crate::parser::error::ParseError;
+
 // Struct that tracks the latest and historical price of ZEC in the wallet
 #[derive(Clone, Debug)]
 pub struct WalletZecPriceInfo {
@@ -44,10 +46,11 @@ impl WalletZecPriceInfo {
     pub fn read<R: Read>(mut reader: R) -> io::Result<Self> {
         let version = reader.read_u64::<LittleEndian>()?;
         if version > Self::serialized_version() {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "Can't read ZecPriceInfo because of incorrect version",
-            ));
+            return Err(ParseError::UnsupportedVersion {
+                record: "price info",
+                version,
+            }
+            .into());
         }
 
         // The "current" zec price is not persisted, since it is almost certainly outdated
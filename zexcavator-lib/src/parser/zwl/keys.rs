@@ -0,0 +1,406 @@
+use bip0039::{English, Mnemonic};
+use byteorder::{LittleEndian, ReadBytesExt};
+use crypto_secretbox::aead::{Aead, KeyInit};
+use crypto_secretbox::{Key, Nonce, XSalsa20Poly1305};
+use sapling::zip32::{ExtendedFullViewingKey, ExtendedSpendingKey};
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+use sha2::{Digest, Sha256};
+use std::fmt::Display;
+use std::io::{self, Read};
+use zcash_encoding::Vector;
+
+use super::ZwlWallet;
+use super::walletokey::WalletOKey;
+use super::wallettkey::WalletTKey;
+use super::walletzkey::WalletZKey;
+use crate::parser::error::ParseError;
+use crate::parser::zcashd::transparent_address;
+use crate::settings::Network;
+
+#[derive(Debug, Clone)]
+pub struct Keys {
+    // Is the wallet encrypted? If it is, then when writing to disk, the seed is always encrypted
+    // and the individual spending keys are not written
+    pub encrypted: bool,
+
+    // In memory only (i.e, this field is not written to disk). Is the wallet unlocked and are
+    // the spending keys present?
+    pub unlocked: bool,
+
+    pub enc_seed: [u8; 48], // If locked, this contains the encrypted seed
+    pub nonce: Vec<u8>,     // Nonce used to encrypt the wallet.
+
+    pub seed: [u8; 32], // Seed phrase for this wallet. If wallet is locked, this is 0
+
+    // List of keys, actually in this wallet. This is a combination of HD keys derived from the seed,
+    // viewing keys and imported spending keys.
+    pub zkeys: Vec<WalletZKey>,
+
+    // Transparent keys. If the wallet is locked, then the secret keys will be encrypted,
+    // but the addresses will be present. This Vec contains both wallet and imported tkeys
+    pub tkeys: Vec<WalletTKey>,
+
+    // Unified address (Orchard) keys actually in this wallet.
+    // If wallet is locked, only viewing keys are present.
+    pub okeys: Vec<WalletOKey>,
+}
+
+impl Keys {
+    pub fn serialized_version() -> u64 {
+        22
+    }
+
+    pub fn read<R: Read>(mut reader: R) -> io::Result<Self> {
+        let version = reader.read_u64::<LittleEndian>()?;
+        if version > Self::serialized_version() {
+            return Err(ParseError::UnsupportedVersion {
+                record: "keys",
+                version,
+            }
+            .into());
+        }
+
+        // Read if wallet is encrypted
+        let encrypted = reader.read_u8()? > 0;
+
+        // Read "possible" encypted seed
+        let mut enc_seed = [0u8; 48];
+        reader.read_exact(&mut enc_seed)?;
+
+        // Read nounce used for encyption
+        let nonce = Vector::read(&mut reader, |r| r.read_u8())?;
+
+        // Read "possible" clear seed
+        let mut seed_bytes = [0u8; 32];
+        reader.read_exact(&mut seed_bytes)?;
+
+        // Orchard keys were added in version 22
+        let okeys = if version <= 21 {
+            vec![]
+        } else {
+            Vector::read(&mut reader, |r| WalletOKey::read(r))?
+        };
+
+        let zkeys = Vector::read(&mut reader, |r| WalletZKey::read(r))?;
+
+        // Up to version 20, only the raw transparent keys and their addresses were stored
+        let tkeys = if version <= 20 {
+            let tkeys = Vector::read(&mut reader, |r| read_secret_key(r))?;
+            let taddresses = Vector::read(&mut reader, |r| ZwlWallet::read_string(r))?;
+            raw_tkeys(tkeys, taddresses)
+        } else {
+            Vector::read(&mut reader, |r| WalletTKey::read(r))?
+        };
+
+        Ok(Self {
+            encrypted,
+            unlocked: !encrypted,
+            enc_seed,
+            nonce,
+            seed: seed_bytes,
+            zkeys,
+            tkeys,
+            okeys,
+        })
+    }
+}
+
+impl Keys {
+    /// Reads the keys of wallet files up to version 14, which were written without a
+    /// keys version of their own and follow the layout of the wallet `version`.
+    ///
+    /// Files before version 4 stored neither encryption data nor transparent addresses,
+    /// those addresses are left empty until [`Keys::derive_taddresses`] is called with
+    /// the network of the wallet.
+    pub fn read_old<R: Read>(version: u64, mut reader: R) -> io::Result<Self> {
+        let encrypted = version >= 4 && reader.read_u8()? > 0;
+
+        let mut enc_seed = [0u8; 48];
+        if version >= 4 {
+            reader.read_exact(&mut enc_seed)?;
+        }
+
+        let nonce = if version >= 4 {
+            Vector::read(&mut reader, |r| r.read_u8())?
+        } else {
+            vec![]
+        };
+
+        let mut seed_bytes = [0u8; 32];
+        reader.read_exact(&mut seed_bytes)?;
+
+        // Up to version 6, the spending and viewing keys were written out individually
+        let zkeys: Vec<WalletZKey> = if version <= 6 {
+            let extsks = Vector::read(&mut reader, |r| ExtendedSpendingKey::read(r))?;
+
+            let extfvks = if version >= 4 {
+                Vector::read(&mut reader, |r| ExtendedFullViewingKey::read(r))?
+            } else {
+                extsks
+                    .iter()
+                    .map(|extsk| extsk.to_extended_full_viewing_key())
+                    .collect()
+            };
+
+            // A locked wallet only has the viewing keys
+            if extsks.is_empty() {
+                extfvks
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, extfvk)| WalletZKey::new_locked_hdkey(i as u32, extfvk))
+                    .collect()
+            } else {
+                extsks
+                    .into_iter()
+                    .zip(extfvks)
+                    .enumerate()
+                    .map(|(i, (extsk, extfvk))| {
+                        let zkey = WalletZKey::new_hdkey(i as u32, extsk);
+                        if zkey.extfvk != extfvk {
+                            return Err(io::Error::from(ParseError::InvalidKey(
+                                "Full viewing key doesn't match its spending key".into(),
+                            )));
+                        }
+                        Ok(zkey)
+                    })
+                    .collect::<io::Result<_>>()?
+            }
+        } else {
+            Vector::read(&mut reader, |r| WalletZKey::read(r))?
+        };
+
+        let tkeys = Vector::read(&mut reader, |r| read_secret_key(r))?;
+        let taddresses = if version >= 4 {
+            Vector::read(&mut reader, |r| ZwlWallet::read_string(r))?
+        } else {
+            vec![String::new(); tkeys.len()]
+        };
+
+        Ok(Self {
+            encrypted,
+            unlocked: !encrypted,
+            enc_seed,
+            nonce,
+            seed: seed_bytes,
+            zkeys,
+            tkeys: raw_tkeys(tkeys, taddresses),
+            okeys: vec![],
+        })
+    }
+
+    /// Fills in the transparent addresses that were not stored in the wallet file,
+    /// see [`Keys::read_old`].
+    pub fn derive_taddresses(&mut self, network: Network) {
+        let secp = Secp256k1::new();
+        for tkey in self.tkeys.iter_mut().filter(|t| t.address.is_empty()) {
+            if let Some(key) = &tkey.key {
+                let pubkey = PublicKey::from_secret_key(&secp, key);
+                tkey.address = transparent_address(network, &pubkey.serialize());
+            }
+        }
+    }
+}
+
+fn read_secret_key<R: Read>(mut reader: R) -> io::Result<SecretKey> {
+    let mut tpk_bytes = [0u8; 32];
+    reader.read_exact(&mut tpk_bytes)?;
+    SecretKey::from_slice(&tpk_bytes)
+        .map_err(|e| io::Error::from(ParseError::InvalidKey(e.to_string())))
+}
+
+/// HD transparent keys from the raw secret keys and addresses of older wallets.
+fn raw_tkeys(tkeys: Vec<SecretKey>, taddresses: Vec<String>) -> Vec<WalletTKey> {
+    tkeys
+        .into_iter()
+        .zip(taddresses)
+        .enumerate()
+        .map(|(i, (key, address))| WalletTKey::from_raw(key, address, i as u32))
+        .collect()
+}
+
+impl Keys {
+    /// Decrypts the seed and the spending keys of an encrypted wallet.
+    ///
+    /// ZecWallet Lite encrypts with libsodium's `secretbox` (XSalsa20-Poly1305), keyed
+    /// by the double SHA-256 of the password. HD keys are not stored encrypted, they
+    /// are derived again from the decrypted seed with the coin type of `network`.
+    /// Imported keys carry their own encrypted copy and nonce.
+    pub fn unlock(&mut self, password: &str, network: Network) -> io::Result<()> {
+        if !self.encrypted || self.unlocked {
+            return Ok(());
+        }
+
+        let key = double_sha256(password.as_bytes());
+        let seed = secretbox_open(&key, &self.nonce, &self.enc_seed)
+            .map_err(|_| io::Error::new(io::ErrorKind::PermissionDenied, "Incorrect password"))?;
+        self.seed = seed.try_into().map_err(|_| {
+            io::Error::new(io::ErrorKind::InvalidData, "Decrypted seed is not 32 bytes")
+        })?;
+
+        let bip39_seed = <Mnemonic<English>>::from_entropy(self.seed)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?
+            .to_seed("");
+
+        for okey in self.okeys.iter_mut() {
+            okey.unlock(&bip39_seed, &key, network)?;
+        }
+        for zkey in self.zkeys.iter_mut() {
+            zkey.unlock(&bip39_seed, &key, network)?;
+        }
+        for tkey in self.tkeys.iter_mut() {
+            tkey.unlock(&bip39_seed, &key, network)?;
+        }
+
+        self.unlocked = true;
+        Ok(())
+    }
+}
+
+/// Key derivation used by ZecWallet Lite to turn a password into a `secretbox` key.
+pub fn double_sha256(payload: &[u8]) -> [u8; 32] {
+    let h1 = Sha256::digest(payload);
+    Sha256::digest(h1).into()
+}
+
+/// Opens a libsodium `secretbox`, where the MAC is stored before the ciphertext.
+pub fn secretbox_open(key: &[u8; 32], nonce: &[u8], ciphertext: &[u8]) -> io::Result<Vec<u8>> {
+    let nonce: [u8; 24] = nonce.try_into().map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Invalid nonce length {}", nonce.len()),
+        )
+    })?;
+
+    XSalsa20Poly1305::new(&Key::from(*key))
+        .decrypt(&Nonce::from(nonce), ciphertext)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Decryption failed"))
+}
+
+impl Display for Keys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, ">> Keys << ").unwrap();
+        writeln!(f, "Version: {}", Keys::serialized_version()).unwrap();
+        writeln!(f, "Encrypted: {}", self.encrypted).unwrap();
+
+        match self.encrypted {
+            true => {
+                writeln!(f, "Encrypted seed: {}", hex::encode(self.enc_seed)).unwrap();
+                writeln!(f, "Nonce: {}", hex::encode(&self.nonce)).unwrap();
+            }
+            false => {
+                writeln!(f, "Seed: {}", hex::encode(self.seed)).unwrap();
+            }
+        }
+
+        writeln!(f, "=== ORCHARD ===").unwrap();
+        writeln!(f, "Orchard keys found: {}", self.okeys.len()).unwrap();
+
+        for okey in &self.okeys {
+            writeln!(f, "{}", okey).unwrap();
+        }
+
+        writeln!(f, "=== SAPLING ===").unwrap();
+        writeln!(f, "Sapling keys found: {}", self.zkeys.len()).unwrap();
+
+        for zkey in &self.zkeys {
+            writeln!(f, "{}", zkey).unwrap();
+        }
+
+        writeln!(f, "=== TRANSPARENT ===").unwrap();
+        writeln!(f, "Transparent keys found: {}", self.tkeys.len()).unwrap();
+        for tkey in &self.tkeys {
+            writeln!(f, "{}", tkey).unwrap();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Password of `testvectors/zwl/wallet-encrypted.hex`, the keys of
+    /// `zecwallet-light-wallet.dat` locked the way ZecWallet Lite locks them. The seed,
+    /// an imported sapling key and an imported transparent key were sealed with an
+    /// implementation of `secretbox` independent of this crate.
+    const PASSWORD: &str = "zexcavator-2016";
+
+    fn encrypted_keys() -> Keys {
+        let fixture = include_str!("../../../testvectors/zwl/wallet-encrypted.hex");
+        let hex: String = fixture.split_whitespace().collect();
+        ZwlWallet::read_bytes(&hex::decode(hex).unwrap())
+            .unwrap()
+            .keys
+    }
+
+    fn real_keys() -> Keys {
+        let data = std::fs::read("../zecwallet-light-wallet.dat").unwrap();
+        ZwlWallet::read_bytes(&data).unwrap().keys
+    }
+
+    #[test]
+    fn test_double_sha256() {
+        assert_eq!(
+            hex::encode(double_sha256(PASSWORD.as_bytes())),
+            "0fb598a8b6d5dba3119d580c8036c78bc6476c025e66866e17387ee7717ea19b"
+        );
+    }
+
+    #[test]
+    fn test_secretbox_open() {
+        let keys = encrypted_keys();
+        let key = double_sha256(PASSWORD.as_bytes());
+
+        let seed = secretbox_open(&key, &keys.nonce, &keys.enc_seed).unwrap();
+        assert_eq!(
+            hex::encode(seed),
+            "2a6a52c2210217ecfc8d1400a62056166503d2a3169e0dc54f7d51ce3cf7153b"
+        );
+
+        let mut tampered = keys.enc_seed;
+        tampered[20] ^= 1;
+        assert!(secretbox_open(&key, &keys.nonce, &tampered).is_err());
+        assert!(secretbox_open(&double_sha256(b"wrong"), &keys.nonce, &keys.enc_seed).is_err());
+        assert!(secretbox_open(&key, &keys.nonce[..12], &keys.enc_seed).is_err());
+    }
+
+    #[test]
+    fn test_unlock() {
+        let mut keys = encrypted_keys();
+        assert!(keys.encrypted && !keys.unlocked);
+        assert_eq!(keys.seed, [0; 32]);
+        assert!(keys.zkeys.iter().all(|z| z.extsk.is_none()));
+        assert!(keys.tkeys.iter().all(|t| t.key.is_none()));
+        assert!(keys.okeys.iter().all(|o| o.sk.is_none()));
+
+        let err = keys.unlock("wrong password", Network::Mainnet).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        assert!(!keys.unlocked);
+        assert_eq!(keys.seed, [0; 32]);
+
+        keys.unlock(PASSWORD, Network::Mainnet).unwrap();
+        assert!(keys.unlocked);
+
+        // HD keys are derived again from the seed, imported keys are decrypted from
+        // their own copy
+        let real = real_keys();
+        assert_eq!(keys.seed, real.seed);
+        let extsks: Vec<_> = keys.zkeys.iter().map(|z| z.extsk.clone()).collect();
+        let expected: Vec<_> = real.zkeys.iter().map(|z| z.extsk.clone()).collect();
+        assert_eq!(extsks, expected);
+        let tkeys: Vec<_> = keys.tkeys.iter().map(|t| t.key).collect();
+        let expected: Vec<_> = real.tkeys.iter().map(|t| t.key).collect();
+        assert_eq!(tkeys, expected);
+        let osks: Vec<_> = keys
+            .okeys
+            .iter()
+            .map(|o| o.sk.map(|sk| *sk.to_bytes()))
+            .collect();
+        let expected: Vec<_> = real
+            .okeys
+            .iter()
+            .map(|o| o.sk.map(|sk| *sk.to_bytes()))
+            .collect();
+        assert_eq!(osks, expected);
+    }
+}
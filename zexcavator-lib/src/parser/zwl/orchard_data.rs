@@ -11,6 +11,8 @@ use zcash_primitives::{
     transaction::TxId,
 };
 
+use crate::parser::error::ParseError;
+
 pub const MERKLE_DEPTH: u8 = 32;
 
 pub const SER_V1: u8 = 1;
@@ -79,7 +81,13 @@ impl OrchardNoteData {
     // Reading a note also needs the corresponding address to read from.
     pub fn read<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
         let version = reader.read_u64::<LittleEndian>()?;
-        assert!(version <= Self::serialized_version());
+        if version > Self::serialized_version() {
+            return Err(ParseError::UnsupportedVersion {
+                record: "orchard note",
+                version,
+            }
+            .into());
+        }
 
         let fvk = FullViewingKey::read(&mut reader)?;
 
@@ -87,22 +95,29 @@ impl OrchardNoteData {
         // Raw address bytes is 43
         let mut address_bytes = [0u8; 43];
         reader.read_exact(&mut address_bytes)?;
-        let note_address = Address::from_raw_address_bytes(&address_bytes).unwrap();
+        let invalid = |e: &str| io::Error::new(io::ErrorKind::InvalidData, e.to_string());
+        let note_address = Option::<Address>::from(Address::from_raw_address_bytes(&address_bytes))
+            .ok_or_else(|| invalid("Invalid orchard note address"))?;
         let note_value = reader.read_u64::<LittleEndian>()?;
         let mut rho_bytes = [0u8; 32];
         reader.read_exact(&mut rho_bytes)?;
-        let note_rho = orchard_old::note::Nullifier::from_bytes(&rho_bytes).unwrap();
+        let note_rho = Option::<orchard_old::note::Nullifier>::from(
+            orchard_old::note::Nullifier::from_bytes(&rho_bytes),
+        )
+        .ok_or_else(|| invalid("Invalid orchard note rho"))?;
         let mut note_rseed_bytes = [0u8; 32];
         reader.read_exact(&mut note_rseed_bytes)?;
-        let note_rseed = RandomSeed::from_bytes(note_rseed_bytes, &note_rho).unwrap();
+        let note_rseed =
+            Option::<RandomSeed>::from(RandomSeed::from_bytes(note_rseed_bytes, &note_rho))
+                .ok_or_else(|| invalid("Invalid orchard note rseed"))?;
 
-        let note = orchard_old::Note::from_parts(
+        let note = Option::<orchard_old::Note>::from(orchard_old::Note::from_parts(
             note_address,
             NoteValue::from_raw(note_value),
             note_rho,
             note_rseed,
-        )
-        .unwrap();
+        ))
+        .ok_or_else(|| invalid("Invalid orchard note"))?;
 
         let witness_position = Optional::read(&mut reader, |r| {
             let pos = r.read_u64::<LittleEndian>()?;
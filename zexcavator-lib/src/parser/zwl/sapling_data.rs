@@ -11,6 +11,12 @@ use zcash_primitives::{
 
 use super::transactions::WitnessCache;
 
+/// Reads the commitment randomness of a note created before ZIP 212.
+fn read_fr(bytes: &[u8; 32]) -> io::Result<jubjub::Fr> {
+    Option::<jubjub::Fr>::from(jubjub::Fr::from_bytes(bytes))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Invalid note rcm"))
+}
+
 // Reading a note also needs the corresponding address to read from.
 fn read_rseed<R: ReadBytesExt>(mut reader: R) -> io::Result<Rseed> {
     let note_type = reader.read_u8()?;
@@ -19,7 +25,7 @@ fn read_rseed<R: ReadBytesExt>(mut reader: R) -> io::Result<Rseed> {
     reader.read_exact(&mut r_bytes)?;
 
     let r = match note_type {
-        1 => Rseed::BeforeZip212(jubjub::Fr::from_bytes(&r_bytes).unwrap()),
+        1 => Rseed::BeforeZip212(read_fr(&r_bytes)?),
         2 => Rseed::AfterZip212(r_bytes),
         _ => return Err(io::Error::new(io::ErrorKind::InvalidInput, "Bad note type")),
     };
@@ -93,7 +99,7 @@ impl SaplingNoteData {
             let mut r_bytes: [u8; 32] = [0; 32];
             reader.read_exact(&mut r_bytes)?;
 
-            let r = jubjub::Fr::from_bytes(&r_bytes).unwrap();
+            let r = read_fr(&r_bytes)?;
 
             (value, Rseed::BeforeZip212(r))
         } else {
@@ -107,7 +113,12 @@ impl SaplingNoteData {
             .fvk
             .vk
             .to_payment_address(diversifier)
-            .unwrap()
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Invalid sapling note diversifier",
+                )
+            })?
             .create_note(NoteValue::from_raw(value), rseed);
 
         let witnesses_vec = Vector::read(&mut reader, |r| read_incremental_witness(r))?;
@@ -138,11 +149,9 @@ impl SaplingNoteData {
                 None
             };
 
-            if spent.is_some() && spent_at_height.is_some() {
-                Some((spent.unwrap(), spent_at_height.unwrap() as u32))
-            } else {
-                None
-            }
+            spent
+                .zip(spent_at_height)
+                .map(|(txid, height)| (txid, height as u32))
         } else {
             Optional::read(&mut reader, |r| {
                 let mut txid_bytes = [0u8; 32];
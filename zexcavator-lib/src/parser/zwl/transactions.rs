@@ -18,7 +18,8 @@ use zcash_primitives::{
     },
 };
 
-use super::{orchard_data::OrchardNoteData, sapling_data::SaplingNoteData};
+use super::{ZwlWallet, orchard_data::OrchardNoteData, sapling_data::SaplingNoteData};
+use crate::parser::error::ParseError;
 
 pub const MAX_REORG: usize = 100;
 
@@ -169,7 +170,12 @@ impl WalletTx {
             Vector::read(&mut reader, |r| {
                 let mut rho_bytes = [0u8; 32];
                 r.read_exact(&mut rho_bytes)?;
-                Ok(orchard_old::note::Nullifier::from_bytes(&rho_bytes).unwrap())
+                Option::<orchard_old::note::Nullifier>::from(
+                    orchard_old::note::Nullifier::from_bytes(&rho_bytes),
+                )
+                .ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidData, "Invalid orchard nullifier")
+                })
             })?
         };
 
@@ -271,10 +277,14 @@ impl Utxo {
         let version = reader.read_u64::<LittleEndian>()?;
 
         let address_len = reader.read_i32::<LittleEndian>()?;
-        let mut address_bytes = vec![0; address_len as usize];
-        reader.read_exact(&mut address_bytes)?;
-        let address = String::from_utf8(address_bytes).unwrap();
-        assert_eq!(address.chars().take(1).collect::<Vec<char>>()[0], 't');
+        let address = ZwlWallet::read_utf8(&mut reader, address_len.max(0) as u64)?;
+        if !address.starts_with('t') {
+            return Err(ParseError::InvalidKey(format!(
+                "UTXO address {} is not transparent",
+                address
+            ))
+            .into());
+        }
 
         let mut txid_bytes = [0; 32];
         reader.read_exact(&mut txid_bytes)?;
@@ -437,9 +447,7 @@ pub struct OutgoingTxMetadata {
 impl OutgoingTxMetadata {
     pub fn read<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
         let address_len = reader.read_u64::<LittleEndian>()?;
-        let mut address_bytes = vec![0; address_len as usize];
-        reader.read_exact(&mut address_bytes)?;
-        let address = String::from_utf8(address_bytes).unwrap();
+        let address = ZwlWallet::read_utf8(&mut reader, address_len)?;
 
         let value = reader.read_u64::<LittleEndian>()?;
 
@@ -507,7 +515,7 @@ impl WalletTxns {
             let mut txid_bytes = [0u8; 32];
             r.read_exact(&mut txid_bytes)?;
 
-            Ok((TxId::from_bytes(txid_bytes), WalletTx::read(r).unwrap()))
+            Ok((TxId::from_bytes(txid_bytes), WalletTx::read(r)?))
         })?;
 
         let txs = txs_tuples.into_iter().collect::<HashMap<TxId, WalletTx>>();
@@ -521,28 +529,26 @@ impl WalletTxns {
     pub fn read<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
         let version = reader.read_u64::<LittleEndian>()?;
         if version > Self::serialized_version() {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "Can't read wallettxns because of incorrect version",
-            ));
+            return Err(ParseError::UnsupportedVersion {
+                record: "transactions",
+                version,
+            }
+            .into());
         }
 
         let txs_tuples = Vector::read(&mut reader, |r| {
             let mut txid_bytes = [0u8; 32];
             r.read_exact(&mut txid_bytes)?;
 
-            Ok((TxId::from_bytes(txid_bytes), WalletTx::read(r).unwrap()))
+            Ok((TxId::from_bytes(txid_bytes), WalletTx::read(r)?))
         })?;
 
         let current = txs_tuples.into_iter().collect::<HashMap<TxId, WalletTx>>();
         let last_txid = current
             .values()
-            .fold(None, |c: Option<(TxId, BlockHeight)>, w| {
-                if c.is_none() || w.block > c.unwrap().1 {
-                    Some((w.txid, w.block))
-                } else {
-                    c
-                }
+            .fold(None, |c: Option<(TxId, BlockHeight)>, w| match c {
+                Some((_, block)) if w.block <= block => c,
+                _ => Some((w.txid, w.block)),
             })
             .map(|v| v.0);
 
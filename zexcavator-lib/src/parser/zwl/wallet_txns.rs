@@ -8,6 +8,7 @@ use zcash_encoding::Vector;
 use zcash_primitives::{consensus::BlockHeight, transaction::TxId};
 
 use super::transactions::WalletTx;
+use crate::parser::error::ParseError;
 
 pub struct WalletTxns {
     pub current: HashMap<TxId, WalletTx>,
@@ -22,28 +23,26 @@ impl WalletTxns {
     pub fn read<R: Read>(mut reader: R) -> io::Result<Self> {
         let version = reader.read_u64::<LittleEndian>()?;
         if version > Self::serialized_version() {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "Can't read wallettxns because of incorrect version",
-            ));
+            return Err(ParseError::UnsupportedVersion {
+                record: "transactions",
+                version,
+            }
+            .into());
         }
 
         let txs_tuples = Vector::read(&mut reader, |r| {
             let mut txid_bytes = [0u8; 32];
             r.read_exact(&mut txid_bytes)?;
 
-            Ok((TxId::from_bytes(txid_bytes), WalletTx::read(r).unwrap()))
+            Ok((TxId::from_bytes(txid_bytes), WalletTx::read(r)?))
         })?;
 
         let current = txs_tuples.into_iter().collect::<HashMap<TxId, WalletTx>>();
         let last_txid = current
             .values()
-            .fold(None, |c: Option<(TxId, BlockHeight)>, w| {
-                if c.is_none() || w.block > c.unwrap().1 {
-                    Some((w.txid, w.block))
-                } else {
-                    c
-                }
+            .fold(None, |c: Option<(TxId, BlockHeight)>, w| match c {
+                Some((_, block)) if w.block <= block => c,
+                _ => Some((w.txid, w.block)),
             })
             .map(|v| v.0);
 
@@ -4,6 +4,12 @@ use byteorder::{LittleEndian, ReadBytesExt};
 use orchard_old::keys::{FullViewingKey, Scope, SpendingKey};
 use zcash_encoding::{Optional, Vector};
 use zcash_keys::address::UnifiedAddress;
+use zcash_primitives::consensus::NetworkConstants;
+use zcash_primitives::zip32::AccountId;
+
+use super::keys::secretbox_open;
+use crate::parser::error::ParseError;
+use crate::settings::Network;
 
 use orchard_new::Address as NewAddress;
 use orchard_old::Address as OldAddress;
@@ -40,17 +46,23 @@ impl WalletOKey {
 
     pub fn read<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
         let version = reader.read_u8()?;
-        assert!(version <= Self::serialized_version());
+        if version > Self::serialized_version() {
+            return Err(ParseError::UnsupportedVersion {
+                record: "orchard key",
+                version: version.into(),
+            }
+            .into());
+        }
 
         // Read orchard key type
         let keytype = match reader.read_u32::<LittleEndian>()? {
             0 => Ok(WalletOKeyType::HdKey),
             1 => Ok(WalletOKeyType::ImportedSpendingKey),
             2 => Ok(WalletOKeyType::ImportedFullViewKey),
-            n => Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                format!("Invalid okey type {}", n),
-            )),
+            n => Err(io::Error::from(ParseError::InvalidKey(format!(
+                "Unknown okey type {}",
+                n
+            )))),
         }?;
 
         // read if key is locked
@@ -66,15 +78,19 @@ impl WalletOKey {
         let sk = Optional::read(&mut reader, |r| {
             let mut bytes = [0u8; 32];
             r.read_exact(&mut bytes)?;
-            Ok(SpendingKey::from_bytes(bytes).unwrap())
+            Option::<SpendingKey>::from(SpendingKey::from_bytes(bytes)).ok_or_else(|| {
+                io::Error::from(ParseError::InvalidKey(
+                    "Invalid orchard spending key".into(),
+                ))
+            })
         })?;
 
         // Derive unified address (orchard only) from fvk
         let old_address: orchard_old::Address = fvk.address_at(0u64, Scope::External);
 
-        let new_address = NewAddress::from_old(old_address);
-        let unified_address = UnifiedAddress::from_receivers(Some(new_address), None, None)
-            .expect("Failed to construct unified address");
+        let unified_address = NewAddress::from_old(old_address)
+            .and_then(|new_address| UnifiedAddress::from_receivers(Some(new_address), None, None))
+            .ok_or_else(|| ParseError::InvalidKey("Invalid orchard address".into()))?;
 
         // read "possible" encrypted key
         let enc_key = Optional::read(&mut reader, |r| Vector::read(r, |r| r.read_u8()))?;
@@ -95,6 +111,52 @@ impl WalletOKey {
     }
 }
 
+impl WalletOKey {
+    /// Restores the spending key of a locked key. HD keys are derived again from
+    /// `bip39_seed`, imported spending keys are decrypted with `key`.
+    pub fn unlock(
+        &mut self,
+        bip39_seed: &[u8],
+        key: &[u8; 32],
+        network: Network,
+    ) -> io::Result<()> {
+        let invalid = |e: &str| io::Error::new(io::ErrorKind::InvalidData, e.to_string());
+
+        match self.keytype {
+            WalletOKeyType::HdKey => {
+                let hdkey_num = self
+                    .hdkey_num
+                    .ok_or_else(|| invalid("HD key without a key number"))?;
+                let account =
+                    AccountId::try_from(hdkey_num).map_err(|_| invalid("Invalid key number"))?;
+                let sk = SpendingKey::from_zip32_seed(
+                    bip39_seed,
+                    network.network_type().coin_type(),
+                    account.into(),
+                )
+                .map_err(|_| invalid("Invalid zip32 seed"))?;
+                self.sk = Some(sk);
+            }
+            WalletOKeyType::ImportedSpendingKey => {
+                let (Some(enc_key), Some(nonce)) = (&self.enc_key, &self.nonce) else {
+                    return Err(invalid("Imported key is missing its encrypted copy"));
+                };
+                let sk_bytes: [u8; 32] = secretbox_open(key, nonce, enc_key)?
+                    .try_into()
+                    .map_err(|_| invalid("Decrypted key is not 32 bytes"))?;
+                let sk = Option::from(SpendingKey::from_bytes(sk_bytes))
+                    .ok_or_else(|| invalid("Invalid orchard spending key"))?;
+                self.sk = Some(sk);
+            }
+            // Viewing keys are never encrypted
+            WalletOKeyType::ImportedFullViewKey => {}
+        }
+
+        self.locked = false;
+        Ok(())
+    }
+}
+
 #[allow(unreachable_patterns)]
 impl fmt::Display for WalletOKey {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -134,12 +196,12 @@ impl fmt::Display for WalletOKey {
     }
 }
 
-pub trait MyFrom<T> {
-    fn from_old(old: T) -> Self;
+pub trait MyFrom<T>: Sized {
+    fn from_old(old: T) -> Option<Self>;
 }
 
 impl MyFrom<OldAddress> for NewAddress {
-    fn from_old(old: OldAddress) -> Self {
-        Self::from_raw_address_bytes(&old.to_raw_address_bytes()).unwrap()
+    fn from_old(old: OldAddress) -> Option<Self> {
+        Option::<NewAddress>::from(Self::from_raw_address_bytes(&old.to_raw_address_bytes()))
     }
 }
@@ -5,6 +5,15 @@ use std::{
     io::{self, Read},
 };
 use zcash_encoding::{Optional, Vector};
+use zcash_primitives::{
+    legacy::keys::{AccountPrivKey, NonHardenedChildIndex},
+    zip32::AccountId,
+};
+
+use super::keys::secretbox_open;
+use crate::parser::error::ParseError;
+use crate::parser::zwl::ZwlWallet;
+use crate::settings::Network;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum WalletTKeyType {
@@ -34,16 +43,22 @@ impl WalletTKey {
 
     pub fn read<R: Read>(mut reader: R) -> io::Result<Self> {
         let version = reader.read_u8()?;
-        assert!(version <= Self::serialized_version());
+        if version > Self::serialized_version() {
+            return Err(ParseError::UnsupportedVersion {
+                record: "transparent key",
+                version: version.into(),
+            }
+            .into());
+        }
 
         // read type of the key
         let keytype: WalletTKeyType = match reader.read_u32::<LittleEndian>()? {
             0 => Ok(WalletTKeyType::HdKey),
             1 => Ok(WalletTKeyType::ImportedKey),
-            n => Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                format!("Unknown tkey type: {}", n),
-            )),
+            n => Err(io::Error::from(ParseError::InvalidKey(format!(
+                "Unknown tkey type: {}",
+                n
+            )))),
         }?;
 
         // read if address is locked
@@ -53,18 +68,11 @@ impl WalletTKey {
             let mut tpk_bytes = [0u8; 32];
             r.read_exact(&mut tpk_bytes)?;
             SecretKey::from_slice(&tpk_bytes)
-                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+                .map_err(|e| io::Error::from(ParseError::InvalidKey(e.to_string())))
         })?;
 
         // read encoded t address as String
-        // Strings are written as <littleendian> len + bytes
-        let str_len = reader.read_u64::<LittleEndian>()?;
-        let mut str_bytes = vec![0; str_len as usize];
-        reader.read_exact(&mut str_bytes)?;
-
-        // The actual encoded address
-        let address = String::from_utf8(str_bytes)
-            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        let address = ZwlWallet::read_string(&mut reader)?;
 
         // If HD derived, read the key index
         let hdkey_num = Optional::read(&mut reader, |r| r.read_u32::<LittleEndian>())?;
@@ -85,6 +93,60 @@ impl WalletTKey {
             address,
         })
     }
+
+    /// HD key number `hdkey_num`, as stored by wallets up to keys version 20 which
+    /// only kept the raw secret keys and their addresses.
+    pub fn from_raw(key: SecretKey, address: String, hdkey_num: u32) -> Self {
+        Self {
+            keytype: WalletTKeyType::HdKey,
+            locked: false,
+            key: Some(key),
+            address,
+            hdkey_num: Some(hdkey_num),
+            enc_key: None,
+            nonce: None,
+        }
+    }
+}
+
+impl WalletTKey {
+    /// Restores the secret key of a locked key. HD keys are derived again from
+    /// `bip39_seed` at `m/44'/133'/0'/0/n`, imported keys are decrypted with `key`.
+    pub fn unlock(
+        &mut self,
+        bip39_seed: &[u8],
+        key: &[u8; 32],
+        network: Network,
+    ) -> io::Result<()> {
+        let invalid = |e: &str| io::Error::new(io::ErrorKind::InvalidData, e.to_string());
+
+        match self.keytype {
+            WalletTKeyType::HdKey => {
+                let hdkey_num = self
+                    .hdkey_num
+                    .ok_or_else(|| invalid("HD key without a key number"))?;
+                let sk = AccountPrivKey::from_seed(&network.params(), bip39_seed, AccountId::ZERO)
+                    .map_err(|_| invalid("Invalid zip32 seed"))?
+                    .derive_external_secret_key(
+                        NonHardenedChildIndex::from_index(hdkey_num)
+                            .ok_or_else(|| invalid("Invalid key number"))?,
+                    )
+                    .map_err(|e| invalid(&e.to_string()))?;
+                self.key = Some(sk);
+            }
+            WalletTKeyType::ImportedKey => {
+                let (Some(enc_key), Some(nonce)) = (&self.enc_key, &self.nonce) else {
+                    return Err(invalid("Imported key is missing its encrypted copy"));
+                };
+                let sk_bytes = secretbox_open(key, nonce, enc_key)?;
+                self.key =
+                    Some(SecretKey::from_slice(&sk_bytes).map_err(|e| invalid(&e.to_string()))?);
+            }
+        }
+
+        self.locked = false;
+        Ok(())
+    }
 }
 
 impl fmt::Display for WalletTKey {
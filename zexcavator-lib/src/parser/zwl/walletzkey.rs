@@ -5,6 +5,12 @@ use byteorder::{LittleEndian, ReadBytesExt};
 use sapling::PaymentAddress;
 use sapling::zip32::{ExtendedFullViewingKey, ExtendedSpendingKey};
 use zcash_encoding::{Optional, Vector};
+use zcash_primitives::consensus::NetworkConstants;
+use zcash_primitives::zip32::ChildIndex;
+
+use super::keys::secretbox_open;
+use crate::parser::error::ParseError;
+use crate::settings::Network;
 
 #[derive(PartialEq, Debug, Clone)]
 pub enum WalletZKeyType {
@@ -36,17 +42,23 @@ impl WalletZKey {
 
     pub fn read<R: Read>(mut reader: R) -> io::Result<Self> {
         let version = reader.read_u8()?;
-        assert!(version <= Self::serialized_version());
+        if version > Self::serialized_version() {
+            return Err(ParseError::UnsupportedVersion {
+                record: "sapling key",
+                version: version.into(),
+            }
+            .into());
+        }
 
         // read type of the key
         let keytype: WalletZKeyType = match reader.read_u32::<LittleEndian>()? {
             0 => Ok(WalletZKeyType::HdKey),
             1 => Ok(WalletZKeyType::ImportedSpendingKey),
             2 => Ok(WalletZKeyType::ImportedViewKey),
-            n => Err(io::Error::new(
-                ErrorKind::InvalidInput,
-                format!("Unknown zkey type {}", n),
-            )),
+            n => Err(io::Error::from(ParseError::InvalidKey(format!(
+                "Unknown zkey type {}",
+                n
+            )))),
         }?;
 
         // read if address is locked
@@ -81,6 +93,79 @@ impl WalletZKey {
             nonce,
         })
     }
+
+    /// HD key number `hdkey_num`, as stored by wallets up to version 6 which only
+    /// kept the raw spending keys.
+    pub fn new_hdkey(hdkey_num: u32, extsk: ExtendedSpendingKey) -> Self {
+        let extfvk = extsk.to_extended_full_viewing_key();
+        let (_, zaddress) = extfvk.default_address();
+
+        Self {
+            keytype: WalletZKeyType::HdKey,
+            locked: false,
+            extsk: Some(extsk),
+            extfvk,
+            zaddress,
+            hdkey_num: Some(hdkey_num),
+            enc_key: None,
+            nonce: None,
+        }
+    }
+
+    /// Locked HD key number `hdkey_num`, of which only the viewing key was stored.
+    pub fn new_locked_hdkey(hdkey_num: u32, extfvk: ExtendedFullViewingKey) -> Self {
+        let (_, zaddress) = extfvk.default_address();
+
+        Self {
+            keytype: WalletZKeyType::HdKey,
+            locked: true,
+            extsk: None,
+            extfvk,
+            zaddress,
+            hdkey_num: Some(hdkey_num),
+            enc_key: None,
+            nonce: None,
+        }
+    }
+}
+
+impl WalletZKey {
+    /// Restores the spending key of a locked key. HD keys are derived again from
+    /// `bip39_seed`, imported spending keys are decrypted with `key`.
+    pub fn unlock(
+        &mut self,
+        bip39_seed: &[u8],
+        key: &[u8; 32],
+        network: Network,
+    ) -> io::Result<()> {
+        match self.keytype {
+            WalletZKeyType::HdKey => {
+                let hdkey_num = self.hdkey_num.ok_or_else(|| {
+                    io::Error::new(ErrorKind::InvalidData, "HD key without a key number")
+                })?;
+                let extsk = ExtendedSpendingKey::master(bip39_seed)
+                    .derive_child(ChildIndex::hardened(32))
+                    .derive_child(ChildIndex::hardened(network.network_type().coin_type()))
+                    .derive_child(ChildIndex::hardened(hdkey_num));
+                self.extsk = Some(extsk);
+            }
+            WalletZKeyType::ImportedSpendingKey => {
+                let (Some(enc_key), Some(nonce)) = (&self.enc_key, &self.nonce) else {
+                    return Err(io::Error::new(
+                        ErrorKind::InvalidData,
+                        "Imported key is missing its encrypted copy",
+                    ));
+                };
+                let extsk_bytes = secretbox_open(key, nonce, enc_key)?;
+                self.extsk = Some(ExtendedSpendingKey::read(&extsk_bytes[..])?);
+            }
+            // Viewing keys are never encrypted
+            WalletZKeyType::ImportedViewKey => {}
+        }
+
+        self.locked = false;
+        Ok(())
+    }
 }
 
 impl fmt::Display for WalletZKey {
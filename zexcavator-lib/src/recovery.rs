@@ -0,0 +1,12 @@
+//! # Recovery
+//!
+//! Offline tools that help owners get back into wallets they can't fully unlock.
+//! They only ever work on files and phrases supplied by the user, and never touch
+//! the network.
+//!
+//! - [`password`]: dictionary and mask search for the password of an encrypted
+//!   ZecWallet Lite wallet.
+//! - [`mnemonic`]: repair of BIP-39 phrases with missing, swapped or misspelled words.
+
+pub mod mnemonic;
+pub mod password;
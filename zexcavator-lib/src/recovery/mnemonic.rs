@@ -0,0 +1,350 @@
+//! # Mnemonic Repair
+//!
+//! Recovers BIP-39 phrases that were written down or remembered incorrectly. The
+//! damaged phrase is expanded into every phrase it could have been, and only those
+//! passing the BIP-39 checksum are kept. The repairs covered are:
+//!
+//! - **Misspelled words**: words not in the English wordlist are replaced with every
+//!   wordlist entry within a small edit distance.
+//! - **Unknown words**: up to [`MAX_UNKNOWN_WORDS`] positions written as `?` are
+//!   tried with all 2048 words.
+//! - **Missing last word**: a phrase one word short of a valid length gets an
+//!   unknown word appended, e.g. a 23 word phrase missing its 24th word.
+//! - **Swapped words**: every pair of adjacent words is also tried swapped.
+//!
+//! The checksum alone usually leaves several candidates. They can be narrowed down
+//! offline with [`matches_addresses`], which derives addresses from a candidate and
+//! compares them to addresses known to belong to the wallet, for instance the ones
+//! found in a wallet file by [`addresses_from_wallet`].
+
+use std::collections::HashSet;
+use std::io;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use bip0039::{English, Mnemonic};
+use rayon::prelude::*;
+use sapling::zip32::ExtendedSpendingKey;
+use sha2::{Digest, Sha256};
+use zcash_client_backend::encoding::encode_transparent_address;
+use zcash_keys::encoding::encode_payment_address;
+use zcash_primitives::{
+    consensus::NetworkConstants,
+    legacy::keys::{AccountPrivKey, IncomingViewingKey, NonHardenedChildIndex},
+    zip32::{AccountId, ChildIndex},
+};
+
+use crate::parser::WalletParserFactory;
+use crate::settings::Network;
+
+/// Maximum number of `?` positions in a phrase.
+pub const MAX_UNKNOWN_WORDS: usize = 2;
+
+/// Phrase lengths allowed by BIP-39.
+const VALID_LENGTHS: [usize; 5] = [12, 15, 18, 21, 24];
+
+/// Upper bound on the number of phrases checked in one repair.
+const MAX_SEARCH_SPACE: u64 = 1 << 32;
+
+/// Options of a mnemonic repair.
+#[derive(Debug, Clone)]
+pub struct RepairOptions {
+    /// Maximum edit distance between a misspelled word and its replacements.
+    pub max_distance: usize,
+    /// Whether to also try swapping adjacent words.
+    pub try_swaps: bool,
+}
+
+impl Default for RepairOptions {
+    fn default() -> Self {
+        Self {
+            max_distance: 2,
+            try_swaps: true,
+        }
+    }
+}
+
+/// The English BIP-39 wordlist.
+///
+/// Built from `bip0039` by encoding entropy whose first 11 bits are the word index.
+fn wordlist() -> &'static [String] {
+    static WORDLIST: OnceLock<Vec<String>> = OnceLock::new();
+    WORDLIST.get_or_init(|| {
+        (0u16..2048)
+            .map(|i| {
+                let mut entropy = [0u8; 16];
+                entropy[0] = (i >> 3) as u8;
+                entropy[1] = ((i & 0x7) << 5) as u8;
+                let mnemonic =
+                    <Mnemonic<English>>::from_entropy(entropy).expect("valid entropy length");
+                mnemonic
+                    .into_phrase()
+                    .split_whitespace()
+                    .next()
+                    .expect("non empty phrase")
+                    .to_string()
+            })
+            .collect()
+    })
+}
+
+/// Levenshtein distance between two words.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            current[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(current[j] + 1);
+        }
+        prev = current;
+    }
+    prev[b.len()]
+}
+
+/// Wordlist indices that `word` may stand for, closest first.
+fn alternatives(word: &str, max_distance: usize) -> Vec<u16> {
+    let words = wordlist();
+    if let Some(i) = words.iter().position(|w| w == word) {
+        return vec![i as u16];
+    }
+
+    // BIP-39 words are unique in their first four letters
+    let prefix: String = word.chars().take(4).collect();
+    let mut scored: Vec<(usize, u16)> = words
+        .iter()
+        .enumerate()
+        .filter_map(|(i, w)| {
+            let distance = if prefix.len() == 4 && w.starts_with(&prefix) {
+                0
+            } else {
+                edit_distance(word, w)
+            };
+            (distance <= max_distance).then_some((distance, i as u16))
+        })
+        .collect();
+    scored.sort();
+    scored.into_iter().map(|(_, i)| i).collect()
+}
+
+/// Whether the words at `indices` form a phrase with a valid BIP-39 checksum.
+fn checksum_valid(indices: &[u16]) -> bool {
+    let total_bits = indices.len() * 11;
+    let checksum_bits = total_bits / 33;
+    let entropy_bytes = (total_bits - checksum_bits) / 8;
+
+    let mut bytes = vec![0u8; total_bits.div_ceil(8)];
+    for (n, index) in indices.iter().enumerate() {
+        for bit in 0..11 {
+            if index & (1 << (10 - bit)) != 0 {
+                let pos = n * 11 + bit;
+                bytes[pos / 8] |= 1 << (7 - pos % 8);
+            }
+        }
+    }
+
+    let hash = Sha256::digest(&bytes[..entropy_bytes]);
+    let mask = (0xff00u16 >> checksum_bits) as u8;
+    (hash[0] & mask) == (bytes[entropy_bytes] & mask)
+}
+
+/// Finds every phrase `phrase` could have been. Unknown words are written as `?`.
+pub fn repair(phrase: &str, options: &RepairOptions) -> io::Result<Vec<String>> {
+    let invalid_input = |e: String| io::Error::new(io::ErrorKind::InvalidInput, e);
+
+    let mut words: Vec<String> = phrase
+        .split_whitespace()
+        .map(|w| w.to_lowercase())
+        .collect();
+    if VALID_LENGTHS.contains(&(words.len() + 1)) {
+        words.push("?".to_string());
+    }
+    if !VALID_LENGTHS.contains(&words.len()) {
+        return Err(invalid_input(format!(
+            "A phrase has 12, 15, 18, 21 or 24 words, found {}",
+            words.len()
+        )));
+    }
+
+    let unknown = words.iter().filter(|w| *w == "?").count();
+    if unknown > MAX_UNKNOWN_WORDS {
+        return Err(invalid_input(format!(
+            "At most {} words can be unknown, found {}",
+            MAX_UNKNOWN_WORDS, unknown
+        )));
+    }
+
+    let mut positions = Vec::with_capacity(words.len());
+    for (n, word) in words.iter().enumerate() {
+        let candidates = if word == "?" {
+            (0..2048).collect()
+        } else {
+            alternatives(word, options.max_distance)
+        };
+        if candidates.is_empty() {
+            return Err(invalid_input(format!(
+                "No replacement found for word {} ({})",
+                n + 1,
+                word
+            )));
+        }
+        positions.push(candidates);
+    }
+
+    // Phrases in the original order come first, then with each adjacent pair swapped
+    let mut orders = vec![positions.clone()];
+    if options.try_swaps {
+        for i in 0..positions.len() - 1 {
+            let mut swapped = positions.clone();
+            swapped.swap(i, i + 1);
+            orders.push(swapped);
+        }
+    }
+
+    let order_size = |order: &Vec<Vec<u16>>| {
+        order
+            .iter()
+            .try_fold(1u64, |acc, p| acc.checked_mul(p.len() as u64))
+    };
+    orders
+        .iter()
+        .try_fold(0u64, |acc, order| acc.checked_add(order_size(order)?))
+        .filter(|n| *n <= MAX_SEARCH_SPACE)
+        .ok_or_else(|| invalid_input("Too many possible phrases to check".to_string()))?;
+
+    let mut seen = HashSet::new();
+    let mut phrases = vec![];
+    for order in orders {
+        let total = order_size(&order).unwrap_or_default();
+        let mut valid: Vec<(u64, Vec<u16>)> = (0..total)
+            .into_par_iter()
+            .filter_map(|mut index| {
+                let n = index;
+                let mut indices = vec![0u16; order.len()];
+                for (slot, position) in indices.iter_mut().zip(order.iter()).rev() {
+                    *slot = position[(index % position.len() as u64) as usize];
+                    index /= position.len() as u64;
+                }
+                checksum_valid(&indices).then_some((n, indices))
+            })
+            .collect();
+        valid.sort();
+
+        for (_, indices) in valid {
+            if seen.insert(indices.clone()) {
+                let words = wordlist();
+                phrases.push(
+                    indices
+                        .iter()
+                        .map(|i| words[*i as usize].as_str())
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                );
+            }
+        }
+    }
+
+    Ok(phrases)
+}
+
+/// Whether any of the first `depth` ZecWallet Lite style transparent or sapling
+/// addresses derived from `phrase` for `network` is in `addresses`.
+pub fn matches_addresses(phrase: &str, addresses: &[String], depth: u32, network: Network) -> bool {
+    let Ok(mnemonic) = <Mnemonic<English>>::from_str(phrase) else {
+        return false;
+    };
+    let seed = mnemonic.to_seed("");
+    let network_type = network.network_type();
+
+    let master = ExtendedSpendingKey::master(&seed);
+    let transparent = AccountPrivKey::from_seed(&network.params(), &seed, AccountId::ZERO)
+        .ok()
+        .and_then(|key| key.to_account_pubkey().derive_external_ivk().ok());
+
+    (0..depth).any(|n| {
+        let (_, zaddr) = master
+            .derive_child(ChildIndex::hardened(32))
+            .derive_child(ChildIndex::hardened(network_type.coin_type()))
+            .derive_child(ChildIndex::hardened(n))
+            .default_address();
+        if addresses.contains(&encode_payment_address(
+            network_type.hrp_sapling_payment_address(),
+            &zaddr,
+        )) {
+            return true;
+        }
+
+        transparent
+            .as_ref()
+            .and_then(|ivk| {
+                NonHardenedChildIndex::from_index(n).and_then(|i| ivk.derive_address(i).ok())
+            })
+            .is_some_and(|taddr| {
+                addresses.contains(&encode_transparent_address(
+                    &network_type.b58_pubkey_address_prefix(),
+                    &network_type.b58_script_address_prefix(),
+                    &taddr,
+                ))
+            })
+    })
+}
+
+/// Keeps the phrases deriving one of `addresses`. See [`matches_addresses`].
+pub fn filter_by_addresses(
+    phrases: Vec<String>,
+    addresses: &[String],
+    depth: u32,
+    network: Network,
+) -> Vec<String> {
+    phrases
+        .into_par_iter()
+        .filter(|phrase| matches_addresses(phrase, addresses, depth, network))
+        .collect()
+}
+
+/// Network and transparent and sapling addresses of the seed derived keys found in a
+/// wallet file.
+pub fn addresses_from_wallet(filename: &str) -> io::Result<(Network, Vec<String>)> {
+    let wallet = WalletParserFactory::read(filename)?;
+
+    let mut addresses = vec![];
+    for account in wallet.parser.get_wallet_accounts()? {
+        addresses.extend(account.keys.tkeys.into_iter().map(|k| k.address));
+        addresses.extend(account.keys.zkeys.into_iter().map(|k| k.address));
+    }
+
+    Ok((wallet.parser.get_network(), addresses))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn test_wordlist() {
+        assert_eq!(wordlist()[0], "abandon");
+        assert_eq!(wordlist()[2047], "zoo");
+    }
+
+    #[test]
+    fn test_repair_misspelled_and_swapped() {
+        let damaged = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about abandn";
+        let phrases = repair(damaged, &RepairOptions::default()).unwrap();
+        assert!(phrases.contains(&PHRASE.to_string()));
+    }
+
+    #[test]
+    fn test_repair_missing_last_word() {
+        let damaged = PHRASE.rsplit_once(' ').unwrap().0;
+        let phrases = repair(damaged, &RepairOptions::default()).unwrap();
+        assert!(phrases.contains(&PHRASE.to_string()));
+        assert!(
+            phrases
+                .iter()
+                .all(|p| Mnemonic::<English>::from_str(p).is_ok())
+        );
+    }
+}
@@ -0,0 +1,577 @@
+//! # Password Recovery
+//!
+//! Searches for the password of an encrypted ZecWallet Lite wallet. Each candidate
+//! is tested against the encrypted seed stored in the wallet file, the same check
+//! ZecWallet Lite does when unlocking, so the search runs entirely offline.
+//!
+//! ## Search space
+//! Candidates are described by a [`Mask`]. Each mask position is either a literal
+//! character, a character class or a word from the wordlist:
+//!
+//! | Token | Matches                                          |
+//! |-------|--------------------------------------------------|
+//! | `?w`  | a wordlist entry, after applying mutation rules  |
+//! | `?d`  | a digit                                          |
+//! | `?l`  | a lowercase letter                               |
+//! | `?u`  | an uppercase letter                              |
+//! | `?s`  | a printable ASCII symbol                         |
+//! | `?a`  | any printable ASCII character                    |
+//! | `??`  | a literal `?`                                    |
+//!
+//! The default mask is `?w`, i.e. a plain dictionary attack. `?w19?d?d` tries every
+//! word followed by a year between 1900 and 1999.
+//!
+//! ## Checkpoints
+//! Candidates are numbered, so progress is a single index. When a checkpoint file
+//! is given, the index is saved after every batch and the search resumes from it.
+//! The checkpoint also stores a fingerprint of the wallet and search space, so it
+//! is never applied to a different search.
+
+use std::collections::HashSet;
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+
+use crate::parser::WalletParser;
+use crate::parser::zwl::ZwlWallet;
+use crate::parser::zwl::keys::{double_sha256, secretbox_open};
+
+/// Number of candidates tested between two checkpoints.
+const BATCH_SIZE: u64 = 1 << 20;
+
+/// Transformation applied to every wordlist entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MutationRule {
+    AsIs,
+    Lowercase,
+    Uppercase,
+    Capitalize,
+    Reverse,
+    Leet,
+}
+
+impl MutationRule {
+    pub fn apply(&self, word: &str) -> String {
+        match self {
+            Self::AsIs => word.to_string(),
+            Self::Lowercase => word.to_lowercase(),
+            Self::Uppercase => word.to_uppercase(),
+            Self::Capitalize => {
+                let mut chars = word.chars();
+                match chars.next() {
+                    Some(first) => first.to_uppercase().chain(chars).collect(),
+                    None => String::new(),
+                }
+            }
+            Self::Reverse => word.chars().rev().collect(),
+            Self::Leet => word
+                .chars()
+                .map(|c| match c.to_ascii_lowercase() {
+                    'a' => '4',
+                    'e' => '3',
+                    'i' => '1',
+                    'o' => '0',
+                    's' => '5',
+                    't' => '7',
+                    _ => c,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl FromStr for MutationRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "none" | "as-is" => Ok(Self::AsIs),
+            "lower" | "lowercase" => Ok(Self::Lowercase),
+            "upper" | "uppercase" => Ok(Self::Uppercase),
+            "capitalize" => Ok(Self::Capitalize),
+            "reverse" => Ok(Self::Reverse),
+            "leet" => Ok(Self::Leet),
+            other => Err(format!("Unknown mutation rule: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum MaskToken {
+    Word,
+    Charset(Vec<char>),
+    Literal(char),
+}
+
+/// Shape of the candidate passwords. See the module documentation for the syntax.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mask {
+    tokens: Vec<MaskToken>,
+}
+
+impl Mask {
+    pub fn uses_wordlist(&self) -> bool {
+        self.tokens.contains(&MaskToken::Word)
+    }
+}
+
+impl Default for Mask {
+    fn default() -> Self {
+        Self {
+            tokens: vec![MaskToken::Word],
+        }
+    }
+}
+
+impl FromStr for Mask {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let printable = || (' '..='~').collect::<Vec<_>>();
+
+        let mut tokens = vec![];
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            if c != '?' {
+                tokens.push(MaskToken::Literal(c));
+                continue;
+            }
+
+            let token = match chars.next() {
+                Some('w') => MaskToken::Word,
+                Some('d') => MaskToken::Charset(('0'..='9').collect()),
+                Some('l') => MaskToken::Charset(('a'..='z').collect()),
+                Some('u') => MaskToken::Charset(('A'..='Z').collect()),
+                Some('s') => MaskToken::Charset(
+                    printable()
+                        .into_iter()
+                        .filter(|c| !c.is_ascii_alphanumeric())
+                        .collect(),
+                ),
+                Some('a') => MaskToken::Charset(printable()),
+                Some('?') => MaskToken::Literal('?'),
+                Some(other) => return Err(format!("Unknown mask token: ?{}", other)),
+                None => return Err("Mask ends with an incomplete token".to_string()),
+            };
+            tokens.push(token);
+        }
+
+        if tokens.is_empty() {
+            return Err("Mask is empty".to_string());
+        }
+
+        Ok(Self { tokens })
+    }
+}
+
+/// Outcome of a password search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RecoveryResult {
+    Found(String),
+    Exhausted,
+    Stopped,
+}
+
+/// Progress saved between runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Checkpoint {
+    fingerprint: String,
+    next: u128,
+}
+
+impl Checkpoint {
+    fn load(path: &Path) -> io::Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Malformed checkpoint file");
+        let contents = fs::read_to_string(path)?;
+        let mut fingerprint = None;
+        let mut next = None;
+        for line in contents.lines() {
+            match line.split_once(' ') {
+                Some(("fingerprint", value)) => fingerprint = Some(value.to_string()),
+                Some(("next", value)) => next = Some(value.parse().map_err(|_| invalid())?),
+                _ => return Err(invalid()),
+            }
+        }
+
+        Ok(Some(Self {
+            fingerprint: fingerprint.ok_or_else(invalid)?,
+            next: next.ok_or_else(invalid)?,
+        }))
+    }
+
+    fn save(&self, path: &Path) -> io::Result<()> {
+        // Write to a temporary file first, so an interrupted write never loses progress
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, self.to_string())?;
+        fs::rename(tmp, path)
+    }
+}
+
+impl Display for Checkpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "fingerprint {}", self.fingerprint)?;
+        writeln!(f, "next {}", self.next)
+    }
+}
+
+/// Password search against the encrypted seed of a ZecWallet Lite wallet.
+#[derive(Debug, Clone)]
+pub struct PasswordRecovery {
+    enc_seed: [u8; 48],
+    nonce: Vec<u8>,
+    // Alternatives for every mask position
+    positions: Vec<Vec<String>>,
+    total: u128,
+    checkpoint: Option<PathBuf>,
+    threads: Option<usize>,
+    batch_size: u64,
+}
+
+impl PasswordRecovery {
+    /// Prepares a search against the wallet at `wallet_path`. Every wordlist entry is
+    /// expanded with each of `rules`; with no rules, words are used as they are.
+    pub fn new(
+        wallet_path: &Path,
+        wordlist: &[String],
+        rules: &[MutationRule],
+        mask: &Mask,
+    ) -> io::Result<Self> {
+        let invalid_input = |e: &str| io::Error::new(io::ErrorKind::InvalidInput, e.to_string());
+
+        let wallet = ZwlWallet::read(
+            wallet_path
+                .to_str()
+                .ok_or_else(|| invalid_input("Invalid wallet path"))?,
+        )?;
+        if !wallet.keys.encrypted {
+            return Err(invalid_input("Wallet is not encrypted"));
+        }
+
+        Self::from_encrypted_seed(
+            wallet.keys.enc_seed,
+            wallet.keys.nonce,
+            wordlist,
+            rules,
+            mask,
+        )
+    }
+
+    fn from_encrypted_seed(
+        enc_seed: [u8; 48],
+        nonce: Vec<u8>,
+        wordlist: &[String],
+        rules: &[MutationRule],
+        mask: &Mask,
+    ) -> io::Result<Self> {
+        let invalid_input = |e: &str| io::Error::new(io::ErrorKind::InvalidInput, e.to_string());
+
+        let words = Self::mutate(wordlist, rules);
+        if mask.uses_wordlist() && words.is_empty() {
+            return Err(invalid_input("Mask uses ?w but the wordlist is empty"));
+        }
+
+        let positions: Vec<Vec<String>> = mask
+            .tokens
+            .iter()
+            .map(|token| match token {
+                MaskToken::Word => words.clone(),
+                MaskToken::Charset(chars) => chars.iter().map(|c| c.to_string()).collect(),
+                MaskToken::Literal(c) => vec![c.to_string()],
+            })
+            .collect();
+
+        let total = positions
+            .iter()
+            .try_fold(1u128, |acc, p| acc.checked_mul(p.len() as u128))
+            .ok_or_else(|| invalid_input("Search space is too large"))?;
+
+        Ok(Self {
+            enc_seed,
+            nonce,
+            positions,
+            total,
+            checkpoint: None,
+            threads: None,
+            batch_size: BATCH_SIZE,
+        })
+    }
+
+    /// Saves progress to `path`, and resumes from it if it already exists.
+    pub fn with_checkpoint(mut self, path: PathBuf) -> Self {
+        self.checkpoint = Some(path);
+        self
+    }
+
+    /// Limits the number of worker threads. Defaults to one per CPU core.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    /// Tests `batch_size` candidates between two checkpoints instead of [`BATCH_SIZE`].
+    #[cfg(test)]
+    fn with_batch_size(mut self, batch_size: u64) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    /// Applies every rule to every word, dropping duplicates.
+    fn mutate(wordlist: &[String], rules: &[MutationRule]) -> Vec<String> {
+        let rules = if rules.is_empty() {
+            &[MutationRule::AsIs][..]
+        } else {
+            rules
+        };
+
+        let mut seen = HashSet::new();
+        wordlist
+            .iter()
+            .flat_map(|w| rules.iter().map(move |r| r.apply(w)))
+            .filter(|w| seen.insert(w.clone()))
+            .collect()
+    }
+
+    /// Number of candidates in the search space.
+    pub fn total(&self) -> u128 {
+        self.total
+    }
+
+    /// Candidate number `index`. The last mask position varies fastest.
+    pub fn candidate(&self, mut index: u128) -> String {
+        let mut parts = Vec::with_capacity(self.positions.len());
+        for position in self.positions.iter().rev() {
+            let len = position.len() as u128;
+            parts.push(position[(index % len) as usize].as_str());
+            index /= len;
+        }
+        parts.into_iter().rev().collect()
+    }
+
+    /// Whether `password` decrypts the wallet seed.
+    pub fn test(&self, password: &str) -> bool {
+        let key = double_sha256(password.as_bytes());
+        secretbox_open(&key, &self.nonce, &self.enc_seed).is_ok()
+    }
+
+    /// Identifies the wallet and search space a checkpoint belongs to.
+    fn fingerprint(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.enc_seed);
+        hasher.update(&self.nonce);
+        for position in &self.positions {
+            hasher.update((position.len() as u64).to_le_bytes());
+            for alternative in position {
+                hasher.update((alternative.len() as u64).to_le_bytes());
+                hasher.update(alternative.as_bytes());
+            }
+        }
+        hex::encode(hasher.finalize())
+    }
+
+    /// Runs the search until the password is found, the space is exhausted or `stop`
+    /// is set. `on_progress` is called after every batch with the index of the next
+    /// candidate and the total number of candidates.
+    pub fn run<F>(&self, stop: &AtomicBool, on_progress: F) -> io::Result<RecoveryResult>
+    where
+        F: Fn(u128, u128),
+    {
+        let fingerprint = self.fingerprint();
+        let checkpoint = match &self.checkpoint {
+            Some(path) => Checkpoint::load(path)?,
+            None => None,
+        };
+        let mut next = match checkpoint {
+            Some(checkpoint) if checkpoint.fingerprint != fingerprint => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Checkpoint belongs to a different wallet or search",
+                ));
+            }
+            Some(checkpoint) => checkpoint.next,
+            None => 0,
+        };
+
+        let mut builder = rayon::ThreadPoolBuilder::new();
+        if let Some(threads) = self.threads {
+            builder = builder.num_threads(threads);
+        }
+        let pool = builder
+            .build()
+            .map_err(|e| io::Error::other(e.to_string()))?;
+
+        while next < self.total {
+            if stop.load(Ordering::Relaxed) {
+                return Ok(RecoveryResult::Stopped);
+            }
+
+            let batch = (self.total - next).min(self.batch_size as u128) as u64;
+            let found = pool.install(|| {
+                (0..batch)
+                    .into_par_iter()
+                    .map(|offset| self.candidate(next + offset as u128))
+                    .find_any(|candidate| self.test(candidate))
+            });
+            if let Some(password) = found {
+                return Ok(RecoveryResult::Found(password));
+            }
+
+            next += batch as u128;
+            if let Some(path) = &self.checkpoint {
+                Checkpoint {
+                    fingerprint: fingerprint.clone(),
+                    next,
+                }
+                .save(path)?;
+            }
+            on_progress(next, self.total);
+        }
+
+        Ok(RecoveryResult::Exhausted)
+    }
+}
+
+/// Reads a wordlist, one word per line. Empty lines are skipped.
+pub fn read_wordlist(path: &Path) -> io::Result<Vec<String>> {
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .map(|l| l.trim_end_matches('\r'))
+        .filter(|l| !l.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crypto_secretbox::aead::{Aead, KeyInit};
+    use crypto_secretbox::{Key, Nonce, XSalsa20Poly1305};
+
+    use super::*;
+
+    fn encrypted_seed(password: &str) -> ([u8; 48], Vec<u8>) {
+        let key = double_sha256(password.as_bytes());
+        let nonce = [7u8; 24];
+        let cipher = XSalsa20Poly1305::new(&Key::from(key))
+            .encrypt(&Nonce::from(nonce), &[42u8; 32][..])
+            .unwrap();
+        (cipher.try_into().unwrap(), nonce.to_vec())
+    }
+
+    #[test]
+    fn test_mask_candidates() {
+        let (enc_seed, nonce) = encrypted_seed("unused");
+        let wordlist = vec!["zcash".to_string()];
+        let rules = [MutationRule::AsIs, MutationRule::Capitalize];
+        let mask: Mask = "?w-?d".parse().unwrap();
+
+        let recovery =
+            PasswordRecovery::from_encrypted_seed(enc_seed, nonce, &wordlist, &rules, &mask)
+                .unwrap();
+
+        assert_eq!(recovery.total(), 20);
+        assert_eq!(recovery.candidate(0), "zcash-0");
+        assert_eq!(recovery.candidate(19), "Zcash-9");
+    }
+
+    #[test]
+    fn test_find_password() {
+        let (enc_seed, nonce) = encrypted_seed("Zcash2016!");
+        let wordlist = vec!["bitcoin".to_string(), "zcash".to_string()];
+        let rules = [MutationRule::AsIs, MutationRule::Capitalize];
+        let mask: Mask = "?w20?d?d?s".parse().unwrap();
+
+        let recovery =
+            PasswordRecovery::from_encrypted_seed(enc_seed, nonce, &wordlist, &rules, &mask)
+                .unwrap();
+
+        let result = recovery.run(&AtomicBool::new(false), |_, _| {}).unwrap();
+        assert_eq!(result, RecoveryResult::Found("Zcash2016!".to_string()));
+    }
+
+    fn checkpoint_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "zexcavator-{}-{}.checkpoint",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_resume_from_checkpoint() {
+        let (enc_seed, nonce) = encrypted_seed("zcash-7");
+        let wordlist = vec!["bitcoin".to_string(), "zcash".to_string()];
+        let mask: Mask = "?w-?d".parse().unwrap();
+        let path = checkpoint_path("resume");
+        let recovery =
+            PasswordRecovery::from_encrypted_seed(enc_seed, nonce, &wordlist, &[], &mask)
+                .unwrap()
+                .with_checkpoint(path.clone())
+                .with_batch_size(4);
+
+        // Stopped after the first batch, which is saved
+        let stop = AtomicBool::new(false);
+        let result = recovery
+            .run(&stop, |_, _| stop.store(true, Ordering::Relaxed))
+            .unwrap();
+        assert_eq!(result, RecoveryResult::Stopped);
+        assert_eq!(Checkpoint::load(&path).unwrap().unwrap().next, 4);
+
+        // The search resumes from the second batch and finds candidate 17
+        let progress = std::sync::Mutex::new(vec![]);
+        let result = recovery
+            .run(&AtomicBool::new(false), |next, _| {
+                progress.lock().unwrap().push(next)
+            })
+            .unwrap();
+        assert_eq!(result, RecoveryResult::Found("zcash-7".to_string()));
+        assert_eq!(*progress.lock().unwrap(), [8, 12, 16]);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_checkpoint_fingerprint_mismatch() {
+        let (enc_seed, nonce) = encrypted_seed("unused");
+        let wordlist = vec!["zcash".to_string()];
+        let path = checkpoint_path("mismatch");
+        let recovery = |enc_seed: [u8; 48], mask: &str| {
+            PasswordRecovery::from_encrypted_seed(
+                enc_seed,
+                nonce.clone(),
+                &wordlist,
+                &[],
+                &mask.parse().unwrap(),
+            )
+            .unwrap()
+            .with_checkpoint(path.clone())
+            .with_batch_size(4)
+        };
+
+        let result = recovery(enc_seed, "?w?d").run(&AtomicBool::new(false), |_, _| {});
+        assert_eq!(result.unwrap(), RecoveryResult::Exhausted);
+        let saved = fs::read_to_string(&path).unwrap();
+
+        // Neither another search space nor another wallet picks up the checkpoint
+        let (other_seed, _) = encrypted_seed("other");
+        for (enc_seed, mask) in [(enc_seed, "?w?d?d"), (other_seed, "?w?d")] {
+            let err = recovery(enc_seed, mask)
+                .run(&AtomicBool::new(false), |_, _| {})
+                .unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+            assert_eq!(fs::read_to_string(&path).unwrap(), saved);
+        }
+
+        fs::remove_file(&path).unwrap();
+    }
+}
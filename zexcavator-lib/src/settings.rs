@@ -0,0 +1,304 @@
+//! # Settings
+//!
+//! Network and lightwalletd servers used to sync recovered wallets. Settings are
+//! stored as TOML in `<config_dir>/zexcavator/settings.toml`, shared by the TUI and
+//! the CLI:
+//!
+//! ```toml
+//! network = "mainnet"
+//! server = "https://na.zec.rocks:443"
+//! fallback_servers = ["https://eu.zec.rocks:443", "https://zec.rocks:443"]
+//! max_sync_retries = 3
+//! ```
+//!
+//! When syncing fails `max_sync_retries` times in a row on a server, the next
+//! server of `fallback_servers` is tried, in order. The sync fails once the last
+//! server has failed as many times.
+//!
+//! Missing fields take the default of the configured network, see
+//! [`SyncSettings::for_network`]: a file holding only `network = "testnet"` syncs
+//! against the testnet server, without fallback servers.
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::Context;
+use http::Uri;
+use serde::{Deserialize, Serialize};
+use zcash_primitives::consensus::{self, NetworkType};
+use zingolib::config::{ChainType, RegtestNetwork};
+
+/// Server used when none is configured for mainnet.
+pub const DEFAULT_MAINNET_SERVER: &str = "https://na.zec.rocks:443";
+/// Servers tried after [`DEFAULT_MAINNET_SERVER`] when none are configured.
+pub const DEFAULT_MAINNET_FALLBACK_SERVERS: [&str; 2] =
+    ["https://eu.zec.rocks:443", "https://zec.rocks:443"];
+/// Server used when none is configured for testnet.
+pub const DEFAULT_TESTNET_SERVER: &str = "https://testnet.zec.rocks:443";
+/// Server used when none is configured for regtest.
+pub const DEFAULT_REGTEST_SERVER: &str = "http://127.0.0.1:9067";
+
+/// Consecutive sync failures on a server before moving to the next one.
+const DEFAULT_MAX_SYNC_RETRIES: u32 = 3;
+
+/// Zcash network a wallet belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    #[default]
+    Mainnet,
+    Testnet,
+    Regtest,
+}
+
+impl Network {
+    pub fn all() -> [Self; 3] {
+        [Self::Mainnet, Self::Testnet, Self::Regtest]
+    }
+
+    /// `zingolib` chain type of the network.
+    pub fn chain_type(&self) -> ChainType {
+        match self {
+            Self::Mainnet => ChainType::Mainnet,
+            Self::Testnet => ChainType::Testnet,
+            Self::Regtest => ChainType::Regtest(RegtestNetwork::all_upgrades_active()),
+        }
+    }
+
+    /// Network of a `zingolib` chain type.
+    pub fn from_chain_type(chain: &ChainType) -> Self {
+        match chain {
+            ChainType::Mainnet => Self::Mainnet,
+            ChainType::Testnet => Self::Testnet,
+            ChainType::Regtest(_) => Self::Regtest,
+        }
+    }
+
+    /// Network of an address or key encoding.
+    pub fn from_network_type(network_type: NetworkType) -> Self {
+        match network_type {
+            NetworkType::Main => Self::Mainnet,
+            NetworkType::Test => Self::Testnet,
+            NetworkType::Regtest => Self::Regtest,
+        }
+    }
+
+    /// Network type, which selects the address and key encodings.
+    pub fn network_type(&self) -> NetworkType {
+        match self {
+            Self::Mainnet => NetworkType::Main,
+            Self::Testnet => NetworkType::Test,
+            Self::Regtest => NetworkType::Regtest,
+        }
+    }
+
+    /// Consensus parameters used to derive keys. Regtest shares the testnet coin type.
+    pub fn params(&self) -> consensus::Network {
+        match self {
+            Self::Mainnet => consensus::Network::MainNetwork,
+            Self::Testnet | Self::Regtest => consensus::Network::TestNetwork,
+        }
+    }
+
+    /// Default server of the network.
+    pub fn default_server(&self) -> &'static str {
+        match self {
+            Self::Mainnet => DEFAULT_MAINNET_SERVER,
+            Self::Testnet => DEFAULT_TESTNET_SERVER,
+            Self::Regtest => DEFAULT_REGTEST_SERVER,
+        }
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Mainnet => write!(f, "mainnet"),
+            Self::Testnet => write!(f, "testnet"),
+            Self::Regtest => write!(f, "regtest"),
+        }
+    }
+}
+
+impl FromStr for Network {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "mainnet" | "main" => Ok(Self::Mainnet),
+            "testnet" | "test" => Ok(Self::Testnet),
+            "regtest" => Ok(Self::Regtest),
+            _ => Err(format!(
+                "Unknown network {}, expected mainnet, testnet or regtest",
+                s
+            )),
+        }
+    }
+}
+
+/// Where and how recovered wallets are synced.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "SettingsFile")]
+pub struct SyncSettings {
+    pub network: Network,
+    /// Lightwalletd server tried first.
+    pub server: String,
+    /// Servers tried in order when syncing keeps failing.
+    pub fallback_servers: Vec<String>,
+    /// Consecutive sync failures on a server before moving to the next one.
+    pub max_sync_retries: u32,
+}
+
+/// Settings as read from the settings file, where every field may be missing.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SettingsFile {
+    #[serde(default)]
+    network: Network,
+    server: Option<String>,
+    fallback_servers: Option<Vec<String>>,
+    max_sync_retries: Option<u32>,
+}
+
+impl From<SettingsFile> for SyncSettings {
+    fn from(file: SettingsFile) -> Self {
+        let defaults = Self::for_network(file.network);
+        Self {
+            network: file.network,
+            server: file.server.unwrap_or(defaults.server),
+            fallback_servers: file.fallback_servers.unwrap_or(defaults.fallback_servers),
+            max_sync_retries: file.max_sync_retries.unwrap_or(defaults.max_sync_retries),
+        }
+    }
+}
+
+impl Default for SyncSettings {
+    fn default() -> Self {
+        Self {
+            network: Network::Mainnet,
+            server: DEFAULT_MAINNET_SERVER.to_string(),
+            fallback_servers: DEFAULT_MAINNET_FALLBACK_SERVERS
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            max_sync_retries: DEFAULT_MAX_SYNC_RETRIES,
+        }
+    }
+}
+
+impl SyncSettings {
+    /// Default settings of `network`, without fallback servers outside mainnet.
+    pub fn for_network(network: Network) -> Self {
+        match network {
+            Network::Mainnet => Self::default(),
+            _ => Self {
+                network,
+                server: network.default_server().to_string(),
+                fallback_servers: vec![],
+                ..Self::default()
+            },
+        }
+    }
+
+    /// The configured server followed by the fallback servers, skipping duplicates.
+    pub fn servers(&self) -> Vec<Uri> {
+        let mut servers: Vec<Uri> = vec![];
+        for server in std::iter::once(&self.server).chain(&self.fallback_servers) {
+            match server.parse::<Uri>() {
+                Ok(uri) if !servers.contains(&uri) => servers.push(uri),
+                _ => (),
+            }
+        }
+        servers
+    }
+
+    /// Checks that every server is a valid URI.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for server in std::iter::once(&self.server).chain(&self.fallback_servers) {
+            server
+                .parse::<Uri>()
+                .with_context(|| format!("invalid server {}", server))?;
+        }
+        Ok(())
+    }
+
+    /// Location of the settings file.
+    pub fn default_path() -> anyhow::Result<PathBuf> {
+        let mut path = dirs::config_dir().context("could not locate config directory")?;
+        path.push("zexcavator");
+        path.push("settings.toml");
+        Ok(path)
+    }
+
+    /// Reads the settings file, falling back to the defaults when it doesn't exist.
+    pub fn load() -> anyhow::Result<Self> {
+        let path = Self::default_path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        Self::load_from(&path)
+    }
+
+    pub fn load_from(path: &Path) -> anyhow::Result<Self> {
+        let content =
+            fs::read_to_string(path).with_context(|| format!("failed to read {:?}", path))?;
+        let settings: Self =
+            toml::from_str(&content).with_context(|| format!("failed to parse {:?}", path))?;
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// Writes the settings file, creating its directory if needed.
+    pub fn save(&self) -> anyhow::Result<PathBuf> {
+        let path = Self::default_path()?;
+        self.save_to(&path)?;
+        Ok(path)
+    }
+
+    pub fn save_to(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create directory {:?}", dir))?;
+        }
+        let content = toml::to_string(self).context("failed to serialize settings")?;
+        fs::write(path, content).with_context(|| format!("failed to write {:?}", path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_roundtrip() {
+        let settings = SyncSettings {
+            network: Network::Testnet,
+            server: "https://lwd.example.com:9067".to_string(),
+            fallback_servers: vec![DEFAULT_TESTNET_SERVER.to_string()],
+            max_sync_retries: 5,
+        };
+
+        let parsed: SyncSettings = toml::from_str(&toml::to_string(&settings).unwrap()).unwrap();
+        assert_eq!(parsed, settings);
+        assert_eq!(parsed.servers().len(), 2);
+
+        // Missing fields take the default of the configured network
+        let parsed: SyncSettings = toml::from_str("network = \"testnet\"").unwrap();
+        assert_eq!(parsed, SyncSettings::for_network(Network::Testnet));
+        assert_eq!(parsed.server, DEFAULT_TESTNET_SERVER);
+        assert!(parsed.fallback_servers.is_empty());
+        assert_eq!(parsed.max_sync_retries, DEFAULT_MAX_SYNC_RETRIES);
+
+        let parsed: SyncSettings =
+            toml::from_str("network = \"regtest\"\nmax_sync_retries = 1").unwrap();
+        assert_eq!(parsed.server, DEFAULT_REGTEST_SERVER);
+        assert_eq!(parsed.max_sync_retries, 1);
+
+        let parsed: SyncSettings = toml::from_str("").unwrap();
+        assert_eq!(parsed, SyncSettings::default());
+
+        assert!(toml::from_str::<SyncSettings>("servers = []").is_err());
+    }
+}
@@ -0,0 +1,344 @@
+//! # Sweep
+//!
+//! Moves every recovered fund to a single address. Each synced HD account is swept
+//! in up to two transactions:
+//!
+//! - a **shield** transaction, moving the transparent balance of the account into
+//!   its own shielded pool, since `zingolib` only sends from shielded funds.
+//! - a **send all** transaction, spending the spendable sapling and orchard notes of
+//!   the account to the destination address.
+//!
+//! Funds shielded by a sweep only become spendable once the shielding transaction is
+//! confirmed. The accounts that shield funds are listed by [`shielding_accounts`], and
+//! the funds they shielded are sent by [`send_shielded`] in a second pass, after the
+//! shielding transactions are confirmed.
+//!
+//! Transactions are sent one step at a time. When a step fails, the transactions
+//! already sent are still returned in the [`SweepResult`], next to the failure.
+//!
+//! Keys imported into a wallet are synced from their viewing keys only, so they can't
+//! be swept from here.
+
+use std::collections::BTreeSet;
+
+use zcash_address::ZcashAddress;
+use zcash_client_backend::proposal::Proposal;
+use zingolib::lightclient::LightClient;
+
+use crate::sync::account_balances;
+
+pub use zcash_primitives::transaction::TxId;
+
+/// Transaction built by a sweep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SweepKind {
+    /// Moves the transparent balance of an account to its shielded pool.
+    Shield,
+    /// Sends the shielded balance of an account to the destination address.
+    SendAll,
+}
+
+/// Preview of one transaction of a sweep.
+#[derive(Debug, Clone)]
+pub struct SweepStep {
+    /// HD index of the swept account.
+    pub account: u32,
+    pub kind: SweepKind,
+    /// Zatoshis moved, excluding the fee.
+    pub amount: u64,
+    /// ZIP-317 fee in zatoshis.
+    pub fee: u64,
+    /// Pools the transaction spends from or sends to.
+    pub pools: Vec<String>,
+}
+
+/// Step of a sweep that couldn't be sent.
+#[derive(Debug)]
+pub struct SweepFailure {
+    pub account: u32,
+    pub kind: SweepKind,
+    pub error: anyhow::Error,
+}
+
+/// Outcome of sending the steps of a sweep. Sending stops at the first step that
+/// fails, the transactions sent before it are broadcast and kept here.
+#[derive(Debug, Default)]
+pub struct SweepResult {
+    /// Steps sent, as proposed right before sending.
+    pub sent: Vec<SweepStep>,
+    /// Transactions broadcast, in the order they were sent.
+    pub txids: Vec<TxId>,
+    pub failed: Option<SweepFailure>,
+}
+
+impl SweepResult {
+    /// Sends `steps` in order, stopping at the first failure.
+    async fn send(
+        lc: &mut LightClient,
+        address: &ZcashAddress,
+        steps: impl IntoIterator<Item = (u32, SweepKind)>,
+    ) -> Self {
+        let mut result = Self::default();
+        for (account, kind) in steps {
+            match send_step(lc, address, account, kind).await {
+                Ok((step, txids)) => {
+                    result.sent.push(step);
+                    result.txids.extend(txids);
+                }
+                Err(error) => {
+                    result.failed = Some(SweepFailure {
+                        account,
+                        kind,
+                        error,
+                    });
+                    break;
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Confirmation status of a transaction sent by a sweep.
+#[derive(Debug, Clone)]
+pub struct SweepTransaction {
+    pub txid: String,
+    pub status: String,
+    pub confirmed: bool,
+}
+
+fn parse_address(address: &str) -> anyhow::Result<ZcashAddress> {
+    ZcashAddress::try_from_encoded(address.trim())
+        .map_err(|e| anyhow::anyhow!("invalid address {}: {}", address, e))
+}
+
+fn account_id(account: u32) -> anyhow::Result<zip32::AccountId> {
+    zip32::AccountId::try_from(account)
+        .map_err(|_| anyhow::anyhow!("invalid account id {}", account))
+}
+
+/// Summarizes a single step proposal into a [`SweepStep`].
+fn summarize<FeeRuleT, NoteRef>(
+    account: u32,
+    kind: SweepKind,
+    proposal: &Proposal<FeeRuleT, NoteRef>,
+) -> anyhow::Result<SweepStep> {
+    let mut amount = 0;
+    let mut fee = 0;
+    let mut pools = BTreeSet::new();
+
+    for step in proposal.steps().iter() {
+        fee += step.balance().fee_required().into_u64();
+
+        if !step.transparent_inputs().is_empty() {
+            pools.insert("Transparent".to_string());
+        }
+        if let Some(inputs) = step.shielded_inputs() {
+            for note in inputs.notes() {
+                pools.insert(format!("{:?}", note.note().protocol()));
+            }
+        }
+        for pool in step.payment_pools().values() {
+            pools.insert(pool.to_string());
+        }
+        for change in step.balance().proposed_change() {
+            pools.insert(change.output_pool().to_string());
+        }
+
+        amount += match kind {
+            SweepKind::Shield => step
+                .transparent_inputs()
+                .iter()
+                .map(|output| output.value().into_u64())
+                .sum::<u64>()
+                .saturating_sub(step.balance().fee_required().into_u64()),
+            SweepKind::SendAll => step
+                .transaction_request()
+                .total()
+                .map_err(|e| anyhow::anyhow!("invalid proposal amount: {:?}", e))?
+                .into_u64(),
+        };
+    }
+
+    Ok(SweepStep {
+        account,
+        kind,
+        amount,
+        fee,
+        pools: pools.into_iter().collect(),
+    })
+}
+
+/// Proposes the transaction of one step, storing it in the client so it can be sent
+/// with [`LightClient::send_stored_proposal`].
+async fn propose_step(
+    lc: &mut LightClient,
+    address: &ZcashAddress,
+    account: u32,
+    kind: SweepKind,
+) -> anyhow::Result<SweepStep> {
+    match kind {
+        SweepKind::Shield => {
+            let proposal = lc
+                .propose_shield(account_id(account)?)
+                .await
+                .map_err(|e| anyhow::anyhow!("failed to propose shielding: {}", e))?;
+            summarize(account, kind, &proposal)
+        }
+        SweepKind::SendAll => {
+            let proposal = lc
+                .propose_send_all(address.clone(), false, None, account_id(account)?)
+                .await
+                .map_err(|e| anyhow::anyhow!("failed to propose send: {}", e))?;
+            summarize(account, kind, &proposal)
+        }
+    }
+}
+
+/// Builds the preview of a sweep of the first `no_of_accounts` accounts to `address`.
+///
+/// Accounts with nothing to move in a pool are skipped. Any other failure to propose
+/// a transaction, such as funds that aren't spendable yet, is returned.
+pub async fn propose_sweep(
+    lc: &mut LightClient,
+    address: &str,
+    no_of_accounts: u32,
+) -> anyhow::Result<Vec<SweepStep>> {
+    let address = parse_address(address)?;
+    let balances = account_balances(lc, no_of_accounts).await?;
+
+    let mut steps = vec![];
+    for (account, balance) in (0..no_of_accounts).zip(balances) {
+        let transparent = balance
+            .total_transparent_balance
+            .map_or(0, |z| z.into_u64());
+        let shielded: u64 = [balance.total_sapling_balance, balance.total_orchard_balance]
+            .into_iter()
+            .flatten()
+            .map(|z| z.into_u64())
+            .sum();
+        for (kind, funds) in [
+            (SweepKind::Shield, transparent),
+            (SweepKind::SendAll, shielded),
+        ] {
+            // Proposals fail when there are no funds to move, which isn't an error here
+            if funds == 0 {
+                continue;
+            }
+            let step = propose_step(lc, &address, account, kind).await?;
+            if step.amount > 0 {
+                steps.push(step);
+            }
+        }
+    }
+
+    if steps.is_empty() {
+        anyhow::bail!("nothing to sweep, no spendable funds found");
+    }
+
+    Ok(steps)
+}
+
+/// Sends the transactions previewed by [`propose_sweep`], stopping at the first
+/// step that fails. See [`SweepResult`].
+///
+/// Each step is proposed again right before sending, as the client only stores the
+/// latest proposal.
+pub async fn send_sweep(
+    lc: &mut LightClient,
+    address: &str,
+    steps: &[SweepStep],
+) -> anyhow::Result<SweepResult> {
+    let address = parse_address(address)?;
+    let steps = steps.iter().map(|step| (step.account, step.kind));
+    Ok(SweepResult::send(lc, &address, steps).await)
+}
+
+/// Proposes the transaction of one step again and sends it.
+async fn send_step(
+    lc: &mut LightClient,
+    address: &ZcashAddress,
+    account: u32,
+    kind: SweepKind,
+) -> anyhow::Result<(SweepStep, Vec<TxId>)> {
+    let step = propose_step(lc, address, account, kind).await?;
+    let sent = lc
+        .send_stored_proposal()
+        .await
+        .map_err(|e| anyhow::anyhow!("failed to send transaction of account {}: {}", account, e))?;
+    Ok((step, sent.into_iter().collect()))
+}
+
+/// Accounts of a sweep that shield transparent funds. The funds they shield are left
+/// in the wallet until [`send_shielded`] sends them.
+pub fn shielding_accounts(steps: &[SweepStep]) -> Vec<u32> {
+    let accounts: BTreeSet<u32> = steps
+        .iter()
+        .filter(|s| s.kind == SweepKind::Shield)
+        .map(|s| s.account)
+        .collect();
+    accounts.into_iter().collect()
+}
+
+/// Second pass of a sweep: sends the funds shielded by the first pass for `accounts`
+/// to `address`, stopping at the first account that fails. Fails until all the
+/// transactions `txids` of the first pass are confirmed, as shielded funds can't be
+/// spent before.
+pub async fn send_shielded(
+    lc: &mut LightClient,
+    address: &str,
+    accounts: &[u32],
+    txids: &[TxId],
+) -> anyhow::Result<SweepResult> {
+    let pending = refresh_statuses(lc, txids)
+        .await?
+        .into_iter()
+        .filter(|tx| !tx.confirmed)
+        .count();
+    if pending > 0 {
+        anyhow::bail!(
+            "{} sweep transaction(s) not confirmed yet, try again later",
+            pending
+        );
+    }
+
+    let address = parse_address(address)?;
+    let steps = accounts
+        .iter()
+        .map(|&account| (account, SweepKind::SendAll));
+    Ok(SweepResult::send(lc, &address, steps).await)
+}
+
+/// Returns the confirmation status of the given transactions, as known by the wallet.
+pub async fn transaction_statuses(lc: &LightClient, txids: &[TxId]) -> Vec<SweepTransaction> {
+    let wallet = lc.wallet.lock().await;
+    txids
+        .iter()
+        .map(|txid| {
+            let status = wallet.wallet_transactions.get(txid).map(|tx| tx.status());
+            SweepTransaction {
+                txid: txid.to_string(),
+                status: status
+                    .map(|s| s.to_string())
+                    .unwrap_or_else(|| "unknown".to_string()),
+                confirmed: status.is_some_and(|s| s.is_confirmed()),
+            }
+        })
+        .collect()
+}
+
+/// Syncs the wallet with the chain tip, then returns the status of the given
+/// transactions. See [`transaction_statuses`].
+pub async fn refresh_statuses(
+    lc: &mut LightClient,
+    txids: &[TxId],
+) -> anyhow::Result<Vec<SweepTransaction>> {
+    lc.sync()
+        .await
+        .map_err(|e| anyhow::anyhow!("failed to start sync: {}", e))?;
+    lc.await_sync()
+        .await
+        .map_err(|e| anyhow::anyhow!("failed to sync: {}", e))?;
+
+    Ok(transaction_statuses(lc, txids).await)
+}
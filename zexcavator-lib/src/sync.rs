@@ -0,0 +1,694 @@
+//! # Sync
+//!
+//! Builds a `zingolib` [`LightClient`] from a wallet file or a mnemonic phrase and
+//! drives `pepper-sync` until the wallet is fully scanned. Progress and log lines
+//! are published through shared buffers so any front end can display them.
+
+use std::fmt;
+use std::num::NonZero;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use bip0039::{English, Mnemonic};
+use http::Uri;
+use pepper_sync::sync::{SyncConfig, TransparentAddressDiscovery};
+use pepper_sync::sync_status;
+use zingolib::config::ZingoConfig;
+use zingolib::config::load_clientconfig;
+use zingolib::data::PollReport;
+use zingolib::lightclient::LightClient;
+use zingolib::wallet::balance::AccountBalance;
+use zingolib::wallet::{LightWallet, WalletBase, WalletSettings};
+
+use crate::parser::detect::{self, WalletFormat};
+use crate::parser::{Wallet, WalletImportedKeys, WalletParserFactory};
+use crate::settings::SyncSettings;
+
+pub type LogBuffer = Arc<Mutex<Vec<String>>>;
+
+pub fn new_log_buffer() -> LogBuffer {
+    Arc::new(Mutex::new(Vec::new()))
+}
+
+/// Sums the balance of every pool of an account, in zatoshis.
+pub fn total_balance(balance: &AccountBalance) -> u64 {
+    [
+        balance.total_transparent_balance,
+        balance.total_sapling_balance,
+        balance.total_orchard_balance,
+    ]
+    .into_iter()
+    .flatten()
+    .map(|z| z.into_u64())
+    .sum()
+}
+
+/// Formats an amount of zatoshis as ZEC.
+pub fn format_zec(zatoshis: u64) -> String {
+    format!("{}.{:08}", zatoshis / 100_000_000, zatoshis % 100_000_000)
+}
+
+/// Fetches the balance of the first `no_of_accounts` accounts of the wallet.
+pub async fn account_balances(
+    light_client: &LightClient,
+    no_of_accounts: u32,
+) -> anyhow::Result<Vec<AccountBalance>> {
+    let wallet = light_client.wallet.lock().await;
+    let mut balances = Vec::with_capacity(no_of_accounts as usize);
+    for id in 0..no_of_accounts {
+        let account_id = zip32::AccountId::try_from(id)
+            .map_err(|_| anyhow::anyhow!("invalid account id {}", id))?;
+        let balance = wallet
+            .account_balance(account_id)
+            .await
+            .map_err(|e| anyhow::anyhow!("failed to get balance of account {}: {}", id, e))?;
+        balances.push(balance);
+    }
+    Ok(balances)
+}
+
+/// Tracks consecutive sync failures and moves to the next server once a server has
+/// failed too many times in a row. Syncing gives up once the last server has.
+struct Failover {
+    servers: Vec<Uri>,
+    current: usize,
+    failures: u32,
+    max_failures: u32,
+}
+
+impl Failover {
+    fn new(servers: Vec<Uri>, max_failures: u32) -> Self {
+        Self {
+            servers,
+            current: 0,
+            failures: 0,
+            max_failures: max_failures.max(1),
+        }
+    }
+
+    /// Records a failure, returning the server to switch to if it is time to, or an
+    /// error once every server has failed `max_failures` times in a row.
+    fn failed(&mut self) -> anyhow::Result<Option<Uri>> {
+        self.failures += 1;
+        if self.failures < self.max_failures {
+            return Ok(None);
+        }
+        if self.current + 1 >= self.servers.len() {
+            let servers: Vec<String> = self.servers.iter().map(|s| s.to_string()).collect();
+            anyhow::bail!(
+                "sync failed {} times in a row on every server ({}), giving up",
+                self.max_failures,
+                servers.join(", ")
+            );
+        }
+        self.current += 1;
+        self.failures = 0;
+        Ok(Some(self.servers[self.current].clone()))
+    }
+}
+
+/// Balance found for a key imported into the wallet. Imported keys are scanned from
+/// their viewing key only, so their funds aren't part of a sweep, see
+/// [`IMPORTED_KEYS_NOTE`].
+#[derive(Debug, Clone)]
+pub struct ImportedKeyBalance {
+    pub address: String,
+    /// Zatoshis found, `None` for keys that can't be scanned, such as imported
+    /// transparent keys.
+    pub balance: Option<u64>,
+}
+
+impl fmt::Display for ImportedKeyBalance {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.balance {
+            Some(balance) => write!(
+                f,
+                "Imported key {}: {} ZEC (view-only)",
+                self.address,
+                format_zec(balance)
+            ),
+            None => write!(f, "Imported key {}: not scanned", self.address),
+        }
+    }
+}
+
+/// Shown next to the balances of imported keys.
+pub const IMPORTED_KEYS_NOTE: &str = "Imported keys are scanned view-only and aren't swept, sweep them separately with their spending key";
+
+/// Total balance of the imported keys that were scanned.
+pub fn imported_total(imported: &[ImportedKeyBalance]) -> u64 {
+    imported.iter().filter_map(|k| k.balance).sum()
+}
+
+#[derive(Debug, Clone)]
+pub struct Syncer {
+    log_buffer: LogBuffer,
+    // Progress between 0.0 and 1.0
+    pub progress: Arc<Mutex<f32>>,
+    pub sync_complete: Arc<Mutex<bool>>, // TODO: Replace with AtomicBool
+    // Number of HD accounts being synced
+    pub no_of_accounts: Arc<Mutex<u32>>,
+    // Balances of the keys imported into the wallet, scanned separately from the seed
+    pub imported_balances: Arc<Mutex<Vec<ImportedKeyBalance>>>,
+    // Network and servers used by the next sync
+    pub settings: Arc<Mutex<SyncSettings>>,
+    // Wallet file the last sync started from, exported along with the synced data
+    pub source_wallet: Arc<Mutex<Option<Wallet>>>,
+    // Whether damaged wallet files are read as far as possible
+    tolerant_read: bool,
+}
+
+impl Syncer {
+    pub fn new_with_log(log_buffer: LogBuffer) -> Self {
+        Self {
+            log_buffer,
+            progress: Arc::new(Mutex::new(0.0)),
+            sync_complete: Arc::new(Mutex::new(false)),
+            no_of_accounts: Arc::new(Mutex::new(1)),
+            imported_balances: Arc::new(Mutex::new(Vec::new())),
+            settings: Arc::new(Mutex::new(SyncSettings::default())),
+            source_wallet: Arc::new(Mutex::new(None)),
+            tolerant_read: false,
+        }
+    }
+
+    pub fn with_settings(self, settings: SyncSettings) -> Self {
+        *self.settings.lock().unwrap() = settings;
+        self
+    }
+
+    /// Reads damaged ZecWallet Lite files as far as possible, see
+    /// [`WalletParserFactory::read_as_tolerant`].
+    pub fn with_tolerant_read(mut self, tolerant: bool) -> Self {
+        self.tolerant_read = tolerant;
+        self
+    }
+
+    pub fn get_settings(&self) -> SyncSettings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    pub fn get_progress(&self) -> Arc<Mutex<f32>> {
+        Arc::clone(&self.progress)
+    }
+
+    fn log(&self, line: String) {
+        self.log_buffer.lock().unwrap().push(line);
+    }
+
+    /// Logs `e` and hands it back, to fail the sync with `?`.
+    fn logged(&self, e: anyhow::Error) -> anyhow::Error {
+        self.log(format!("{}", e));
+        e
+    }
+
+    pub fn get_no_of_accounts(&self) -> u32 {
+        *self.no_of_accounts.lock().unwrap()
+    }
+
+    /// Wallet settings for recovery. The transparent gap limit is widened so that at
+    /// least `transparent_addresses` external addresses are discovered.
+    pub(crate) fn wallet_settings(transparent_addresses: u32) -> WalletSettings {
+        let mut transparent_address_discovery = TransparentAddressDiscovery::recovery();
+        transparent_address_discovery.gap_limit = transparent_address_discovery
+            .gap_limit
+            .max(u8::try_from(transparent_addresses).unwrap_or(u8::MAX));
+
+        WalletSettings {
+            sync_config: SyncConfig {
+                transparent_address_discovery,
+            },
+        }
+    }
+
+    /// Client config pointing at the first configured server.
+    fn client_config(&self, transparent_addresses: u32) -> anyhow::Result<ZingoConfig> {
+        let settings = self.get_settings();
+        let server = settings
+            .servers()
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("no valid lightwalletd server configured"))?;
+        self.log(format!("Using server {} on {}", server, settings.network));
+
+        load_clientconfig(
+            server,
+            None,
+            settings.network.chain_type(),
+            Self::wallet_settings(transparent_addresses),
+            NonZero::new(1).unwrap(),
+        )
+        .map_err(|e| anyhow::anyhow!("failed to load client config: {}", e))
+    }
+
+    fn install_crypto_provider(&self) {
+        if let Err(e) = rustls::crypto::ring::default_provider().install_default() {
+            self.log(format!("Error installing crypto provider: {:?}", e));
+        }
+    }
+
+    /// Syncs the wallet file at `path`, read as `format` or in the format detected from
+    /// its content. `secret` is the password of an encrypted wallet, or the seed phrase
+    /// of a wallet that doesn't store its seed.
+    pub async fn start_wallet_sync_from_path(
+        &self,
+        path: PathBuf,
+        format: Option<WalletFormat>,
+        secret: Option<String>,
+    ) -> anyhow::Result<LightClient> {
+        let filename = path
+            .to_str()
+            .ok_or_else(|| self.logged(anyhow::anyhow!("invalid wallet path {:?}", path)))?;
+        let read = match format {
+            Some(format) if self.tolerant_read => {
+                WalletParserFactory::read_as_tolerant(filename, format)
+            }
+            Some(format) => WalletParserFactory::read_as(filename, format),
+            None => match detect::detect(&path) {
+                Ok(candidates) => {
+                    let ranked: Vec<String> = candidates.iter().map(|c| c.to_string()).collect();
+                    self.log(format!("Detected wallet formats: {}", ranked.join(", ")));
+                    WalletParserFactory::read_detected(filename, &candidates, self.tolerant_read)
+                }
+                Err(e) => Err(e.into()),
+            },
+        };
+        let mut wallet_parser = match read {
+            Ok(wallet_parser) => {
+                self.log(format!("Reading wallet as {}", wallet_parser.format));
+                for lost in wallet_parser.parser.get_lost_sections() {
+                    self.log(format!("Damaged wallet file, lost {}", lost));
+                }
+                wallet_parser
+            }
+            Err(e) => {
+                self.log(format!("Error reading wallet: {}", e));
+                return Err(e.into());
+            }
+        };
+        if let Err(e) = wallet_parser.unlock(secret.as_deref()) {
+            self.log(format!("Error opening wallet: {}", e));
+            return Err(e.into());
+        }
+
+        // Syncing against another network would silently find nothing
+        let wallet_network = wallet_parser.parser.get_network();
+        let network = self.get_settings().network;
+        if wallet_network != network {
+            let msg = format!(
+                "Wallet belongs to {} but the sync settings use {}, change the network in the settings",
+                wallet_network, network
+            );
+            self.log(msg.clone());
+            anyhow::bail!(msg);
+        }
+
+        *self.source_wallet.lock().unwrap() = match Wallet::from_parser(&*wallet_parser.parser) {
+            Ok(wallet) => Some(wallet),
+            Err(e) => {
+                self.log(format!("Error reading wallet accounts: {}", e));
+                None
+            }
+        };
+
+        // Syncing a made up seed would silently find nothing
+        let Some(seed) = wallet_parser.parser.get_wallet_seed() else {
+            let msg = "Wallet stores no BIP-39 seed, it can't be synced from a seed phrase";
+            self.log(msg.to_string());
+            anyhow::bail!(msg);
+        };
+        let bd = wallet_parser.parser.get_birthday();
+        let wallet_version = wallet_parser.parser.get_wallet_version();
+        let no_of_accounts = wallet_parser.parser.get_hd_account_count().max(1);
+        let t_addresses = wallet_parser.parser.get_hd_transparent_address_count();
+        let imported_keys = wallet_parser.parser.get_imported_keys();
+        *self.no_of_accounts.lock().unwrap() = no_of_accounts;
+        self.imported_balances.lock().unwrap().clear();
+
+        self.install_crypto_provider();
+
+        let zc = match self.client_config(t_addresses) {
+            Ok(zc) => zc,
+            Err(e) => {
+                self.log(format!("{}", e));
+                return Err(e);
+            }
+        };
+
+        let initial_bh: u32 = bd
+            .try_into()
+            .map_err(|_| self.logged(anyhow::anyhow!("invalid wallet birthday {}", bd)))?;
+        let mnemonic = Mnemonic::from_entropy(seed)
+            .map_err(|e| self.logged(anyhow::anyhow!("invalid wallet seed: {}", e)))?;
+        let lw = LightWallet::new(
+            zc.chain,
+            WalletBase::Mnemonic {
+                mnemonic,
+                no_of_accounts: NonZero::new(no_of_accounts).unwrap_or(NonZero::<u32>::MIN),
+            },
+            initial_bh.into(),
+            Self::wallet_settings(t_addresses),
+        )
+        .map_err(|e| self.logged(anyhow::anyhow!("failed to create wallet: {}", e)))?;
+
+        let light_client = LightClient::create_from_wallet(lw, zc, true)
+            .map_err(|e| self.logged(anyhow::anyhow!("failed to create light client: {}", e)))?;
+
+        let mnemonic = {
+            let wallet_guard = light_client.wallet.lock().await;
+            let mnemonic = wallet_guard.mnemonic().cloned();
+            mnemonic
+        };
+
+        self.log(format!("=== WALLET VERSION: {} ===", wallet_version));
+        if let Some(mnemonic) = mnemonic {
+            self.log(format!("Mnemonic: {}", mnemonic));
+        }
+        self.log(format!(
+            "Scanning {} HD account(s) and {} transparent address(es)",
+            no_of_accounts, t_addresses
+        ));
+        self.log(format!("Starting sync from birthday: {}", bd));
+
+        if !imported_keys.is_empty() {
+            self.sync_imported_keys(imported_keys, &zc, initial_bh)
+                .await?;
+        }
+
+        self.run_sync(light_client).await
+    }
+
+    /// Scans the keys imported into the wallet. Each imported shielded key gets its own
+    /// view-only wallet built from a single-pool UFVK, so the balance found can't be
+    /// spent from here. Imported transparent keys can't be expressed as a UFVK, so they
+    /// are listed without a balance.
+    async fn sync_imported_keys(
+        &self,
+        imported_keys: WalletImportedKeys,
+        config: &ZingoConfig,
+        birthday: u32,
+    ) -> anyhow::Result<()> {
+        for tkey in &imported_keys.tkeys {
+            let imported = ImportedKeyBalance {
+                address: tkey.address.clone(),
+                balance: None,
+            };
+            self.log(format!("{}", imported));
+            self.imported_balances.lock().unwrap().push(imported);
+        }
+
+        let ufvks = imported_keys
+            .zkeys
+            .iter()
+            .map(|z| (z.address.clone(), z.to_ufvk()))
+            .chain(
+                imported_keys
+                    .okeys
+                    .iter()
+                    .map(|o| (o.address.clone(), o.to_ufvk())),
+            );
+
+        for (address, ufvk) in ufvks {
+            let Some(ufvk) = ufvk else {
+                self.log(format!("Invalid imported key for {}, skipping", address));
+                continue;
+            };
+
+            let lw = match LightWallet::new(
+                config.chain,
+                WalletBase::Ufvk(ufvk.encode(&config.chain)),
+                birthday.into(),
+                Self::wallet_settings(1),
+            ) {
+                Ok(lw) => lw,
+                Err(e) => {
+                    self.log(format!("Error loading imported key for {}: {}", address, e));
+                    continue;
+                }
+            };
+            let light_client = match LightClient::create_from_wallet(lw, config.clone(), true) {
+                Ok(lc) => lc,
+                Err(e) => {
+                    self.log(format!("Error loading imported key for {}: {}", address, e));
+                    continue;
+                }
+            };
+
+            self.log(format!("Scanning imported key for {}", address));
+            let light_client = self.drive_sync(light_client).await?;
+
+            match account_balances(&light_client, 1).await {
+                Ok(balances) => {
+                    let imported = ImportedKeyBalance {
+                        address,
+                        balance: Some(balances.first().map_or(0, total_balance)),
+                    };
+                    self.log(format!("{}", imported));
+                    self.imported_balances.lock().unwrap().push(imported);
+                }
+                Err(e) => self.log(format!("{}", e)),
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn start_wallet_sync_from_mnemonic(
+        &self,
+        mnemonic_str: String,
+        birthday: Option<u32>,
+    ) -> anyhow::Result<LightClient> {
+        self.install_crypto_provider();
+        *self.no_of_accounts.lock().unwrap() = 1;
+        self.imported_balances.lock().unwrap().clear();
+        *self.source_wallet.lock().unwrap() = None;
+
+        let zc = self.client_config(1).map_err(|e| self.logged(e))?;
+
+        let mnemonic = Mnemonic::<English>::from_str(&mnemonic_str)
+            .map_err(|e| self.logged(anyhow::anyhow!("invalid seed phrase: {}", e)))?;
+
+        let birthday = birthday.unwrap_or_default();
+
+        let lw = LightWallet::new(
+            zc.chain,
+            WalletBase::Mnemonic {
+                mnemonic,
+                no_of_accounts: NonZero::<u32>::MIN,
+            },
+            birthday.into(),
+            Self::wallet_settings(1),
+        )
+        .map_err(|e| self.logged(anyhow::anyhow!("failed to create wallet: {}", e)))?;
+
+        let light_client = LightClient::create_from_wallet(lw, zc, true)
+            .map_err(|e| self.logged(anyhow::anyhow!("failed to create light client: {}", e)))?;
+
+        self.log(format!("Starting sync from birthday: {}", birthday));
+
+        self.run_sync(light_client).await
+    }
+
+    /// Restarts a failed sync, on the next server if the current one keeps failing.
+    /// Starting the sync is retried until it succeeds, or until every server has
+    /// failed, see [`Failover::failed`].
+    async fn restart_sync(
+        &self,
+        light_client: &mut LightClient,
+        failover: &mut Failover,
+    ) -> anyhow::Result<()> {
+        loop {
+            if let Some(server) = failover.failed().map_err(|e| self.logged(e))? {
+                self.log(format!("Switching to server {}", server));
+                light_client.set_server(server);
+            }
+
+            self.log("Restarting sync".to_string());
+            match light_client.sync().await {
+                Ok(_) => {
+                    self.log("Sync resumed".to_string());
+                    return Ok(());
+                }
+                Err(e) => {
+                    self.log(format!("Error starting syncing: {}", e));
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                }
+            }
+        }
+    }
+
+    /// Syncs the recovered wallet and logs the balance found in each account, plus
+    /// the imported keys, once it is fully scanned.
+    async fn run_sync(&self, light_client: LightClient) -> anyhow::Result<LightClient> {
+        let light_client = self.drive_sync(light_client).await?;
+
+        match account_balances(&light_client, self.get_no_of_accounts()).await {
+            Ok(balances) => {
+                for (i, balance) in balances.iter().enumerate() {
+                    self.log(format!(
+                        "Account {} (HD index {}): {} ZEC",
+                        i + 1,
+                        i,
+                        format_zec(total_balance(balance))
+                    ));
+                }
+                let imported = self.imported_balances.lock().unwrap().clone();
+                let total = balances.iter().map(total_balance).sum::<u64>();
+                if imported.is_empty() {
+                    self.log(format!("Total ZEC found: {}", format_zec(total)));
+                } else {
+                    let imported = imported_total(&imported);
+                    self.log(format!(
+                        "Total ZEC found: {}, {} of it view-only",
+                        format_zec(total + imported),
+                        format_zec(imported)
+                    ));
+                    self.log(IMPORTED_KEYS_NOTE.to_string());
+                }
+            }
+            Err(e) => self.log(format!("{}", e)),
+        }
+
+        *self.sync_complete.lock().unwrap() = true;
+
+        Ok(light_client)
+    }
+
+    /// Drives `pepper-sync` to completion, restarting it whenever it fails. After
+    /// `max_sync_retries` consecutive failures the next configured server is used, and
+    /// the sync fails once the last one has failed as many times.
+    async fn drive_sync(&self, mut light_client: LightClient) -> anyhow::Result<LightClient> {
+        let settings = self.get_settings();
+        let mut failover = Failover::new(settings.servers(), settings.max_sync_retries);
+
+        match light_client.sync().await {
+            Ok(_) => self.log("Sync started".to_string()),
+            Err(e) => {
+                self.log(format!("Error starting syncing: {}", e));
+                self.restart_sync(&mut light_client, &mut failover).await?;
+            }
+        }
+
+        let mut interval = tokio::time::interval(Duration::from_secs(1));
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            match light_client.poll_sync() {
+                PollReport::NoHandle => {}
+                PollReport::NotReady => {
+                    let wallet_guard = light_client.wallet.lock().await;
+                    match sync_status(&*wallet_guard).await {
+                        Ok(status) => {
+                            *self.progress.lock().unwrap() =
+                                status.percentage_total_outputs_scanned;
+                            self.log(format!("{}", status));
+                        }
+                        Err(e) => {
+                            self.log(format!("{}", e));
+                            continue;
+                        }
+                    };
+                }
+                PollReport::Ready(result) => match result {
+                    Ok(sync_result) => {
+                        self.log(format!("Sync result: {:?}", sync_result));
+                        break;
+                    }
+                    Err(e) => {
+                        self.log(format!("Sync error: {}", e));
+                        self.restart_sync(&mut light_client, &mut failover).await?;
+                        continue;
+                    }
+                },
+            };
+        }
+
+        match light_client.await_sync().await {
+            Ok(_) => self.log("Sync finished".to_string()),
+            Err(e) => self.log(format!("{}", e)),
+        }
+
+        Ok(light_client)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[derive(Debug, serde::Deserialize, Clone)]
+    pub struct WalletTestVector {
+        pub mnemonic: String,
+        pub birthday: Option<u64>,
+    }
+
+    #[tokio::test]
+    async fn test_mnemonic_vectors_from_file() {
+        let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        path.push("testvectors/mnemonic.json");
+
+        println!("Using test vector file: {}", path.display());
+
+        let json = fs::read_to_string(&path).expect("Failed to read test vector file");
+        let vectors: Vec<WalletTestVector> =
+            serde_json::from_str(&json).expect("Failed to parse test vectors");
+
+        for (i, vec) in vectors.iter().enumerate() {
+            println!("\n Running vector {i}: {}", &vec.mnemonic);
+            println!("from birthday: {}", vec.birthday.unwrap_or(0));
+
+            let log_buffer = Arc::new(Mutex::new(vec![]));
+            let syncer = Syncer::new_with_log(log_buffer.clone());
+
+            let client = syncer
+                .start_wallet_sync_from_mnemonic(
+                    vec.mnemonic.clone(),
+                    Some(vec.birthday.unwrap_or(0) as u32),
+                )
+                .await
+                .unwrap();
+
+            let complete = *syncer.sync_complete.lock().unwrap();
+            assert!(
+                complete,
+                "Vector {i} failed: Sync did not complete\nMnemonic: {}\nBirthday: {:?}",
+                vec.mnemonic, vec.birthday
+            );
+
+            let balances = client
+                .wallet
+                .lock()
+                .await
+                .account_balance(zip32::AccountId::try_from(0).unwrap())
+                .await
+                .unwrap();
+            let final_balance = balances.total_transparent_balance.unwrap()
+                + balances.total_sapling_balance.unwrap()
+                + balances.total_orchard_balance.unwrap();
+            let balance_in_zec = final_balance.unwrap() / NonZero::new(10u64.pow(8)).unwrap();
+
+            println!(
+                "Vector {i} passed! Found balance: {} ZEC",
+                balance_in_zec.into_u64()
+            );
+        }
+    }
+
+    #[test]
+    fn test_failover() {
+        let servers: Vec<Uri> = vec![
+            Uri::from_static("https://a.example.com:443"),
+            Uri::from_static("https://b.example.com:443"),
+        ];
+        let mut failover = Failover::new(servers.clone(), 2);
+
+        assert_eq!(failover.failed().unwrap(), None);
+        assert_eq!(failover.failed().unwrap(), Some(servers[1].clone()));
+        // Syncing gives up once the last server has failed as many times
+        assert_eq!(failover.failed().unwrap(), None);
+        assert!(failover.failed().is_err());
+    }
+}
@@ -12,21 +12,28 @@ use tuirealm::event::NoUserEvent;
 use tuirealm::props::{PropPayload, PropValue};
 use tuirealm::terminal::{CrosstermTerminalAdapter, TerminalAdapter, TerminalBridge};
 use tuirealm::{Application, AttrValue, Attribute, EventListenerCfg, Update};
+use zexcavator_lib::settings::SyncSettings;
+use zexcavator_lib::sync::Syncer;
 use zingolib::lightclient::LightClient;
 
 use crate::components::HandleMessage;
+use crate::components::error_dialog::ErrorDialog;
 use crate::components::log_viewer::{LogViewer, SyncSource, new_log_buffer};
 use crate::components::menu::MenuOptions;
 use crate::components::mnemonic_input::MnemonicInput;
-use crate::views::export::send::ExportSendView;
+use crate::components::repair_input::RepairField;
+use crate::components::settings_input::SettingsField;
+use crate::views::export::send::{ExportSendForm, ExportSendView};
 use crate::views::export::zewif::ExportZewifView;
 use crate::views::export::zingolib::ExportZingolibView;
 use crate::views::export::{ExportOptions, ExportView};
 use crate::views::main_menu::MainMenu;
+use crate::views::settings::{SettingsStatusView, SettingsView};
 use crate::views::sync::SyncView;
 use crate::views::zecwallet::ZecwalletMenu;
 use crate::views::zecwallet::from_mnemonic::ZecwalletFromMnemonic;
 use crate::views::zecwallet::from_path::ZecwalletFromPath;
+use crate::views::zecwallet::repair_mnemonic::{RepairResultView, ZecwalletRepairMnemonic};
 use crate::views::{Mountable, Renderable, main_menu};
 
 use super::{Id, Msg};
@@ -38,11 +45,13 @@ pub enum Screen {
     ZecwalletInput,
     ZecwalletFromPath,
     ZecwalletFromMnemonic,
+    ZecwalletRepairMnemonic,
     ZcashdInput,
     Result,
     ExportZewif,
     ExportSend,
     ExportZingolib,
+    Settings,
 }
 
 pub struct Model<T>
@@ -59,11 +68,15 @@ where
     pub terminal: TerminalBridge<T>,
     /// Active screen
     pub screen: Screen,
-    pub sync_view: Arc<SyncView>,
+    pub syncer: Arc<Syncer>,
     pub light_client: Arc<RwLock<Option<LightClient>>>,
     pub export_menu: ExportView,
     pub export_zewif: ExportZewifView,
+    pub export_send: ExportSendView,
     pub export_zingolib: ExportZingolibView,
+    pub repair_result: RepairResultView,
+    pub settings_status: SettingsStatusView,
+    pub error_dialog: ErrorDialog,
 }
 
 impl Default for Model<CrosstermTerminalAdapter> {
@@ -71,15 +84,30 @@ impl Default for Model<CrosstermTerminalAdapter> {
         let log_buffer_path = new_log_buffer();
         let light_client = Arc::new(RwLock::new(None));
         let export_menu = ExportView::new(Arc::clone(&light_client));
-        let export_zewif = ExportZewifView::new(Arc::clone(&light_client));
+        let syncer = Syncer::new_with_log(log_buffer_path.clone());
+        let export_zewif =
+            ExportZewifView::new(Arc::clone(&light_client), Arc::clone(&syncer.source_wallet));
         let export_send = ExportSendView::new(Arc::clone(&light_client));
         let export_zingolib = ExportZingolibView::new(Arc::clone(&light_client));
+        let repair_result = RepairResultView::default();
+        let settings_status = SettingsStatusView::default();
+        let error_dialog = ErrorDialog::default();
+        // A broken settings file is reported on the settings screen, defaults are used
+        let settings = SyncSettings::load().unwrap_or_else(|e| {
+            *settings_status.status.lock().unwrap() =
+                Some(format!("Error loading settings: {}", e));
+            SyncSettings::default()
+        });
 
         let mut app = Self::init_app(
             export_menu.clone(),
             export_zewif.clone(),
             export_send.clone(),
             export_zingolib.clone(),
+            repair_result.clone(),
+            &settings,
+            settings_status.clone(),
+            error_dialog.clone(),
         );
 
         assert!(
@@ -97,11 +125,15 @@ impl Default for Model<CrosstermTerminalAdapter> {
             redraw: true,
             screen: Screen::MainMenu,
             terminal: TerminalBridge::init_crossterm().expect("Cannot initialize terminal"),
-            sync_view: Arc::new(SyncView::new_with_log(log_buffer_path)),
+            syncer: Arc::new(syncer.with_settings(settings)),
             light_client,
             export_menu,
             export_zewif,
+            export_send,
             export_zingolib,
+            repair_result,
+            settings_status,
+            error_dialog,
         }
     }
 }
@@ -122,7 +154,11 @@ where
                         Screen::ZecwalletFromMnemonic => {
                             ZecwalletFromMnemonic::render(&mut self.app, f)
                         }
-                        Screen::ZcashdInput => todo!(),
+                        Screen::ZecwalletRepairMnemonic => {
+                            ZecwalletRepairMnemonic::render(&mut self.app, f)
+                        }
+                        // zcashd wallets are read from their path, like ZecWallet files
+                        Screen::ZcashdInput => ZecwalletFromPath::render(&mut self.app, f),
                         Screen::Result => {
                             let area = f.area();
                             self.app.view(&Id::ExportView, f, area);
@@ -139,6 +175,11 @@ where
                             let area = f.area();
                             self.app.view(&Id::ExportZingolib, f, area);
                         }
+                        Screen::Settings => SettingsView::render(&mut self.app, f),
+                    }
+                    if self.error_dialog.is_open() {
+                        self.app
+                            .view(&Id::ErrorDialog, f, ErrorDialog::area(f.area()));
                     }
                 })
                 .is_ok()
@@ -150,6 +191,10 @@ where
         export_zewif: ExportZewifView,
         export_send: ExportSendView,
         export_zingolib: ExportZingolibView,
+        repair_result: RepairResultView,
+        settings: &SyncSettings,
+        settings_status: SettingsStatusView,
+        error_dialog: ErrorDialog,
     ) -> Application<Id, Msg, NoUserEvent> {
         // Setup application
         // NOTE: NoUserEvent is a shorthand to tell tui-realm we're not going to use any custom user event
@@ -177,6 +222,14 @@ where
 
         assert!(ZecwalletFromMnemonic::mount(&mut app).is_ok());
 
+        assert!(ZecwalletRepairMnemonic::mount(&mut app).is_ok());
+        assert!(
+            app.mount(Id::RepairResult, Box::new(repair_result), Vec::default())
+                .is_ok()
+        );
+
+        assert!(SettingsView::mount(&mut app, settings, settings_status).is_ok());
+
         assert!(SyncView::mount(&mut app).is_ok());
         assert!(
             app.mount(Id::ExportView, Box::new(export_menu), Vec::default())
@@ -191,8 +244,12 @@ where
 
         // Mount export send view
         assert!(
-            app.mount(Id::ExportSend, Box::new(export_send), Vec::default())
-                .is_ok()
+            app.mount(
+                Id::ExportSend,
+                Box::new(ExportSendForm::new(export_send)),
+                Vec::default()
+            )
+            .is_ok()
         );
 
         // Mount export zingolib view
@@ -205,6 +262,12 @@ where
             .is_ok()
         );
 
+        // Mount error dialog, drawn over any screen
+        assert!(
+            app.mount(Id::ErrorDialog, Box::new(error_dialog), Vec::default())
+                .is_ok()
+        );
+
         // Focus main menu
         assert!(app.active(&Id::MainMenu).is_ok());
 
@@ -218,10 +281,13 @@ where
     T: TerminalAdapter,
 {
     fn update(&mut self, msg: Option<Msg>) -> Option<Msg> {
-        if *self.sync_view.sync_complete.lock().unwrap() {
+        if *self.syncer.sync_complete.lock().unwrap() {
             let export_menu = self.export_menu.clone();
+            let no_of_accounts = self.syncer.get_no_of_accounts();
+            let imported_balances = self.syncer.imported_balances.lock().unwrap().clone();
             tokio::spawn(async move {
-                let balance = export_menu.load_balance().await;
+                *export_menu.imported_balances.write().await = imported_balances;
+                let balance = export_menu.load_balance(no_of_accounts).await;
                 if let Some(b) = balance {
                     let mut guard = export_menu.balance.write().await;
                     guard.replace(b);
@@ -230,7 +296,12 @@ where
             });
 
             self.navigate_to(Screen::Result);
-            *self.sync_view.sync_complete.lock().unwrap() = false;
+            *self.syncer.sync_complete.lock().unwrap() = false;
+            self.redraw = true;
+        }
+        if self.error_dialog.is_open() && !self.app.focus().is_some_and(|id| *id == Id::ErrorDialog)
+        {
+            let _ = self.app.active(&Id::ErrorDialog);
             self.redraw = true;
         }
         if self.screen == Screen::Syncing {
@@ -247,7 +318,7 @@ where
             self.redraw = true;
         }
         if self.screen == Screen::Syncing {
-            let progress = *self.sync_view.get_progress().lock().unwrap();
+            let progress = *self.syncer.get_progress().lock().unwrap();
             let _ = self.app.attr(
                 &Id::ProgressBar,
                 Attribute::Value,
@@ -278,6 +349,11 @@ where
                     None
                 }
                 Msg::None => None,
+                Msg::ErrorDialogClose => {
+                    *self.error_dialog.error.lock().unwrap() = None;
+                    self.navigate_to(Screen::MainMenu);
+                    None
+                }
                 Msg::SeedInputValidate(path) => {
                     match ZecwalletFromPath::validate_path(PathBuf::from_str(&path).unwrap()) {
                         Err(_) => None::<Msg>,
@@ -317,9 +393,12 @@ where
                                 });
                                 return None;
                             }
-                            ExportOptions::ZeWIF => {
+                            option @ (ExportOptions::ZeWIF | ExportOptions::ZeWIFWatchOnly) => {
                                 self.navigate_to(Screen::ExportZewif);
                                 let view = self.export_zewif.clone();
+                                *view.saved_path.lock().unwrap() = None;
+                                *view.watch_only.lock().unwrap() =
+                                    option == ExportOptions::ZeWIFWatchOnly;
                                 tokio::spawn(async move {
                                     let path_or_err = view
                                         .do_save()
@@ -332,7 +411,8 @@ where
                                 return None;
                             }
                             ExportOptions::Send => {
-                                todo!();
+                                self.navigate_to(Screen::ExportSend);
+                                return None;
                             }
                             ExportOptions::Cancel => {
                                 self.navigate_to(Screen::MainMenu);
@@ -345,7 +425,11 @@ where
                         Screen::MainMenu => MainMenu::handle_message(msg, self),
                         Screen::ZecwalletInput
                         | Screen::ZecwalletFromPath
-                        | Screen::ZecwalletFromMnemonic => ZecwalletMenu::handle_message(msg, self),
+                        | Screen::ZcashdInput
+                        | Screen::ZecwalletFromMnemonic
+                        | Screen::ZecwalletRepairMnemonic => {
+                            ZecwalletMenu::handle_message(msg, self)
+                        }
                         Screen::Result => ExportView::handle_message(msg, self),
                         _ => None,
                     }
@@ -363,6 +447,22 @@ where
                     None
                 }
                 Msg::FromPathInputBlur => {
+                    assert!(self.app.active(&Id::ZecwalletFromPathPassword).is_ok());
+                    None
+                }
+                Msg::PasswordInputChanged(password) => {
+                    assert!(
+                        self.app
+                            .attr(
+                                &Id::ZecwalletFromPathPassword,
+                                Attribute::Text,
+                                AttrValue::String(password)
+                            )
+                            .is_ok()
+                    );
+                    None
+                }
+                Msg::PasswordInputBlur => {
                     assert!(self.app.active(&Id::ZecwalletFromPathButton).is_ok());
                     None
                 }
@@ -390,6 +490,105 @@ where
                     assert!(self.app.active(&Id::ZecwalletFromPath).is_ok());
                     None
                 }
+                Msg::RepairInputChanged(field, s) => {
+                    let id = match field {
+                        RepairField::Phrase => Id::RepairPhraseInput,
+                        RepairField::WalletPath => Id::RepairWalletInput,
+                    };
+                    assert!(
+                        self.app
+                            .attr(&id, Attribute::Text, AttrValue::String(s))
+                            .is_ok()
+                    );
+                    None
+                }
+                Msg::RepairInputBlur(field) => {
+                    let next = match field {
+                        RepairField::Phrase => Id::RepairWalletInput,
+                        RepairField::WalletPath => Id::RepairMnemonicButton,
+                    };
+                    assert!(self.app.active(&next).is_ok());
+                    None
+                }
+                Msg::RepairMnemonicSubmitBlur => {
+                    assert!(self.app.active(&Id::RepairPhraseInput).is_ok());
+                    None
+                }
+                Msg::RepairMnemonicSubmit => {
+                    let query = |id: &Id| {
+                        self.app
+                            .query(id, Attribute::Text)
+                            .ok()
+                            .flatten()
+                            .and_then(|v| v.as_string())
+                            .filter(|v| !v.trim().is_empty())
+                    };
+                    let Some(phrase) = query(&Id::RepairPhraseInput) else {
+                        return None;
+                    };
+                    let wallet_path = query(&Id::RepairWalletInput);
+
+                    let result = Arc::clone(&self.repair_result.result);
+                    *result.lock().unwrap() = Some("Searching…".to_string());
+
+                    // The search is CPU bound, keep it off the async workers
+                    tokio::task::spawn_blocking(move || {
+                        let text =
+                            match RepairResultView::do_repair(&phrase, wallet_path.as_deref()) {
+                                Ok(phrases) if phrases.is_empty() => {
+                                    "No valid phrase found".to_string()
+                                }
+                                Ok(phrases) => format!(
+                                    "Found {} candidate phrases:\n{}",
+                                    phrases.len(),
+                                    phrases.join("\n")
+                                ),
+                                Err(e) => format!("Error: {}", e),
+                            };
+                        *result.lock().unwrap() = Some(text);
+                    });
+                    None
+                }
+                Msg::SettingsInputChanged(field, s) => {
+                    let id = match field {
+                        SettingsField::Network => Id::SettingsNetwork,
+                        SettingsField::Server => Id::SettingsServer,
+                        SettingsField::FallbackServers => Id::SettingsFallbackServers,
+                    };
+                    assert!(
+                        self.app
+                            .attr(&id, Attribute::Text, AttrValue::String(s))
+                            .is_ok()
+                    );
+                    None
+                }
+                Msg::SettingsInputBlur(field) => {
+                    let next = match field {
+                        SettingsField::Network => Id::SettingsServer,
+                        SettingsField::Server => Id::SettingsFallbackServers,
+                        SettingsField::FallbackServers => Id::SettingsSaveButton,
+                    };
+                    assert!(self.app.active(&next).is_ok());
+                    None
+                }
+                Msg::SettingsSaveBlur => {
+                    assert!(self.app.active(&Id::SettingsNetwork).is_ok());
+                    None
+                }
+                Msg::SettingsSave => {
+                    let status = match SettingsView::read(&self.app, &self.syncer.get_settings()) {
+                        Ok(settings) => {
+                            *self.syncer.settings.lock().unwrap() = settings.clone();
+                            match settings.save() {
+                                Ok(path) => format!("Saved to {}", path.display()),
+                                Err(e) => format!("Applied, but not saved: {}", e),
+                            }
+                        }
+                        Err(e) => format!("Error: {}", e),
+                    };
+                    *self.settings_status.status.lock().unwrap() = Some(status);
+                    None
+                }
                 Msg::FromMnemonicSubmit => {
                     let mnemonic: String = self
                         .app
@@ -426,23 +625,54 @@ where
                         .unwrap()
                         .unwrap_string();
 
-                    Some(Msg::StartSync(SyncSource::WalletFile(
-                        PathBuf::from_str(&path).unwrap(),
-                    )))
+                    let password = self
+                        .app
+                        .query(&Id::ZecwalletFromPathPassword, Attribute::Text)
+                        .ok()
+                        .flatten()
+                        .and_then(|p| p.as_string())
+                        .filter(|p| !p.is_empty());
+
+                    Some(Msg::StartSync(SyncSource::WalletFile {
+                        path: PathBuf::from_str(&path).unwrap(),
+                        password,
+                    }))
                 }
                 Msg::StartSync(source) => {
                     self.navigate_to(Screen::Syncing);
+                    // A sweep in progress belongs to the previous wallet
+                    self.export_send.reset();
 
-                    let sv = Arc::clone(&self.sync_view);
+                    let syncer = Arc::clone(&self.syncer);
                     let lc_lock = Arc::clone(&self.light_client);
+                    let error = Arc::clone(&self.error_dialog.error);
 
                     tokio::spawn(async move {
                         let result_lc: LightClient = match source {
-                            SyncSource::WalletFile(path) => {
-                                sv.start_wallet_sync_from_path(path).await
+                            SyncSource::WalletFile { path, password } => {
+                                // Errors are also reported in the sync log
+                                match syncer
+                                    .start_wallet_sync_from_path(path, None, password)
+                                    .await
+                                {
+                                    Ok(lc) => lc,
+                                    Err(e) => {
+                                        *error.lock().unwrap() = Some(e.to_string());
+                                        return;
+                                    }
+                                }
                             }
                             SyncSource::Mnemonic { mnemonic, birthday } => {
-                                sv.start_wallet_sync_from_mnemonic(mnemonic, birthday).await
+                                match syncer
+                                    .start_wallet_sync_from_mnemonic(mnemonic, birthday)
+                                    .await
+                                {
+                                    Ok(lc) => lc,
+                                    Err(e) => {
+                                        *error.lock().unwrap() = Some(e.to_string());
+                                        return;
+                                    }
+                                }
                             }
                         };
 
@@ -458,6 +688,35 @@ where
                     self.navigate_to(Screen::Result);
                     None
                 }
+                Msg::SendPropose(address) => {
+                    let view = self.export_send.clone();
+                    let no_of_accounts = self.syncer.get_no_of_accounts();
+                    tokio::spawn(async move {
+                        view.do_propose(address, no_of_accounts).await;
+                    });
+                    None
+                }
+                Msg::SendConfirm => {
+                    let view = self.export_send.clone();
+                    tokio::spawn(async move {
+                        view.do_send().await;
+                    });
+                    None
+                }
+                Msg::SendRefresh => {
+                    let view = self.export_send.clone();
+                    tokio::spawn(async move {
+                        view.do_refresh().await;
+                    });
+                    None
+                }
+                Msg::SendShielded => {
+                    let view = self.export_send.clone();
+                    tokio::spawn(async move {
+                        view.do_send_shielded().await;
+                    });
+                    None
+                }
                 Msg::InitializeLightClient => None,
                 Msg::FetchBalance => None,
                 Msg::BalanceReady(balance) => {
@@ -494,8 +753,9 @@ impl<T: TerminalAdapter> HasScreenAndQuit for Model<T> {
             Screen::ZecwalletInput => {
                 let _ = self.app.active(&Id::ZecwalletMenu);
             }
-            // TODO
-            Screen::ZcashdInput => (),
+            Screen::ZcashdInput => {
+                let _ = self.app.active(&Id::ZecwalletFromPath);
+            }
             Screen::Syncing => {
                 let _ = self.app.active(&Id::SyncLog);
             }
@@ -505,6 +765,9 @@ impl<T: TerminalAdapter> HasScreenAndQuit for Model<T> {
             Screen::ZecwalletFromMnemonic => {
                 let _ = self.app.active(&Id::MnemonicInput);
             }
+            Screen::ZecwalletRepairMnemonic => {
+                let _ = self.app.active(&Id::RepairPhraseInput);
+            }
             Screen::Result => {
                 let _ = self.app.active(&Id::ExportView);
             }
@@ -517,6 +780,9 @@ impl<T: TerminalAdapter> HasScreenAndQuit for Model<T> {
             Screen::ExportZingolib => {
                 let _ = self.app.active(&Id::ExportZingolib);
             }
+            Screen::Settings => {
+                let _ = self.app.active(&Id::SettingsNetwork);
+            }
         }
     }
 
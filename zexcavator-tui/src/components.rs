@@ -9,11 +9,15 @@ use super::Msg;
 
 // -- modules
 pub mod birthday_input;
+pub mod error_dialog;
 pub mod input;
 pub mod log_viewer;
 pub mod menu;
 pub mod mnemonic_input;
+pub mod password_input;
+pub mod repair_input;
 pub mod result_viewer;
+pub mod settings_input;
 pub mod sync_bar;
 pub mod welcome;
 
@@ -0,0 +1,82 @@
+use std::sync::{Arc, Mutex};
+
+use tuirealm::command::{Cmd, CmdResult};
+use tuirealm::event::{Key, KeyEvent};
+use tuirealm::props::Color;
+use tuirealm::ratatui::layout::{Constraint, Flex, Layout, Rect};
+use tuirealm::ratatui::style::Style;
+use tuirealm::ratatui::text::Text;
+use tuirealm::ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+use tuirealm::{AttrValue, Attribute, Component, Event, Frame, MockComponent, NoUserEvent, State};
+
+use crate::Msg;
+
+/// Dialog drawn over the current screen when a background task fails, e.g. a wallet
+/// file that can't be read. The task sets `error`, the model shows the dialog while
+/// it is set.
+#[derive(Clone, Default)]
+pub struct ErrorDialog {
+    pub error: Arc<Mutex<Option<String>>>,
+}
+
+impl ErrorDialog {
+    pub fn is_open(&self) -> bool {
+        self.error.lock().unwrap().is_some()
+    }
+
+    /// Area of the dialog, centered in `area`.
+    pub fn area(area: Rect) -> Rect {
+        let [area] = Layout::horizontal([Constraint::Percentage(60)])
+            .flex(Flex::Center)
+            .areas(area);
+        let [area] = Layout::vertical([Constraint::Length(9)])
+            .flex(Flex::Center)
+            .areas(area);
+        area
+    }
+}
+
+impl MockComponent for ErrorDialog {
+    fn view(&mut self, frame: &mut Frame, area: Rect) {
+        let Some(error) = self.error.lock().unwrap().clone() else {
+            return;
+        };
+
+        let text = Text::from(format!("{}\n\nPress Enter or Esc to go back", error));
+        let dialog = Paragraph::new(text).wrap(Wrap { trim: false }).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Red))
+                .title("Error"),
+        );
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(dialog, area);
+    }
+
+    fn query(&self, _attr: Attribute) -> Option<AttrValue> {
+        None
+    }
+
+    fn attr(&mut self, _attr: Attribute, _value: AttrValue) {}
+
+    fn state(&self) -> State {
+        State::None
+    }
+
+    fn perform(&mut self, _cmd: Cmd) -> CmdResult {
+        CmdResult::None
+    }
+}
+
+impl Component<Msg, NoUserEvent> for ErrorDialog {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter | Key::Esc,
+                ..
+            }) => Some(Msg::ErrorDialogClose),
+            _ => None,
+        }
+    }
+}
@@ -1,23 +1,20 @@
 use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
 use tuirealm::command::CmdResult;
 use tuirealm::event::Key;
-
-pub type LogBuffer = Arc<Mutex<Vec<String>>>;
+pub use zexcavator_lib::sync::{LogBuffer, new_log_buffer};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum SyncSource {
-    WalletFile(PathBuf),
+    WalletFile {
+        path: PathBuf,
+        password: Option<String>,
+    },
     Mnemonic {
         mnemonic: String,
         birthday: Option<u32>,
     },
 }
 
-pub fn new_log_buffer() -> LogBuffer {
-    Arc::new(Mutex::new(Vec::new()))
-}
-
 pub struct LogViewer {
     logs: LogBuffer,
 }
@@ -0,0 +1,79 @@
+use tui_realm_stdlib::Input;
+use tuirealm::command::{Cmd, CmdResult, Direction, Position};
+use tuirealm::{
+    Component, Event, MockComponent, NoUserEvent,
+    event::{Key, KeyEvent},
+};
+use tuirealm::{State, StateValue};
+
+use crate::Msg;
+
+/// Inputs of the mnemonic repair screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepairField {
+    Phrase,
+    WalletPath,
+}
+
+#[derive(MockComponent)]
+pub struct RepairInput {
+    component: Input,
+    field: RepairField,
+}
+
+impl RepairInput {
+    pub fn new(field: RepairField, initial_text: String, label: String) -> Self {
+        Self {
+            component: Input::default()
+                .input_type(tuirealm::props::InputType::Text)
+                .value(initial_text)
+                .title(label, tuirealm::props::Alignment::Left),
+            field,
+        }
+    }
+}
+
+impl Component<Msg, NoUserEvent> for RepairInput {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        let cmd = match ev {
+            Event::Keyboard(KeyEvent {
+                code: Key::Left, ..
+            }) => self.perform(Cmd::Move(Direction::Left)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Right, ..
+            }) => self.perform(Cmd::Move(Direction::Right)),
+            Event::Keyboard(KeyEvent {
+                code: Key::Home, ..
+            }) => self.perform(Cmd::GoTo(Position::Begin)),
+            Event::Keyboard(KeyEvent { code: Key::End, .. }) => {
+                self.perform(Cmd::GoTo(Position::End))
+            }
+            Event::Keyboard(KeyEvent { code: Key::Tab, .. }) => {
+                return Some(Msg::RepairInputBlur(self.field));
+            } // Focus lost
+            Event::Keyboard(KeyEvent {
+                code: Key::Delete, ..
+            }) => self.perform(Cmd::Cancel),
+            Event::Keyboard(KeyEvent {
+                code: Key::Backspace,
+                ..
+            }) => self.perform(Cmd::Delete),
+            Event::Keyboard(KeyEvent {
+                code: Key::Char(ch),
+                ..
+            }) => self.perform(Cmd::Type(ch)),
+            Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => return Some(Msg::Start),
+            Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => return Some(Msg::RepairMnemonicSubmit),
+            _ => CmdResult::None,
+        };
+
+        match cmd {
+            CmdResult::Changed(State::One(StateValue::String(s))) => {
+                Some(Msg::RepairInputChanged(self.field, s))
+            }
+            _ => None,
+        }
+    }
+}
@@ -5,6 +5,8 @@
 use std::io;
 
 use components::log_viewer::SyncSource;
+use components::repair_input::RepairField;
+use components::settings_input::SettingsField;
 use tuirealm::application::PollStrategy;
 use tuirealm::ratatui::crossterm::event::DisableMouseCapture;
 use tuirealm::ratatui::crossterm::execute;
@@ -14,7 +16,6 @@ use tuirealm::{AttrValue, Attribute, Update};
 mod app;
 mod components;
 mod views;
-mod walletparsers;
 use app::model::Model;
 use zingolib::wallet::balance::AccountBalance;
 mod constants;
@@ -30,9 +31,19 @@ pub enum Msg {
     StartSync(SyncSource),
     BirthdayInputChanged(String),
     BirthdayInputBlur,
+    PasswordInputChanged(String),
+    PasswordInputBlur,
     FromPathSubmitBlur,
     FromMnemonicSubmitBlur,
     FromPathInputBlur,
+    RepairInputChanged(RepairField, String),
+    RepairInputBlur(RepairField),
+    RepairMnemonicSubmit,
+    RepairMnemonicSubmitBlur,
+    SettingsInputChanged(SettingsField, String),
+    SettingsInputBlur(SettingsField),
+    SettingsSave,
+    SettingsSaveBlur,
     MenuSelected(String),
     MenuCursorMove(usize),
     FromMnemonicSubmit,
@@ -41,6 +52,11 @@ pub enum Msg {
     InitializeLightClient,
     BalanceReady(AccountBalance),
     FetchBalance,
+    SendPropose(String),
+    SendConfirm,
+    SendRefresh,
+    SendShielded,
+    ErrorDialogClose,
     None,
 }
 
@@ -63,9 +79,19 @@ pub enum Id {
     ZecwalletView,
     ZecwalletMenu,
     ZecwalletFromPath,
+    ZecwalletFromPathPassword,
     ZecwalletFromMnemonic,
     ZecwalletFromPathButton,
     ZecwalletFromMnemonicButton,
+    RepairPhraseInput,
+    RepairWalletInput,
+    RepairMnemonicButton,
+    RepairResult,
+    SettingsNetwork,
+    SettingsServer,
+    SettingsFallbackServers,
+    SettingsSaveButton,
+    SettingsStatus,
     SyncLog,
     ProgressBar,
     ExportView,
@@ -74,6 +100,7 @@ pub enum Id {
     ExportZewif,
     ExportSend,
     ExportZingolib,
+    ErrorDialog,
 }
 
 #[tokio::main]
@@ -4,6 +4,7 @@ use crate::{Id, Msg};
 
 pub mod export;
 pub mod main_menu;
+pub mod settings;
 pub mod sync;
 pub mod zecwallet;
 
@@ -2,7 +2,6 @@ pub mod send;
 pub mod zewif;
 pub mod zingolib;
 
-use std::num::NonZero;
 use std::sync::Arc;
 
 use ::zingolib::lightclient::LightClient;
@@ -12,6 +11,10 @@ use tuirealm::command::CmdResult;
 use tuirealm::ratatui::layout::{Constraint, Direction, Layout};
 use tuirealm::ratatui::widgets::{Block, Borders, Paragraph};
 use tuirealm::{Component, Frame, MockComponent, NoUserEvent, State};
+use zexcavator_lib::sync::{
+    IMPORTED_KEYS_NOTE, ImportedKeyBalance, account_balances, format_zec, imported_total,
+    total_balance,
+};
 
 use crate::Msg;
 use crate::app::model::{HasScreenAndQuit, Screen};
@@ -23,6 +26,7 @@ use crate::components::menu::{Menu, MenuOptions};
 pub enum ExportOptions {
     Zingolib,
     ZeWIF,
+    ZeWIFWatchOnly,
     Send,
     Cancel,
 }
@@ -32,14 +36,21 @@ impl MenuOptions for ExportOptions {
     where
         Self: Sized,
     {
-        vec![Self::Zingolib, Self::ZeWIF, Self::Send, Self::Cancel]
+        vec![
+            Self::Zingolib,
+            Self::ZeWIF,
+            Self::ZeWIFWatchOnly,
+            Self::Send,
+            Self::Cancel,
+        ]
     }
 
     fn label(&self) -> &'static str {
         match self {
             Self::Zingolib => "Zingolib",
-            Self::ZeWIF => "ZeWIF (WARNING: experimental. Only exports mnemonic phrase)",
-            Self::Send => "Send (Not implemented)",
+            Self::ZeWIF => "ZeWIF (WARNING: experimental)",
+            Self::ZeWIFWatchOnly => "ZeWIF watch-only (no seed or spending keys)",
+            Self::Send => "Send (sweep all funds to an address)",
             Self::Cancel => "Cancel",
         }
     }
@@ -48,7 +59,9 @@ impl MenuOptions for ExportOptions {
 #[derive(Debug, Clone)]
 pub struct ExportView {
     pub light_client: Arc<RwLock<Option<LightClient>>>,
-    pub balance: Arc<RwLock<Option<AccountBalance>>>,
+    // Balance of each synced HD account, indexed by account number
+    pub balance: Arc<RwLock<Option<Vec<AccountBalance>>>>,
+    pub imported_balances: Arc<RwLock<Vec<ImportedKeyBalance>>>,
     pub menu: Menu<ExportOptions>,
 }
 
@@ -57,20 +70,15 @@ impl ExportView {
         Self {
             light_client,
             balance: Arc::new(RwLock::new(None)),
+            imported_balances: Arc::new(RwLock::new(Vec::new())),
             menu: Menu::new("Choose an export option"),
         }
     }
 
-    pub async fn load_balance(&self) -> Option<AccountBalance> {
+    pub async fn load_balance(&self, no_of_accounts: u32) -> Option<Vec<AccountBalance>> {
         let mut client_guard = self.light_client.write().await;
         let client = client_guard.as_mut()?;
-        let b = client
-            .wallet
-            .lock()
-            .await
-            .account_balance(zip32::AccountId::try_from(0).unwrap())
-            .await
-            .unwrap();
+        let b = account_balances(client, no_of_accounts).await.ok()?;
         self.balance.try_write().unwrap().replace(b.clone());
         drop(client_guard);
         Some(b)
@@ -79,34 +87,57 @@ impl ExportView {
 
 impl MockComponent for ExportView {
     fn view(&mut self, frame: &mut Frame, area: tuirealm::ratatui::prelude::Rect) {
+        let balances = self.balance.try_read().ok().and_then(|guard| guard.clone());
+        let imported = self
+            .imported_balances
+            .try_read()
+            .map(|guard| guard.clone())
+            .unwrap_or_default();
+
+        // Show balance summary, with a breakdown per account when there is more than one
+        let lines = match &balances {
+            Some(balances) => {
+                let total = balances.iter().map(total_balance).sum::<u64>();
+                let mut lines = if imported.is_empty() {
+                    vec![format!("Total ZEC found: {}", format_zec(total))]
+                } else {
+                    let imported = imported_total(&imported);
+                    vec![format!(
+                        "Total ZEC found: {}, {} of it view-only",
+                        format_zec(total + imported),
+                        format_zec(imported)
+                    )]
+                };
+                if balances.len() > 1 || !imported.is_empty() {
+                    lines.extend(balances.iter().enumerate().map(|(i, balance)| {
+                        format!(
+                            "  Account {} (HD index {}): {} ZEC",
+                            i + 1,
+                            i,
+                            format_zec(total_balance(balance))
+                        )
+                    }));
+                }
+                lines.extend(imported.iter().map(|k| format!("  {}", k)));
+                if !imported.is_empty() {
+                    lines.push(IMPORTED_KEYS_NOTE.to_string());
+                }
+                lines
+            }
+            None => vec!["Loading balance...".to_string()],
+        };
+
         let chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Length(3), Constraint::Min(1)])
+            .constraints([
+                Constraint::Length(lines.len() as u16 + 2),
+                Constraint::Min(1),
+            ])
             .split(area);
 
-        // Show balance summary
-        if self.balance.try_read().unwrap().is_some() {
-            let balance;
-            {
-                let balance_guard = self.balance.try_read().unwrap();
-                balance = balance_guard.clone().unwrap();
-            }
-
-            let final_balance = balance.total_transparent_balance.unwrap()
-                + balance.total_sapling_balance.unwrap()
-                + balance.total_orchard_balance.unwrap();
-            let balance_in_zec = final_balance.unwrap() / NonZero::new(10u64.pow(8)).unwrap();
-
-            let text = format!("Total ZEC found: {:}", balance_in_zec.into_u64());
-            let para =
-                Paragraph::new(text).block(Block::default().borders(Borders::ALL).title("Balance"));
-            frame.render_widget(para, chunks[0]);
-        } else {
-            let text = "Loading balance...".to_string();
-            let para =
-                Paragraph::new(text).block(Block::default().borders(Borders::ALL).title("Balance"));
-            frame.render_widget(para, chunks[0]);
-        }
+        let para = Paragraph::new(lines.join("\n"))
+            .block(Block::default().borders(Borders::ALL).title("Balance"));
+        frame.render_widget(para, chunks[0]);
 
         self.menu.view(frame, chunks[1]);
     }
@@ -149,7 +180,7 @@ where
                             model.navigate_to(Screen::ExportZingolib);
                             todo!()
                         }
-                        ExportOptions::ZeWIF => {
+                        ExportOptions::ZeWIF | ExportOptions::ZeWIFWatchOnly => {
                             model.navigate_to(Screen::ExportZewif);
                         }
                         ExportOptions::Send => {
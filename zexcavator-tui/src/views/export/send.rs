@@ -1,66 +1,391 @@
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use tokio::sync::RwLock;
+use tui_realm_stdlib::Input;
+use tuirealm::command::{Cmd, CmdResult, Direction as CmdDirection, Position};
 use tuirealm::event::{Key, KeyEvent};
+use tuirealm::props::{Alignment, InputType};
 use tuirealm::ratatui::layout::{Constraint, Direction, Layout};
-use tuirealm::ratatui::widgets::{Block, Borders, Paragraph};
-use tuirealm::{Component, Frame, MockComponent, NoUserEvent, State};
+use tuirealm::ratatui::text::Text;
+use tuirealm::ratatui::widgets::{Block, Borders, Paragraph, Wrap};
+use tuirealm::{Component, Frame, MockComponent, NoUserEvent, State, StateValue};
+use zexcavator_lib::sweep::{
+    SweepFailure, SweepKind, SweepResult, SweepStep, SweepTransaction, TxId, propose_sweep,
+    refresh_statuses, send_shielded, send_sweep, shielding_accounts, transaction_statuses,
+};
+use zexcavator_lib::sync::format_zec;
 use zingolib::lightclient::LightClient;
-use zingolib::wallet::balance::AccountBalance;
 
 use crate::Msg;
 use crate::app::model::HasScreenAndQuit;
 use crate::components::HandleMessage;
 
+/// Progress of the sweep flow
+#[derive(Debug, Clone, Default)]
+pub enum SendState {
+    /// Waiting for the destination address
+    #[default]
+    Address,
+    Proposing,
+    /// Waiting for the user to confirm the proposal
+    Preview {
+        address: String,
+        steps: Vec<SweepStep>,
+    },
+    Sending,
+    Sent {
+        address: String,
+        txids: Vec<TxId>,
+        transactions: Vec<SweepTransaction>,
+        /// Accounts whose shielded funds are sent once the shielding is confirmed
+        pending: Vec<u32>,
+        refreshing: bool,
+        /// Why sending the last transactions failed
+        error: Option<String>,
+    },
+    Failed(String),
+}
+
 #[derive(Debug, Clone)]
 pub struct ExportSendView {
-    // pub light_client: Arc<RwLock<Option<LightClient>>>,
-    pub balance: Arc<RwLock<Option<AccountBalance>>>,
+    pub light_client: Arc<RwLock<Option<LightClient>>>,
+    pub state: Arc<Mutex<SendState>>,
 }
 
 impl ExportSendView {
-    pub fn new(_light_client: Arc<RwLock<Option<LightClient>>>) -> Self {
+    pub fn new(light_client: Arc<RwLock<Option<LightClient>>>) -> Self {
         Self {
-            balance: Arc::new(RwLock::new(None)),
+            light_client,
+            state: Arc::new(Mutex::new(SendState::default())),
+        }
+    }
+
+    /// Forgets the sweep, for a new wallet
+    pub fn reset(&self) {
+        *self.state.lock().unwrap() = SendState::default();
+    }
+
+    /// Builds the sweep proposal of the first `no_of_accounts` accounts
+    pub async fn do_propose(&self, address: String, no_of_accounts: u32) {
+        *self.state.lock().unwrap() = SendState::Proposing;
+
+        let mut guard = self.light_client.write().await;
+        let state = match guard.as_mut() {
+            Some(lc) => match propose_sweep(lc, &address, no_of_accounts).await {
+                Ok(steps) => SendState::Preview { address, steps },
+                Err(e) => SendState::Failed(format!("Error: {}", e)),
+            },
+            None => SendState::Failed("Error: no client".to_string()),
+        };
+        *self.state.lock().unwrap() = state;
+    }
+
+    /// Sends the previewed sweep
+    pub async fn do_send(&self) {
+        let (address, steps) = match &*self.state.lock().unwrap() {
+            SendState::Preview { address, steps } => (address.clone(), steps.clone()),
+            _ => return,
+        };
+        *self.state.lock().unwrap() = SendState::Sending;
+
+        let mut guard = self.light_client.write().await;
+        let state = match guard.as_mut() {
+            Some(lc) => match send_sweep(lc, &address, &steps).await {
+                // Nothing was broadcast, the sweep can be retried from the start
+                Ok(SweepResult {
+                    txids,
+                    failed: Some(failure),
+                    ..
+                }) if txids.is_empty() => SendState::Failed(Self::failure_text(&failure)),
+                Ok(result) => SendState::Sent {
+                    address,
+                    transactions: transaction_statuses(lc, &result.txids).await,
+                    pending: shielding_accounts(&result.sent),
+                    refreshing: false,
+                    error: result.failed.as_ref().map(Self::failure_text),
+                    txids: result.txids,
+                },
+                Err(e) => SendState::Failed(format!("Error: {}", e)),
+            },
+            None => SendState::Failed("Error: no client".to_string()),
+        };
+        *self.state.lock().unwrap() = state;
+    }
+
+    /// Syncs the wallet to update the confirmation status of the sent transactions
+    pub async fn do_refresh(&self) {
+        let txids = match &mut *self.state.lock().unwrap() {
+            SendState::Sent {
+                txids, refreshing, ..
+            } if !*refreshing => {
+                *refreshing = true;
+                txids.clone()
+            }
+            _ => return,
+        };
+
+        let mut guard = self.light_client.write().await;
+        let Some(lc) = guard.as_mut() else {
+            return;
+        };
+        // On failure the previous statuses are kept
+        let refreshed = refresh_statuses(lc, &txids).await;
+
+        if let SendState::Sent {
+            transactions,
+            refreshing,
+            ..
+        } = &mut *self.state.lock().unwrap()
+        {
+            if let Ok(refreshed) = refreshed {
+                *transactions = refreshed;
+            }
+            *refreshing = false;
+        }
+    }
+
+    /// Sends the funds shielded by the sweep, once the sweep transactions are confirmed
+    pub async fn do_send_shielded(&self) {
+        let (address, pending, txids) = match &mut *self.state.lock().unwrap() {
+            SendState::Sent {
+                address,
+                txids,
+                pending,
+                refreshing,
+                ..
+            } if !*refreshing && !pending.is_empty() => {
+                *refreshing = true;
+                (address.clone(), pending.clone(), txids.clone())
+            }
+            _ => return,
+        };
+
+        let mut guard = self.light_client.write().await;
+        let Some(lc) = guard.as_mut() else {
+            return;
+        };
+        let sent = send_shielded(lc, &address, &pending, &txids).await;
+        let sent = match sent {
+            Ok(result) => {
+                let all: Vec<TxId> = txids.into_iter().chain(result.txids).collect();
+                let swept: Vec<u32> = result.sent.iter().map(|s| s.account).collect();
+                let failure = result.failed.as_ref().map(Self::failure_text);
+                Ok((transaction_statuses(lc, &all).await, all, swept, failure))
+            }
+            Err(e) => Err(e),
+        };
+
+        if let SendState::Sent {
+            txids,
+            transactions,
+            pending,
+            refreshing,
+            error,
+            ..
+        } = &mut *self.state.lock().unwrap()
+        {
+            match sent {
+                Ok((statuses, all, swept, failure)) => {
+                    *transactions = statuses;
+                    *txids = all;
+                    // Accounts sent before a failure are not sent again
+                    pending.retain(|account| !swept.contains(account));
+                    *error = failure;
+                }
+                Err(e) => *error = Some(format!("Error: {}", e)),
+            }
+            *refreshing = false;
         }
     }
+
+    fn failure_text(failure: &SweepFailure) -> String {
+        let action = match failure.kind {
+            SweepKind::Shield => "shielding",
+            SweepKind::SendAll => "sending",
+        };
+        format!(
+            "Error {} the funds of account {}: {}",
+            action, failure.account, failure.error
+        )
+    }
+
+    fn preview_text(steps: &[SweepStep]) -> String {
+        let mut lines = vec![];
+        for step in steps {
+            let action = match step.kind {
+                SweepKind::Shield => "Shield transparent funds",
+                SweepKind::SendAll => "Send shielded funds",
+            };
+            lines.push(format!("Account {}: {}", step.account, action));
+            lines.push(format!("  Amount: {} ZEC", format_zec(step.amount)));
+            lines.push(format!("  Fee (ZIP-317): {} ZEC", format_zec(step.fee)));
+            lines.push(format!("  Pools: {}", step.pools.join(", ")));
+        }
+
+        let amount: u64 = steps
+            .iter()
+            .filter(|s| s.kind == SweepKind::SendAll)
+            .map(|s| s.amount)
+            .sum();
+        let fee: u64 = steps.iter().map(|s| s.fee).sum();
+        lines.push(String::new());
+        lines.push(format!("Total sent: {} ZEC", format_zec(amount)));
+        lines.push(format!("Total fees: {} ZEC", format_zec(fee)));
+        if !shielding_accounts(steps).is_empty() {
+            lines.push(
+                "Transparent funds are only shielded now. They are sent in a second step, once the shielding transaction is confirmed."
+                    .to_string(),
+            );
+        }
+        lines.push(String::new());
+        lines.push("Press Enter to send, Esc to cancel.".to_string());
+        lines.join("\n")
+    }
 }
 
-impl MockComponent for ExportSendView {
+/// Address input and sweep progress of the send screen
+pub struct ExportSendForm {
+    view: ExportSendView,
+    address: Input,
+}
+
+impl ExportSendForm {
+    pub fn new(view: ExportSendView) -> Self {
+        Self {
+            view,
+            address: Input::default()
+                .input_type(InputType::Text)
+                .title("Destination address", Alignment::Left),
+        }
+    }
+}
+
+impl MockComponent for ExportSendForm {
     fn view(&mut self, frame: &mut Frame, area: tuirealm::ratatui::prelude::Rect) {
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .constraints([Constraint::Length(3), Constraint::Min(1)])
             .split(area);
 
-        // Show balance summary
-        if self.balance.try_read().unwrap().is_some() {
-            let text = format!("{:?}", self.balance.try_read().unwrap().as_ref().unwrap());
-            let para =
-                Paragraph::new(text).block(Block::default().borders(Borders::ALL).title("Balance"));
-            frame.render_widget(para, chunks[0]);
-        }
+        self.address.view(frame, chunks[0]);
+
+        let text = match &*self.view.state.lock().unwrap() {
+            SendState::Address => {
+                "Enter the address to sweep all funds to and press Enter.".to_string()
+            }
+            SendState::Proposing => "Building proposal...".to_string(),
+            SendState::Preview { steps, .. } => ExportSendView::preview_text(steps),
+            SendState::Sending => "Sending transactions...".to_string(),
+            SendState::Sent {
+                transactions,
+                pending,
+                refreshing,
+                error,
+                ..
+            } => {
+                let mut lines: Vec<String> = transactions
+                    .iter()
+                    .map(|tx| format!("{} ({})", tx.txid, tx.status))
+                    .collect();
+                lines.push(String::new());
+                if !pending.is_empty() {
+                    let accounts: Vec<String> = pending.iter().map(|a| a.to_string()).collect();
+                    lines.push(format!(
+                        "The transparent funds of account(s) {} were shielded but not swept yet.",
+                        accounts.join(", ")
+                    ));
+                    lines.push(
+                        "Once the transactions are confirmed, press s to send them.".to_string(),
+                    );
+                }
+                if let Some(error) = error {
+                    lines.push(error.clone());
+                }
+                lines.push(if *refreshing {
+                    "Refreshing...".to_string()
+                } else {
+                    "Press r to refresh the confirmation status.".to_string()
+                });
+                lines.push(
+                    "Esc goes back, the sweep is kept until another wallet is synced.".to_string(),
+                );
+                lines.join("\n")
+            }
+            SendState::Failed(e) => format!("{}\n\nPress Enter to try again.", e),
+        };
+
+        let para = Paragraph::new(Text::from(text))
+            .block(Block::default().borders(Borders::ALL).title("Send"))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(para, chunks[1]);
     }
 
-    fn query(&self, _attr: tuirealm::Attribute) -> Option<tuirealm::AttrValue> {
-        todo!()
+    fn query(&self, attr: tuirealm::Attribute) -> Option<tuirealm::AttrValue> {
+        self.address.query(attr)
     }
 
-    fn attr(&mut self, _attr: tuirealm::Attribute, _value: tuirealm::AttrValue) {}
+    fn attr(&mut self, attr: tuirealm::Attribute, value: tuirealm::AttrValue) {
+        self.address.attr(attr, value)
+    }
 
     fn state(&self) -> State {
-        todo!()
+        self.address.state()
     }
 
-    fn perform(&mut self, _cmd: tuirealm::command::Cmd) -> tuirealm::command::CmdResult {
-        todo!()
+    fn perform(&mut self, cmd: tuirealm::command::Cmd) -> tuirealm::command::CmdResult {
+        self.address.perform(cmd)
     }
 }
 
-impl Component<Msg, NoUserEvent> for ExportSendView {
+impl Component<Msg, NoUserEvent> for ExportSendForm {
     fn on(&mut self, ev: tuirealm::Event<NoUserEvent>) -> Option<Msg> {
+        let state = self.view.state.lock().unwrap().clone();
+        let editable = matches!(state, SendState::Address | SendState::Failed(_));
+
         match ev {
-            tuirealm::Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => Some(Msg::GoToResult),
+            tuirealm::Event::Keyboard(KeyEvent { code: Key::Esc, .. }) => {
+                // Broadcast transactions and the pending second pass are kept, so the
+                // sweep can be finished when coming back
+                if !matches!(state, SendState::Sending | SendState::Sent { .. }) {
+                    self.view.reset();
+                }
+                Some(Msg::GoToResult)
+            }
+            tuirealm::Event::Keyboard(KeyEvent {
+                code: Key::Enter, ..
+            }) => match state {
+                SendState::Address | SendState::Failed(_) => match self.address.state() {
+                    State::One(StateValue::String(address)) if !address.trim().is_empty() => {
+                        Some(Msg::SendPropose(address))
+                    }
+                    _ => None,
+                },
+                SendState::Preview { .. } => Some(Msg::SendConfirm),
+                _ => None,
+            },
+            tuirealm::Event::Keyboard(KeyEvent {
+                code: Key::Char('r'),
+                ..
+            }) if matches!(state, SendState::Sent { .. }) => Some(Msg::SendRefresh),
+            tuirealm::Event::Keyboard(KeyEvent {
+                code: Key::Char('s'),
+                ..
+            }) if matches!(&state, SendState::Sent { pending, .. } if !pending.is_empty()) => {
+                Some(Msg::SendShielded)
+            }
+            tuirealm::Event::Keyboard(KeyEvent { code, .. }) if editable => {
+                match code {
+                    Key::Left => self.perform(Cmd::Move(CmdDirection::Left)),
+                    Key::Right => self.perform(Cmd::Move(CmdDirection::Right)),
+                    Key::Home => self.perform(Cmd::GoTo(Position::Begin)),
+                    Key::End => self.perform(Cmd::GoTo(Position::End)),
+                    Key::Delete => self.perform(Cmd::Cancel),
+                    Key::Backspace => self.perform(Cmd::Delete),
+                    Key::Char(ch) => self.perform(Cmd::Type(ch)),
+                    _ => CmdResult::None,
+                };
+                Some(Msg::None)
+            }
             _ => None,
         }
     }
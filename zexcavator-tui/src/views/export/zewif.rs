@@ -1,11 +1,5 @@
-use std::fs;
-use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
-use anyhow::{Context, Ok};
-use bc_envelope::Envelope;
-use bc_envelope::prelude::CBOREncodable;
-use chrono::Utc;
 use tokio::sync::RwLock;
 use tuirealm::command::CmdResult;
 use tuirealm::event::{Key, KeyEvent};
@@ -13,8 +7,8 @@ use tuirealm::ratatui::layout::{Constraint, Direction, Layout};
 use tuirealm::ratatui::text::Text;
 use tuirealm::ratatui::widgets::{Block, Borders, Paragraph, Wrap};
 use tuirealm::{Component, Frame, MockComponent, NoUserEvent, State};
-use zewif::{Bip39Mnemonic, BlockHeight, SeedMaterial, Zewif, ZewifWallet};
-use zingolib::grpc_connector::get_latest_block;
+use zexcavator_lib::export::save_zewif;
+use zexcavator_lib::parser::Wallet;
 use zingolib::lightclient::LightClient;
 
 use crate::Msg;
@@ -22,80 +16,42 @@ use crate::Msg;
 #[derive(Debug, Clone)]
 pub struct ExportZewifView {
     pub light_client: Arc<RwLock<Option<LightClient>>>,
+    /// Wallet file the client was synced from, see [`zexcavator_lib::sync::Syncer`]
+    pub source_wallet: Arc<Mutex<Option<Wallet>>>,
     pub saved_path: Arc<Mutex<Option<String>>>,
+    /// Elide the seed and spending keys from the export
+    pub watch_only: Arc<Mutex<bool>>,
 }
 
 impl ExportZewifView {
-    pub fn new(light_client: Arc<RwLock<Option<LightClient>>>) -> Self {
+    pub fn new(
+        light_client: Arc<RwLock<Option<LightClient>>>,
+        source_wallet: Arc<Mutex<Option<Wallet>>>,
+    ) -> Self {
         Self {
             light_client,
+            source_wallet,
             saved_path: Arc::new(Mutex::new(None)),
+            watch_only: Arc::new(Mutex::new(false)),
         }
     }
 
-    /// Converts the LightWallet into a ZeWIF-compatible format and saves it to disk
+    /// Converts the LightWallet and its source wallet file into a ZeWIF-compatible
+    /// format and saves it to disk
     pub async fn do_save(&self) -> anyhow::Result<String> {
         let guard = self.light_client.read().await;
         let lc = guard.as_ref().ok_or_else(|| anyhow::anyhow!("no client"))?;
+        let source = self.source_wallet.lock().unwrap().clone();
 
-        let wallet_guard = lc.wallet.lock().await;
-        let mnemonic = wallet_guard.mnemonic().cloned();
-        drop(wallet_guard);
+        let watch_only = *self.watch_only.lock().unwrap();
 
-        let export_height = get_latest_block(lc.get_server_uri()).await.unwrap().height as u32;
-        drop(guard);
+        let paths = save_zewif(lc, source.as_ref(), watch_only, &[]).await?;
 
-        let m = mnemonic.unwrap();
-
-        let path = ExportZewifView::export_to_zewif(Some(m), export_height);
-
-        Ok(path.unwrap().to_string_lossy().to_string())
-    }
-
-    /// Inline implementation of zingolib's LichClient to ZeWIF conversion.
-    /// Eventually, this will be moved to the `zewif-zingolib` crate.
-    pub fn export_to_zewif(
-        mnemonic: Option<bip0039::Mnemonic>,
-        export_height: u32,
-    ) -> Result<PathBuf, anyhow::Error> {
-        let seed_material: Option<SeedMaterial> = match mnemonic {
-            Some(m) => {
-                let phrase = m.clone().into_phrase();
-
-                let zewif_bip39_mnemonic =
-                    Bip39Mnemonic::new(phrase, Some(zewif::MnemonicLanguage::English));
-                Some(SeedMaterial::Bip39Mnemonic(zewif_bip39_mnemonic))
-            }
-            None => None,
-        };
-
-        let mut zewif_wallet: ZewifWallet = ZewifWallet::new(zewif::Network::Main);
-
-        match seed_material {
-            Some(seed_material) => zewif_wallet.set_seed_material(seed_material),
-            None => panic!("no seed material"),
-        }
-
-        let zewif: Zewif = Zewif::new(BlockHeight::from_u32(export_height));
-
-        let mut export_dir = dirs::config_dir().context("could not locate config directory")?;
-        export_dir.push("zexcavator");
-        export_dir.push("exports");
-
-        fs::create_dir_all(&export_dir)
-            .with_context(|| format!("failed to create directory {:?}", export_dir))?;
-
-        // Save to path zexcavator-<timestamp>.zewif
-        let timestamp = Utc::now().format("%Y%m%d_%H%M%S").to_string();
-
-        let filename = format!("zexcavator-{}.zewif", timestamp);
-        let path = export_dir.join(filename);
-
-        // Convert the Zewif instance to an Envelope
-        let envelope = Envelope::from(zewif.clone());
-
-        std::fs::write(&path, envelope.to_cbor_data()).unwrap();
-        Ok(path)
+        Ok(paths
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect::<Vec<_>>()
+            .join("\n"))
     }
 }
 
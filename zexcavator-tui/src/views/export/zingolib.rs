@@ -1,8 +1,5 @@
-use std::fs;
 use std::sync::{Arc, Mutex};
 
-use anyhow::{Context, Ok};
-use chrono::Utc;
 use tokio::sync::RwLock;
 use tuirealm::command::CmdResult;
 use tuirealm::event::{Key, KeyEvent};
@@ -10,6 +7,7 @@ use tuirealm::ratatui::layout::{Constraint, Direction, Layout};
 use tuirealm::ratatui::text::Text;
 use tuirealm::ratatui::widgets::{Block, Borders, Paragraph, Wrap};
 use tuirealm::{Component, Frame, MockComponent, NoUserEvent, State};
+use zexcavator_lib::export::export_to_zingolib;
 use zingolib::lightclient::LightClient;
 
 use crate::Msg;
@@ -33,26 +31,7 @@ impl ExportZingolibView {
         let guard = self.light_client.read().await;
         let lc = guard.as_ref().ok_or_else(|| anyhow::anyhow!("no client"))?;
 
-        let mut buf = Vec::new();
-        {
-            let mut lw_guard = lc.wallet.lock().await;
-            let network = lw_guard.network;
-            lw_guard
-                .write(&mut buf, &network)
-                .context("failed to serialize LightWallet")?;
-        }
-
-        let mut export_dir = dirs::config_dir().context("could not locate config directory")?;
-        export_dir.push("zexcavator");
-        export_dir.push("exports");
-        export_dir.push("zingolib");
-
-        fs::create_dir_all(&export_dir).context("failed to create export directory")?;
-
-        let filename = format!("export-{}.dat", Utc::now().timestamp());
-        let path = export_dir.join(&filename);
-
-        fs::write(&path, &buf).context("failed to write export file")?;
+        let path = export_to_zingolib(lc).await?;
 
         Ok(path.to_string_lossy().into_owned())
     }
@@ -15,6 +15,7 @@ pub enum MainMenuOption {
     Zcashd,
     Ledger,
     Trezor,
+    Settings,
     Exit,
 }
 
@@ -25,6 +26,7 @@ impl MenuOptions for MainMenuOption {
             Self::Zcashd,
             Self::Ledger,
             Self::Trezor,
+            Self::Settings,
             Self::Exit,
         ]
     }
@@ -32,9 +34,10 @@ impl MenuOptions for MainMenuOption {
     fn label(&self) -> &'static str {
         match self {
             Self::Zecwallet => "Zecwallet",
-            Self::Zcashd => "zcashd (Not yet implemented)",
+            Self::Zcashd => "zcashd",
             Self::Ledger => "Ledger (Not yet implemented)",
             Self::Trezor => "Trezor (Not yet implemented)",
+            Self::Settings => "Settings",
             Self::Exit => "Exit",
         }
     }
@@ -88,6 +91,7 @@ where
                     match menu_item {
                         MainMenuOption::Zecwallet => model.navigate_to(Screen::ZecwalletInput),
                         MainMenuOption::Zcashd => model.navigate_to(Screen::ZcashdInput),
+                        MainMenuOption::Settings => model.navigate_to(Screen::Settings),
                         MainMenuOption::Ledger | MainMenuOption::Trezor | MainMenuOption::Exit => {
                             model.set_quit(true)
                         }
@@ -0,0 +1,227 @@
+use std::sync::{Arc, Mutex};
+
+use tuirealm::command::CmdResult;
+use tuirealm::event::Key;
+use tuirealm::props::BorderSides;
+use tuirealm::ratatui::layout::{Constraint, Direction, Layout, Rect};
+use tuirealm::ratatui::text::Text;
+use tuirealm::ratatui::widgets::{Block, Borders, Paragraph, Wrap};
+use tuirealm::{
+    Application, AttrValue, Attribute, Component, Event, Frame, MockComponent, NoUserEvent, State,
+};
+use zexcavator_lib::settings::{Network, SyncSettings};
+
+use crate::components::settings_input::{SettingsField, SettingsInput};
+use crate::constants::colors::ZINGO_GREEN;
+use crate::views::Renderable;
+use crate::{Id, Msg};
+
+pub struct SettingsView;
+
+impl SettingsView {
+    /// Mounts the settings inputs, filled with `settings`
+    pub fn mount(
+        app: &mut Application<Id, Msg, NoUserEvent>,
+        settings: &SyncSettings,
+        status: SettingsStatusView,
+    ) -> anyhow::Result<()> {
+        let inputs = [
+            (
+                Id::SettingsNetwork,
+                SettingsField::Network,
+                settings.network.to_string(),
+                "Network (mainnet, testnet or regtest)",
+            ),
+            (
+                Id::SettingsServer,
+                SettingsField::Server,
+                settings.server.clone(),
+                "Lightwalletd server",
+            ),
+            (
+                Id::SettingsFallbackServers,
+                SettingsField::FallbackServers,
+                settings.fallback_servers.join(","),
+                "Fallback servers, comma separated",
+            ),
+        ];
+        for (id, field, value, label) in inputs {
+            assert!(
+                app.mount(
+                    id,
+                    Box::new(SettingsInput::new(field, value, label.to_string())),
+                    Vec::default()
+                )
+                .is_ok()
+            );
+        }
+
+        // Mount save button
+        assert!(
+            app.mount(
+                Id::SettingsSaveButton,
+                Box::new(SaveButton::default()),
+                Vec::default()
+            )
+            .is_ok()
+        );
+
+        assert!(
+            app.mount(Id::SettingsStatus, Box::new(status), Vec::default())
+                .is_ok()
+        );
+        Ok(())
+    }
+
+    /// Builds the settings from the values typed in the inputs
+    pub fn read(
+        app: &Application<Id, Msg, NoUserEvent>,
+        current: &SyncSettings,
+    ) -> anyhow::Result<SyncSettings> {
+        let query = |id: &Id| {
+            app.query(id, Attribute::Text)
+                .ok()
+                .flatten()
+                .and_then(|v| v.as_string())
+                .unwrap_or_default()
+        };
+
+        let network: Network = query(&Id::SettingsNetwork)
+            .trim()
+            .parse()
+            .map_err(|e: String| anyhow::anyhow!(e))?;
+        let settings = SyncSettings {
+            network,
+            server: query(&Id::SettingsServer).trim().to_string(),
+            fallback_servers: query(&Id::SettingsFallbackServers)
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            max_sync_retries: current.max_sync_retries,
+        };
+        settings.validate()?;
+
+        Ok(settings)
+    }
+}
+
+impl Renderable for SettingsView {
+    fn render(app: &mut Application<Id, Msg, NoUserEvent>, f: &mut Frame) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Length(3),
+                Constraint::Length(3),
+                Constraint::Length(3),
+                Constraint::Min(3),
+            ])
+            .split(f.area());
+        app.view(&Id::SettingsNetwork, f, chunks[0]);
+        app.view(&Id::SettingsServer, f, chunks[1]);
+        app.view(&Id::SettingsFallbackServers, f, chunks[2]);
+        app.view(&Id::SettingsSaveButton, f, chunks[3]);
+        app.view(&Id::SettingsStatus, f, chunks[4]);
+    }
+}
+
+/// Shows the outcome of the last save
+#[derive(Debug, Clone, Default)]
+pub struct SettingsStatusView {
+    pub status: Arc<Mutex<Option<String>>>,
+}
+
+impl MockComponent for SettingsStatusView {
+    fn view(&mut self, frame: &mut Frame, area: Rect) {
+        let msg = self.status.lock().unwrap().clone().unwrap_or_default();
+
+        let para = Paragraph::new(Text::from(msg))
+            .block(Block::default().borders(Borders::ALL).title("Status"))
+            .wrap(Wrap { trim: false });
+
+        frame.render_widget(para, area);
+    }
+
+    fn query(&self, _attr: tuirealm::Attribute) -> Option<tuirealm::AttrValue> {
+        None
+    }
+
+    fn attr(&mut self, _attr: tuirealm::Attribute, _value: tuirealm::AttrValue) {}
+
+    fn state(&self) -> State {
+        State::None
+    }
+
+    fn perform(&mut self, _cmd: tuirealm::command::Cmd) -> tuirealm::command::CmdResult {
+        CmdResult::None
+    }
+}
+
+impl Component<Msg, NoUserEvent> for SettingsStatusView {
+    fn on(&mut self, _ev: Event<NoUserEvent>) -> Option<Msg> {
+        None
+    }
+}
+
+#[derive(Default)]
+pub struct SaveButton {
+    focused: bool,
+}
+
+impl MockComponent for SaveButton {
+    fn view(&mut self, frame: &mut Frame, area: Rect) {
+        let border_style = if self.focused {
+            tuirealm::ratatui::style::Style::default().fg(ZINGO_GREEN)
+        } else {
+            tuirealm::ratatui::style::Style::default()
+        };
+
+        let button = Paragraph::new(Text::raw("Save"))
+            .alignment(tuirealm::props::Alignment::Center)
+            .block(
+                Block::default()
+                    .borders(BorderSides::all())
+                    .border_style(border_style),
+            );
+
+        frame.render_widget(button, area);
+    }
+
+    fn query(&self, _attr: tuirealm::Attribute) -> Option<tuirealm::AttrValue> {
+        None
+    }
+
+    fn attr(&mut self, attr: tuirealm::Attribute, value: tuirealm::AttrValue) {
+        if attr == Attribute::Focus {
+            if let AttrValue::Flag(focus_flag) = value {
+                self.focused = focus_flag;
+            }
+        }
+    }
+
+    fn state(&self) -> State {
+        State::None
+    }
+
+    fn perform(&mut self, _cmd: tuirealm::command::Cmd) -> tuirealm::command::CmdResult {
+        CmdResult::None
+    }
+}
+
+impl Component<Msg, NoUserEvent> for SaveButton {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        if let Event::Keyboard(key) = ev {
+            match key.code {
+                Key::Enter => {
+                    return Some(Msg::SettingsSave);
+                }
+                Key::Tab => return Some(Msg::SettingsSaveBlur),
+                Key::Esc => return Some(Msg::Start),
+                _ => (),
+            }
+        }
+        None
+    }
+}
@@ -1,5 +1,6 @@
 pub mod from_mnemonic;
 pub mod from_path;
+pub mod repair_mnemonic;
 
 use tuirealm::ratatui::layout::{Constraint, Direction, Layout};
 use tuirealm::{Application, Frame, NoUserEvent};
@@ -17,6 +18,7 @@ pub enum ZecwalletMenuOption {
     Mnemonic,
     Path,
     Seed,
+    Repair,
     Back,
 }
 
@@ -25,7 +27,13 @@ impl MenuOptions for ZecwalletMenuOption {
     where
         Self: Sized,
     {
-        vec![Self::Mnemonic, Self::Path, Self::Seed, Self::Back]
+        vec![
+            Self::Mnemonic,
+            Self::Path,
+            Self::Seed,
+            Self::Repair,
+            Self::Back,
+        ]
     }
 
     fn label(&self) -> &'static str {
@@ -33,6 +41,7 @@ impl MenuOptions for ZecwalletMenuOption {
             Self::Mnemonic => "From Mnemonic",
             Self::Path => "From Path",
             Self::Seed => "From Seed (Not yet implemented)",
+            Self::Repair => "Repair Mnemonic",
             Self::Back => "Back",
         }
     }
@@ -79,6 +88,9 @@ where
                             model.navigate_to(Screen::ZecwalletFromMnemonic)
                         }
                         ZecwalletMenuOption::Seed => model.navigate_to(Screen::ZecwalletInput),
+                        ZecwalletMenuOption::Repair => {
+                            model.navigate_to(Screen::ZecwalletRepairMnemonic)
+                        }
                         ZecwalletMenuOption::Back => model.navigate_to(Screen::MainMenu),
                     }
                 }
@@ -12,6 +12,7 @@ use tuirealm::{
 };
 
 use crate::components::input::PathInput;
+use crate::components::password_input::PasswordInput;
 use crate::constants::colors::ZINGO_GREEN;
 use crate::views::Renderable;
 use crate::{Id, Msg};
@@ -34,7 +35,24 @@ impl Mountable for ZecwalletFromPath {
         assert!(
             app.mount(
                 Id::ZecwalletFromPath,
-                Box::new(PathInput::new(String::new(), "Wallet location".to_string())),
+                Box::new(PathInput::new(
+                    String::new(),
+                    "Wallet location (.dat, .db or .zewif)".to_string()
+                )),
+                Vec::default()
+            )
+            .is_ok()
+        );
+
+        // Mount password input
+        assert!(
+            app.mount(
+                Id::ZecwalletFromPathPassword,
+                Box::new(PasswordInput::new(
+                    String::new(),
+                    "Wallet password, zcashd passphrase or Zashi seed phrase (leave empty if not needed)"
+                        .to_string()
+                )),
                 Vec::default()
             )
             .is_ok()
@@ -58,10 +76,15 @@ impl Renderable for ZecwalletFromPath {
         let chunks = Layout::default()
             .direction(Direction::Vertical)
             .margin(1)
-            .constraints([Constraint::Percentage(20), Constraint::Length(3)])
+            .constraints([
+                Constraint::Percentage(20),
+                Constraint::Length(3),
+                Constraint::Length(3),
+            ])
             .split(f.area());
         app.view(&Id::ZecwalletFromPath, f, chunks[0]);
-        app.view(&Id::ZecwalletFromPathButton, f, chunks[1]);
+        app.view(&Id::ZecwalletFromPathPassword, f, chunks[1]);
+        app.view(&Id::ZecwalletFromPathButton, f, chunks[2]);
     }
 }
 
@@ -0,0 +1,213 @@
+use std::sync::{Arc, Mutex};
+
+use tuirealm::command::CmdResult;
+use tuirealm::event::Key;
+use tuirealm::props::BorderSides;
+use tuirealm::ratatui::layout::{Constraint, Direction, Layout, Rect};
+use tuirealm::ratatui::text::Text;
+use tuirealm::ratatui::widgets::{Block, Borders, Paragraph, Wrap};
+use tuirealm::{
+    Application, AttrValue, Attribute, Component, Event, Frame, MockComponent, NoUserEvent, State,
+};
+use zexcavator_lib::recovery::mnemonic::{
+    RepairOptions, addresses_from_wallet, filter_by_addresses, repair,
+};
+
+use crate::components::repair_input::{RepairField, RepairInput};
+use crate::constants::colors::ZINGO_GREEN;
+use crate::views::Renderable;
+use crate::{Id, Msg};
+
+use super::Mountable;
+
+/// Number of addresses derived per candidate phrase when checking a wallet file
+const ADDRESS_DEPTH: u32 = 5;
+
+#[derive(Default)]
+pub struct ZecwalletRepairMnemonic;
+
+impl Mountable for ZecwalletRepairMnemonic {
+    fn mount(app: &mut Application<Id, Msg, tuirealm::event::NoUserEvent>) -> anyhow::Result<()> {
+        // Mount phrase input
+        assert!(
+            app.mount(
+                Id::RepairPhraseInput,
+                Box::new(RepairInput::new(
+                    RepairField::Phrase,
+                    String::new(),
+                    "Damaged mnemonic (write unknown words as ?)".to_string()
+                )),
+                Vec::default()
+            )
+            .is_ok()
+        );
+
+        // Mount wallet path input
+        assert!(
+            app.mount(
+                Id::RepairWalletInput,
+                Box::new(RepairInput::new(
+                    RepairField::WalletPath,
+                    String::new(),
+                    "Wallet file to check addresses against (optional)".to_string()
+                )),
+                Vec::default()
+            )
+            .is_ok()
+        );
+
+        // Mount submit button
+        assert!(
+            app.mount(
+                Id::RepairMnemonicButton,
+                Box::new(SubmitButtonRepair::default()),
+                Vec::default()
+            )
+            .is_ok()
+        );
+        Ok(())
+    }
+}
+
+impl Renderable for ZecwalletRepairMnemonic {
+    fn render(app: &mut Application<Id, Msg, NoUserEvent>, f: &mut Frame) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Length(3),
+                Constraint::Length(3),
+                Constraint::Min(3),
+            ])
+            .split(f.area());
+        app.view(&Id::RepairPhraseInput, f, chunks[0]);
+        app.view(&Id::RepairWalletInput, f, chunks[1]);
+        app.view(&Id::RepairMnemonicButton, f, chunks[2]);
+        app.view(&Id::RepairResult, f, chunks[3]);
+    }
+}
+
+/// Shows the phrases found by a repair
+#[derive(Debug, Clone, Default)]
+pub struct RepairResultView {
+    pub result: Arc<Mutex<Option<String>>>,
+}
+
+impl RepairResultView {
+    /// Repairs `phrase`, keeping only the phrases matching the wallet at `wallet_path`
+    /// if one is given. Runs on the calling thread.
+    pub fn do_repair(phrase: &str, wallet_path: Option<&str>) -> Result<Vec<String>, String> {
+        let phrases = repair(phrase, &RepairOptions::default()).map_err(|e| e.to_string())?;
+
+        match wallet_path {
+            Some(path) => {
+                let (network, addresses) = addresses_from_wallet(path)
+                    .map_err(|e| format!("Can't read wallet addresses: {}", e))?;
+                Ok(filter_by_addresses(
+                    phrases,
+                    &addresses,
+                    ADDRESS_DEPTH,
+                    network,
+                ))
+            }
+            None => Ok(phrases),
+        }
+    }
+}
+
+impl MockComponent for RepairResultView {
+    fn view(&mut self, frame: &mut Frame, area: Rect) {
+        let msg = match &*self.result.lock().unwrap() {
+            Some(result) => result.clone(),
+            None => String::new(),
+        };
+
+        let para = Paragraph::new(Text::from(msg))
+            .block(Block::default().borders(Borders::ALL).title("Candidates"))
+            .wrap(Wrap { trim: false });
+
+        frame.render_widget(para, area);
+    }
+
+    fn query(&self, _attr: tuirealm::Attribute) -> Option<tuirealm::AttrValue> {
+        None
+    }
+
+    fn attr(&mut self, _attr: tuirealm::Attribute, _value: tuirealm::AttrValue) {}
+
+    fn state(&self) -> State {
+        State::None
+    }
+
+    fn perform(&mut self, _cmd: tuirealm::command::Cmd) -> tuirealm::command::CmdResult {
+        CmdResult::None
+    }
+}
+
+impl Component<Msg, NoUserEvent> for RepairResultView {
+    fn on(&mut self, _ev: Event<NoUserEvent>) -> Option<Msg> {
+        None
+    }
+}
+
+#[derive(Default)]
+pub struct SubmitButtonRepair {
+    focused: bool,
+}
+
+impl MockComponent for SubmitButtonRepair {
+    fn view(&mut self, frame: &mut Frame, area: Rect) {
+        let border_style = if self.focused {
+            tuirealm::ratatui::style::Style::default().fg(ZINGO_GREEN)
+        } else {
+            tuirealm::ratatui::style::Style::default()
+        };
+
+        let button = Paragraph::new(Text::raw("Repair"))
+            .alignment(tuirealm::props::Alignment::Center)
+            .block(
+                Block::default()
+                    .borders(BorderSides::all())
+                    .border_style(border_style),
+            );
+
+        frame.render_widget(button, area);
+    }
+
+    fn query(&self, _attr: tuirealm::Attribute) -> Option<tuirealm::AttrValue> {
+        None
+    }
+
+    fn attr(&mut self, attr: tuirealm::Attribute, value: tuirealm::AttrValue) {
+        if attr == Attribute::Focus {
+            if let AttrValue::Flag(focus_flag) = value {
+                self.focused = focus_flag;
+            }
+        }
+    }
+
+    fn state(&self) -> State {
+        State::None
+    }
+
+    fn perform(&mut self, _cmd: tuirealm::command::Cmd) -> tuirealm::command::CmdResult {
+        CmdResult::None
+    }
+}
+
+impl Component<Msg, NoUserEvent> for SubmitButtonRepair {
+    fn on(&mut self, ev: Event<NoUserEvent>) -> Option<Msg> {
+        if let Event::Keyboard(key) = ev {
+            match key.code {
+                Key::Enter => {
+                    return Some(Msg::RepairMnemonicSubmit);
+                }
+                Key::Tab => return Some(Msg::RepairMnemonicSubmitBlur),
+                Key::Esc => return Some(Msg::Start),
+                _ => (),
+            }
+        }
+        None
+    }
+}
@@ -1,4 +0,0 @@
-pub mod walletparsers;
-pub mod ywallet;
-pub mod zingolib;
-pub mod zwl;
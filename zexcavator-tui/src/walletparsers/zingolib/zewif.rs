@@ -1,5 +0,0 @@
-use zingolib::lightclient::LightClient;
-
-pub fn export_zewif(lc: &LightClient) {
-    todo!()
-}